@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use bitcoin::{Address, FeeRate, Network, OutPoint, Transaction, Txid};
+use esplora_client::{TxStatus, Utxo};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+#[derive(Debug)]
+pub enum ChainError {
+    Esplora(esplora_client::Error),
+    Rejected(String), // transaction rejected by the chain, with the reject reason
+    UnsupportedNetwork(Network),
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            ChainError::Esplora(err) => write!(f, "{}", err),
+            ChainError::Rejected(reason) => write!(f, "Transaction rejected: {}", reason),
+            ChainError::UnsupportedNetwork(network) => {
+                write!(f, "No esplora url known for network {}", network)
+            }
+        }
+    }
+}
+
+impl Error for ChainError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChainError::Esplora(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+pub trait ChainBackend: Send + Sync {
+    async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, ChainError>;
+
+    // None if the transaction is neither in the mempool nor mined
+    async fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, ChainError>;
+
+    // Txid of the transaction spending the output, None while the output is unspent
+    async fn get_spending_txid(&self, outpoint: &OutPoint) -> Result<Option<Txid>, ChainError>;
+
+    async fn get_height(&self) -> Result<u32, ChainError>;
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<(), ChainError>;
+
+    async fn get_address_utxo(&self, address: Address) -> Result<Vec<Utxo>, ChainError>;
+
+    // Fee rate expected to get a transaction confirmed within the number of blocks
+    async fn get_fee_rate(&self, confirmation_target: u16) -> Result<FeeRate, ChainError>;
+}
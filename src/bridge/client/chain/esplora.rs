@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use bitcoin::{Address, FeeRate, Network, OutPoint, Transaction, Txid};
+use esplora_client::{AsyncClient, Builder, TxStatus, Utxo};
+
+use super::base::{ChainBackend, ChainError};
+
+pub static BITCOIN_ESPLORA_URL: &str = "https://blockstream.info/api";
+pub static TESTNET_ESPLORA_URL: &str = "https://blockstream.info/testnet/api";
+pub static MUTINYNET_ESPLORA_URL: &str = "https://mutinynet.com/api";
+pub static REGTEST_ESPLORA_URL: &str = "http://localhost:3002";
+
+// Minimum relay fee rate, used when esplora has no estimate
+const MIN_SAT_PER_VB: f64 = 1.0;
+
+// Signet is served by mutinynet. Other signets or test networks need an explicit url, see
+// Esplora::with_url.
+pub fn get_esplora_url(network: Network) -> Result<&'static str, ChainError> {
+    match network {
+        Network::Bitcoin => Ok(BITCOIN_ESPLORA_URL),
+        Network::Testnet => Ok(TESTNET_ESPLORA_URL),
+        Network::Signet => Ok(MUTINYNET_ESPLORA_URL),
+        Network::Regtest => Ok(REGTEST_ESPLORA_URL),
+        _ => Err(ChainError::UnsupportedNetwork(network)),
+    }
+}
+
+pub struct Esplora {
+    client: AsyncClient,
+}
+
+impl Esplora {
+    pub fn new(network: Network) -> Result<Self, ChainError> {
+        Self::with_url(get_esplora_url(network)?)
+    }
+
+    pub fn with_url(url: &str) -> Result<Self, ChainError> {
+        Ok(Esplora {
+            client: Builder::new(url)
+                .build_async()
+                .map_err(ChainError::Esplora)?,
+        })
+    }
+}
+
+#[async_trait]
+impl ChainBackend for Esplora {
+    async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, ChainError> {
+        self.client
+            .get_tx_status(txid)
+            .await
+            .map_err(ChainError::Esplora)
+    }
+
+    async fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, ChainError> {
+        self.client.get_tx(txid).await.map_err(ChainError::Esplora)
+    }
+
+    async fn get_spending_txid(&self, outpoint: &OutPoint) -> Result<Option<Txid>, ChainError> {
+        Ok(self
+            .client
+            .get_output_status(&outpoint.txid, outpoint.vout as u64)
+            .await
+            .map_err(ChainError::Esplora)?
+            .and_then(|output_status| output_status.txid))
+    }
+
+    async fn get_height(&self) -> Result<u32, ChainError> {
+        self.client.get_height().await.map_err(ChainError::Esplora)
+    }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<(), ChainError> {
+        self.client.broadcast(tx).await.map_err(ChainError::Esplora)
+    }
+
+    async fn get_address_utxo(&self, address: Address) -> Result<Vec<Utxo>, ChainError> {
+        self.client
+            .get_address_utxo(address)
+            .await
+            .map_err(ChainError::Esplora)
+    }
+
+    // Esplora estimates sat/vB for a fixed set of targets, the estimate of the largest target not
+    // above the requested one is used.
+    async fn get_fee_rate(&self, confirmation_target: u16) -> Result<FeeRate, ChainError> {
+        let fee_estimates = self
+            .client
+            .get_fee_estimates()
+            .await
+            .map_err(ChainError::Esplora)?;
+        let sat_per_vb = fee_estimates
+            .iter()
+            .filter(|(target, _)| **target <= confirmation_target.max(1))
+            .max_by_key(|(target, _)| **target)
+            .map_or(MIN_SAT_PER_VB, |(_, sat_per_vb)| *sat_per_vb);
+
+        Ok(FeeRate::from_sat_per_kwu(
+            (sat_per_vb.max(MIN_SAT_PER_VB) * 250.0).ceil() as u64,
+        ))
+    }
+}
@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use bitcoin::{
+    absolute, relative, script, Address, Amount, FeeRate, OutPoint, Sequence, Transaction, TxIn,
+    TxOut, Txid, Witness,
+};
+use esplora_client::{TxStatus, Utxo, UtxoStatus};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::base::{ChainBackend, ChainError};
+
+struct MockTransaction {
+    tx: Transaction,
+    block_height: Option<u32>, // None while in the mempool
+}
+
+#[derive(Default)]
+struct MockChainState {
+    height: u32,
+    transactions: HashMap<Txid, MockTransaction>,
+    spent_outpoints: HashMap<OutPoint, Txid>, // spent output and the txid spending it
+    funding_count: i64,
+    fee_rate: Option<FeeRate>, // None for the minimum relay fee rate
+}
+
+// In-memory chain for tests. Broadcast transactions stay in the mempool until blocks are mined
+// with `mine_blocks`. Only input availability, amounts and timelocks are checked, scripts are not
+// executed (see `Simulator` for that). Clones share the same chain.
+#[derive(Clone, Default)]
+pub struct MockChain {
+    state: Arc<Mutex<MockChainState>>,
+}
+
+impl MockChain {
+    pub fn new() -> Self { Self::default() }
+
+    // Creates and mines a transaction paying `amount` to `address`, like a faucet would.
+    pub fn fund_address(&self, address: &Address, amount: Amount) -> OutPoint {
+        let mut state = self.state.lock().unwrap();
+
+        state.funding_count += 1;
+        let tx = Transaction {
+            version: bitcoin::transaction::Version(2),
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: script::Builder::new()
+                    .push_int(state.funding_count)
+                    .into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            }],
+            output: vec![TxOut {
+                value: amount,
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let txid = tx.compute_txid();
+
+        state.height += 1;
+        let block_height = Some(state.height);
+        state
+            .transactions
+            .insert(txid, MockTransaction { tx, block_height });
+
+        OutPoint { txid, vout: 0 }
+    }
+
+    // Mines `num_blocks` blocks, confirming every mempool transaction in the first one.
+    pub fn mine_blocks(&self, num_blocks: u32) {
+        let mut state = self.state.lock().unwrap();
+        if num_blocks == 0 {
+            return;
+        }
+
+        let block_height = state.height + 1;
+        for mock_transaction in state.transactions.values_mut() {
+            if mock_transaction.block_height.is_none() {
+                mock_transaction.block_height = Some(block_height);
+            }
+        }
+        state.height += num_blocks;
+    }
+
+    pub fn set_fee_rate(&self, fee_rate: FeeRate) {
+        self.state.lock().unwrap().fee_rate = Some(fee_rate);
+    }
+
+    pub fn get_output(&self, outpoint: &OutPoint) -> Option<TxOut> {
+        let state = self.state.lock().unwrap();
+        state
+            .transactions
+            .get(&outpoint.txid)
+            .and_then(|mock_transaction| mock_transaction.tx.output.get(outpoint.vout as usize))
+            .cloned()
+    }
+
+    fn verify_transaction(state: &MockChainState, tx: &Transaction) -> Result<(), ChainError> {
+        let txid = tx.compute_txid();
+        if state.transactions.contains_key(&txid) {
+            return Err(ChainError::Rejected(String::from("txn-already-known")));
+        }
+
+        // absolute height locks, evaluated for inclusion in the next block
+        if let absolute::LockTime::Blocks(height) = tx.lock_time {
+            let is_final = height.to_consensus_u32() <= state.height
+                || tx.input.iter().all(|input| input.sequence == Sequence::MAX);
+            if !is_final {
+                return Err(ChainError::Rejected(String::from("non-final")));
+            }
+        }
+
+        let mut total_input_amount = Amount::from_sat(0);
+        for input in tx.input.iter() {
+            let previous_transaction = state.transactions.get(&input.previous_output.txid);
+            let previous_output = previous_transaction.and_then(|previous_transaction| {
+                previous_transaction
+                    .tx
+                    .output
+                    .get(input.previous_output.vout as usize)
+            });
+            if previous_output.is_none() {
+                return Err(ChainError::Rejected(String::from("missing-inputs")));
+            }
+            if state.spent_outpoints.contains_key(&input.previous_output) {
+                return Err(ChainError::Rejected(String::from("txn-mempool-conflict")));
+            }
+
+            // BIP68 relative height locks, evaluated for inclusion in the next block
+            if let Some(relative::LockTime::Blocks(blocks)) = input.sequence.to_relative_lock_time()
+            {
+                let is_final = tx.version.0 < 2
+                    || previous_transaction
+                        .unwrap()
+                        .block_height
+                        .is_some_and(|block_height| {
+                            state.height + 1 - block_height >= blocks.value() as u32
+                        });
+                if !is_final {
+                    return Err(ChainError::Rejected(String::from("non-BIP68-final")));
+                }
+            }
+
+            total_input_amount += previous_output.unwrap().value;
+        }
+
+        let total_output_amount: Amount = tx.output.iter().map(|output| output.value).sum();
+        if total_output_amount > total_input_amount {
+            return Err(ChainError::Rejected(String::from("bad-txns-in-belowout")));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChainBackend for MockChain {
+    async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, ChainError> {
+        let state = self.state.lock().unwrap();
+        let block_height = state
+            .transactions
+            .get(txid)
+            .and_then(|mock_transaction| mock_transaction.block_height);
+
+        Ok(TxStatus {
+            confirmed: block_height.is_some(),
+            block_height,
+            block_hash: None,
+            block_time: None,
+        })
+    }
+
+    async fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, ChainError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .transactions
+            .get(txid)
+            .map(|mock_transaction| mock_transaction.tx.clone()))
+    }
+
+    async fn get_spending_txid(&self, outpoint: &OutPoint) -> Result<Option<Txid>, ChainError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .spent_outpoints
+            .get(outpoint)
+            .copied())
+    }
+
+    async fn get_height(&self) -> Result<u32, ChainError> { Ok(self.state.lock().unwrap().height) }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<(), ChainError> {
+        let mut state = self.state.lock().unwrap();
+        Self::verify_transaction(&state, tx)?;
+
+        let txid = tx.compute_txid();
+        for input in tx.input.iter() {
+            state.spent_outpoints.insert(input.previous_output, txid);
+        }
+        state.transactions.insert(
+            txid,
+            MockTransaction {
+                tx: tx.clone(),
+                block_height: None,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn get_address_utxo(&self, address: Address) -> Result<Vec<Utxo>, ChainError> {
+        let state = self.state.lock().unwrap();
+        let script_pubkey = address.script_pubkey();
+
+        let mut utxos: Vec<Utxo> = vec![];
+        for (txid, mock_transaction) in state.transactions.iter() {
+            for (vout, output) in mock_transaction.tx.output.iter().enumerate() {
+                let outpoint = OutPoint {
+                    txid: *txid,
+                    vout: vout as u32,
+                };
+                if output.script_pubkey == script_pubkey
+                    && !state.spent_outpoints.contains_key(&outpoint)
+                {
+                    utxos.push(Utxo {
+                        txid: *txid,
+                        vout: vout as u32,
+                        status: UtxoStatus {
+                            confirmed: mock_transaction.block_height.is_some(),
+                            block_height: mock_transaction.block_height,
+                            block_hash: None,
+                            block_time: None,
+                        },
+                        value: output.value,
+                    });
+                }
+            }
+        }
+
+        Ok(utxos)
+    }
+
+    async fn get_fee_rate(&self, _confirmation_target: u16) -> Result<FeeRate, ChainError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .fee_rate
+            .unwrap_or(FeeRate::BROADCAST_MIN))
+    }
+}
@@ -0,0 +1,4 @@
+pub mod base;
+pub mod esplora;
+pub mod mock;
+pub mod simulator;
@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use bitcoin::{
+    key::Secp256k1,
+    secp256k1::{All, Message},
+    sighash::{Prevouts, SighashCache},
+    taproot::{ControlBlock, LeafVersion},
+    Address, Amount, FeeRate, OutPoint, ScriptBuf, TapLeafHash, Transaction, TxOut, Txid,
+    XOnlyPublicKey,
+};
+use bitcoin_scriptexec::{Exec, ExecCtx, Options, TxTemplate};
+use esplora_client::{TxStatus, Utxo};
+
+use super::{
+    super::super::transactions::signing::verify_p2wpkh_witness,
+    base::{ChainBackend, ChainError},
+    mock::MockChain,
+};
+
+// In-memory regtest simulator for end-to-end tests. On top of the `MockChain` checks, the spending
+// conditions of every input are executed with bitcoin-scriptexec before a transaction is accepted:
+// taproot key and script path spends, P2WSH scripts (including CSV/CLTV against the spending input)
+// and signatures over the sighash type they commit to. Blocks are only mined on demand.
+#[derive(Clone, Default)]
+pub struct Simulator {
+    chain: MockChain,
+    secp: Secp256k1<All>,
+}
+
+impl Simulator {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn fund_address(&self, address: &Address, amount: Amount) -> OutPoint {
+        self.chain.fund_address(address, amount)
+    }
+
+    pub fn mine_blocks(&self, num_blocks: u32) { self.chain.mine_blocks(num_blocks) }
+
+    pub fn set_fee_rate(&self, fee_rate: FeeRate) { self.chain.set_fee_rate(fee_rate) }
+
+    fn verify_scripts(&self, tx: &Transaction) -> Result<(), ChainError> {
+        let mut prevouts: Vec<TxOut> = vec![];
+        for input in tx.input.iter() {
+            match self.chain.get_output(&input.previous_output) {
+                Some(prev_out) => prevouts.push(prev_out),
+                None => return Err(ChainError::Rejected(String::from("missing-inputs"))),
+            }
+        }
+
+        for input_index in 0..tx.input.len() {
+            self.verify_input(tx, &prevouts, input_index)
+                .map_err(|err| {
+                    ChainError::Rejected(format!("mandatory-script-verify-flag-failed ({})", err))
+                })?;
+        }
+
+        Ok(())
+    }
+
+    fn verify_input(
+        &self,
+        tx: &Transaction,
+        prevouts: &Vec<TxOut>,
+        input_index: usize,
+    ) -> Result<(), String> {
+        let prev_out = &prevouts[input_index];
+        let witness = &tx.input[input_index].witness;
+
+        if prev_out.script_pubkey.is_p2tr() {
+            if witness.is_empty() {
+                return Err(format!("Input {} has an empty witness", input_index));
+            }
+            // annexes are not used by the bridge and are not supported here
+            if witness.len() == 1 {
+                return self.verify_taproot_key_spend(tx, prevouts, input_index);
+            }
+
+            let output_key = XOnlyPublicKey::from_slice(&prev_out.script_pubkey.as_bytes()[2..])
+                .map_err(|_| format!("Input {} spends an invalid taproot output", input_index))?;
+            let script = ScriptBuf::from_bytes(witness[witness.len() - 2].to_vec());
+            let control_block = ControlBlock::decode(witness.last().unwrap()).map_err(|err| {
+                format!(
+                    "Input {} has an invalid control block: {}",
+                    input_index, err
+                )
+            })?;
+            if !control_block.verify_taproot_commitment(&self.secp, output_key, &script) {
+                return Err(format!(
+                    "Input {} leaf script is not committed to by the spent output",
+                    input_index
+                ));
+            }
+
+            let leaf_hash = TapLeafHash::from_script(&script, LeafVersion::TapScript);
+            let stack = witness
+                .iter()
+                .take(witness.len() - 2)
+                .map(|element| element.to_vec())
+                .collect();
+            execute_input_script(
+                ExecCtx::Tapscript,
+                tx,
+                prevouts,
+                input_index,
+                script,
+                stack,
+                Some(leaf_hash),
+            )
+        } else if prev_out.script_pubkey.is_p2wsh() {
+            if witness.is_empty() {
+                return Err(format!("Input {} has an empty witness", input_index));
+            }
+
+            let script = ScriptBuf::from_bytes(witness.last().unwrap().to_vec());
+            if prev_out.script_pubkey != ScriptBuf::new_p2wsh(&script.wscript_hash()) {
+                return Err(format!(
+                    "Input {} witness script does not match the spent output",
+                    input_index
+                ));
+            }
+
+            let stack = witness
+                .iter()
+                .take(witness.len() - 1)
+                .map(|element| element.to_vec())
+                .collect();
+            execute_input_script(
+                ExecCtx::SegwitV0,
+                tx,
+                prevouts,
+                input_index,
+                script,
+                stack,
+                None,
+            )
+        } else if prev_out.script_pubkey.is_p2wpkh() {
+            verify_p2wpkh_witness(&self.secp, tx, input_index, prev_out)
+        } else {
+            Err(format!(
+                "Input {} spends an unsupported output type",
+                input_index
+            ))
+        }
+    }
+
+    fn verify_taproot_key_spend(
+        &self,
+        tx: &Transaction,
+        prevouts: &Vec<TxOut>,
+        input_index: usize,
+    ) -> Result<(), String> {
+        let signature = bitcoin::taproot::Signature::from_slice(&tx.input[input_index].witness[0])
+            .map_err(|err| format!("Input {} has an invalid signature: {}", input_index, err))?;
+        let output_key =
+            XOnlyPublicKey::from_slice(&prevouts[input_index].script_pubkey.as_bytes()[2..])
+                .map_err(|_| format!("Input {} spends an invalid taproot output", input_index))?;
+
+        let sighash = SighashCache::new(tx)
+            .taproot_key_spend_signature_hash(
+                input_index,
+                &Prevouts::All(prevouts),
+                signature.sighash_type,
+            )
+            .map_err(|err| format!("Failed to construct sighash: {}", err))?;
+
+        self.secp
+            .verify_schnorr(&signature.signature, &Message::from(sighash), &output_key)
+            .map_err(|_| format!("Input {} has an invalid signature", input_index))
+    }
+}
+
+fn execute_input_script(
+    ctx: ExecCtx,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    script: ScriptBuf,
+    stack: Vec<Vec<u8>>,
+    leaf_hash: Option<TapLeafHash>,
+) -> Result<(), String> {
+    let mut exec = Exec::new(
+        ctx,
+        Options::default(),
+        TxTemplate {
+            tx: tx.clone(),
+            prevouts: prevouts.clone(),
+            input_idx: input_index,
+            taproot_annex_scriptleaf: leaf_hash.map(|leaf_hash| (leaf_hash, None)),
+        },
+        script,
+        stack,
+    )
+    .map_err(|err| {
+        format!(
+            "Input {} script could not be executed: {:?}",
+            input_index, err
+        )
+    })?;
+
+    loop {
+        if exec.exec_next().is_err() {
+            break;
+        }
+    }
+
+    let result = exec.result().unwrap();
+    if !result.success {
+        return Err(format!(
+            "Input {} script execution failed: {:?}",
+            input_index, result.error
+        ));
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl ChainBackend for Simulator {
+    async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, ChainError> {
+        self.chain.get_tx_status(txid).await
+    }
+
+    async fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, ChainError> {
+        self.chain.get_tx(txid).await
+    }
+
+    async fn get_spending_txid(&self, outpoint: &OutPoint) -> Result<Option<Txid>, ChainError> {
+        self.chain.get_spending_txid(outpoint).await
+    }
+
+    async fn get_height(&self) -> Result<u32, ChainError> { self.chain.get_height().await }
+
+    async fn broadcast(&self, tx: &Transaction) -> Result<(), ChainError> {
+        self.verify_scripts(tx)?;
+        self.chain.broadcast(tx).await
+    }
+
+    async fn get_address_utxo(&self, address: Address) -> Result<Vec<Utxo>, ChainError> {
+        self.chain.get_address_utxo(address).await
+    }
+
+    async fn get_fee_rate(&self, confirmation_target: u16) -> Result<FeeRate, ChainError> {
+        self.chain.get_fee_rate(confirmation_target).await
+    }
+}
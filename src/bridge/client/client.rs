@@ -1,27 +1,48 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display, path::Path};
+use tokio::time::sleep;
 
-use bitcoin::{absolute::Height, Address, Amount, Network, OutPoint};
-use esplora_client::{AsyncClient, Builder, Utxo};
+use bitcoin::{
+    absolute::Height,
+    key::{Keypair, Secp256k1},
+    secp256k1::All,
+    Address, Amount, OutPoint, PublicKey, Txid, XOnlyPublicKey,
+};
+use esplora_client::Utxo;
 
 use super::{
     super::{
+        commitments::{generate_commit_y_public_key, sign_commit_y},
         contexts::{
-            base::generate_keys_from_secret, depositor::DepositorContext,
-            operator::OperatorContext, verifier::VerifierContext, withdrawer::WithdrawerContext,
+            depositor::DepositorContext, operator::OperatorContext, verifier::VerifierContext,
         },
+        error::BridgeError,
         graphs::{
-            base::{BaseGraph, N_OF_N_SECRET, OPERATOR_SECRET},
-            peg_in::PegInGraph,
-            peg_out::{generate_id, PegOutGraph},
+            base::{BaseGraph, PreSigningState},
+            peg_in::{PegInGraph, PegInOperatorStatus, PegInVerifierStatus},
+            peg_out::{
+                generate_id, PegOutAction, PegOutGraph, PegOutOperatorStatus, PegOutRequest,
+            },
+            status::{GraphRole, GraphStatusReport},
+        },
+        keys::{
+            key_chain::{KeyChain, KeyRole},
+            secret_nonce_store::{GraphSecretNonces, SecretNonceStore},
         },
+        registry::ParticipantRegistry,
+        scripts::{generate_pay_to_pubkey_script, generate_pay_to_pubkey_script_address},
         serialization::{serialize, try_deserialize},
-        transactions::base::Input,
+        transactions::{
+            base::{BaseTransaction, Input, InputWithScript},
+            commit_y_funding::CommitYFundingTransaction,
+        },
     },
-    data_store::data_store::DataStore,
+    chain::{base::ChainBackend, esplora::Esplora},
+    data_store::{base::StorageBackend, data_store::DataStore},
+    policy::ProcessingPolicy,
 };
 
-const ESPLORA_URL: &str = "https://mutinynet.com/api";
+const MAX_SAVE_ATTEMPTS: u32 = 3;
 
 pub type UtxoSet = HashMap<OutPoint, Height>;
 
@@ -33,205 +54,899 @@ pub struct BitVMClientData {
 }
 
 pub struct BitVMClient {
-    pub esplora: AsyncClient,
+    pub chain: Box<dyn ChainBackend>,
 
-    depositor_context: Option<DepositorContext>,
+    // Depositors and withdrawers derive a fresh key for every graph
+    depositor_key_chain: Option<KeyChain>,
     operator_context: Option<OperatorContext>,
     verifier_context: Option<VerifierContext>,
-    withdrawer_context: Option<WithdrawerContext>,
+    withdrawer_key_chain: Option<KeyChain>,
 
     data_store: DataStore,
     data: BitVMClientData,
+
+    allowed_writer_public_keys: Vec<XOnlyPublicKey>,
+
+    registry: ParticipantRegistry,
+    n_of_n_taproot_public_key: XOnlyPublicKey,
+
+    // Secret nonces of the verifier by graph id, kept until the graph is presigned
+    verifier_secret_nonces: HashMap<String, GraphSecretNonces>,
+    verifier_secret_nonce_store: Option<SecretNonceStore>,
 }
 
 impl BitVMClient {
+    // The counterparties are only known by their public keys in the registry. The client's own
+    // keys are derived with keys::key_chain::KeyChain, e.g. from a keys::keystore::Keystore: the
+    // operator and verifier keypairs are those at index 0, the depositor and withdrawer keys are
+    // derived by the client for every graph.
     pub async fn new(
-        network: Network,
-        depositor_secret: Option<&str>,
-        operator_secret: Option<&str>,
-        n_of_n_secret: Option<&str>,
-        withdrawer_secret: Option<&str>,
-    ) -> Self {
-        // TODO these publc key values should be hardcoded
-        let operator_keys = generate_keys_from_secret(network, OPERATOR_SECRET);
-        let verifier_keys = generate_keys_from_secret(network, N_OF_N_SECRET);
-
-        let mut depositor_context = None;
-        if depositor_secret.is_some() {
-            depositor_context = Some(DepositorContext::new(
-                network,
-                depositor_secret.unwrap(),
-                &verifier_keys.2,
-                &verifier_keys.3,
-            ));
+        registry: &ParticipantRegistry,
+        depositor_key_chain: Option<&KeyChain>,
+        operator_keypair: Option<&Keypair>,
+        verifier_keypair: Option<&Keypair>,
+        withdrawer_key_chain: Option<&KeyChain>,
+        storage_backend: Option<Box<dyn StorageBackend>>,
+        chain_backend: Option<Box<dyn ChainBackend>>,
+    ) -> Result<Self, BridgeError> {
+        let network = registry.network;
+        let n_of_n_public_keys = &registry.verifier_public_keys;
+        let (n_of_n_public_key, n_of_n_taproot_public_key) = registry.n_of_n_public_key();
+
+        for key_chain in [depositor_key_chain, withdrawer_key_chain]
+            .into_iter()
+            .flatten()
+        {
+            if key_chain.network() != network {
+                return Err(BridgeError::KeyDerivation(format!(
+                    "Key chain of network {} used with a registry of network {}",
+                    key_chain.network(),
+                    network
+                )));
+            }
         }
 
         let mut operator_context = None;
-        if operator_secret.is_some() {
-            operator_context = Some(OperatorContext::new(
+        if let Some(keypair) = operator_keypair {
+            operator_context = Some(OperatorContext::from_keypair(
                 network,
-                operator_secret.unwrap(),
-                &verifier_keys.2,
-                &verifier_keys.3,
+                keypair,
+                &n_of_n_public_key,
+                &n_of_n_taproot_public_key,
             ));
+            if !registry.is_operator(&operator_context.as_ref().unwrap().operator_public_key) {
+                return Err(BridgeError::Registry(String::from(
+                    "Operator public key must be one of the registered operator public keys",
+                )));
+            }
         }
 
         let mut verifier_context = None;
-        if n_of_n_secret.is_some() {
-            verifier_context = Some(VerifierContext::new(
-                network,
-                n_of_n_secret.unwrap(),
-                &operator_keys.2,
-                &operator_keys.3,
-            ));
-        }
-
-        let mut withdrawer_context = None;
-        if withdrawer_secret.is_some() {
-            withdrawer_context = Some(WithdrawerContext::new(
+        if let Some(keypair) = verifier_keypair {
+            verifier_context = Some(VerifierContext::from_keypair(
                 network,
-                withdrawer_secret.unwrap(),
-                &verifier_keys.2,
-                &verifier_keys.3,
-            ));
+                keypair,
+                n_of_n_public_keys,
+            )?);
         }
 
-        let mut data = BitVMClientData {
+        let data = BitVMClientData {
             version: 1,
             peg_in_graphs: vec![],
             peg_out_graphs: vec![],
         };
 
-        let data_store = DataStore::new();
-        let fetched_data = Self::fetch(&data_store).await;
-        if fetched_data.is_some() {
-            data = fetched_data.unwrap();
-        }
+        // Registered operators and verifiers are trusted with all client data, depositors and
+        // withdrawers only with their own graphs, see merge_writer_data.
+        let allowed_writer_public_keys: Vec<XOnlyPublicKey> = registry
+            .operator_public_keys
+            .iter()
+            .chain(n_of_n_public_keys.iter())
+            .map(|public_key| public_key.inner.x_only_public_key().0)
+            .collect();
 
-        Self {
-            esplora: Builder::new(ESPLORA_URL)
-                .build_async()
-                .expect("Could not build esplora client"),
+        let data_store = match storage_backend {
+            Some(backend) => DataStore::with_backend(backend),
+            None => DataStore::new(),
+        };
 
-            depositor_context,
+        let mut client = Self {
+            chain: match chain_backend {
+                Some(backend) => backend,
+                None => Box::new(Esplora::new(network)?),
+            },
+
+            depositor_key_chain: depositor_key_chain.cloned(),
             operator_context,
             verifier_context,
-            withdrawer_context,
+            withdrawer_key_chain: withdrawer_key_chain.cloned(),
 
             data,
 
             data_store,
+
+            allowed_writer_public_keys,
+
+            registry: registry.clone(),
+            n_of_n_taproot_public_key,
+
+            verifier_secret_nonces: HashMap::new(),
+            verifier_secret_nonce_store: None,
+        };
+
+        client.read().await;
+
+        Ok(client)
+    }
+
+    pub fn registry(&self) -> &ParticipantRegistry { &self.registry }
+
+    pub fn data(&self) -> &BitVMClientData { &self.data }
+
+    // Keeps the secret nonces of the verifier in an encrypted file from now on, so a verifier
+    // restarting between pushing its nonces and pre-signing can still pre-sign. The nonces stored
+    // by an earlier run are loaded.
+    pub fn store_verifier_secret_nonces(&mut self, path: &Path) -> Result<(), BridgeError> {
+        if self.verifier_context.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Verifier")));
+        }
+
+        let secret_nonce_store = SecretNonceStore::new(
+            path,
+            &self.verifier_context.as_ref().unwrap().verifier_keypair,
+        );
+        self.verifier_secret_nonces
+            .extend(secret_nonce_store.load()?);
+        self.verifier_secret_nonce_store = Some(secret_nonce_store);
+
+        Ok(())
+    }
+
+    pub fn add_allowed_writer_public_key(&mut self, public_key: &XOnlyPublicKey) {
+        if !self.allowed_writer_public_keys.contains(public_key) {
+            self.allowed_writer_public_keys.push(*public_key);
         }
     }
 
     pub async fn sync(&mut self) { self.read().await; }
 
-    pub async fn flush(&mut self) { self.save().await; }
+    pub async fn flush(&mut self) -> Result<(), BridgeError> { self.save().await }
 
     async fn read(&mut self) {
-        let data = Self::fetch(&self.data_store).await;
+        let data = Self::fetch(&self.data_store, &self.allowed_writer_public_keys).await;
         if data.is_some() {
-            self.data = data.unwrap();
+            let mut data = data.unwrap();
+            self.verify_data(&mut data);
+            self.data = data;
         }
     }
 
-    async fn fetch(data_store: &DataStore) -> Option<BitVMClientData> {
-        let result = data_store.fetch_latest_data().await;
-        if result.is_ok() {
-            let json = result.unwrap();
-            if json.is_some() {
-                let data = try_deserialize::<BitVMClientData>(&json.unwrap());
-                if data.is_ok() {
-                    return Some(data.unwrap());
+    // The latest data of an allowed writer, with the graphs of the depositors and withdrawers who
+    // saved since merged in.
+    async fn fetch(
+        data_store: &DataStore,
+        allowed_writer_public_keys: &Vec<XOnlyPublicKey>,
+    ) -> Option<BitVMClientData> {
+        let data_files = data_store
+            .fetch_latest_data_files(allowed_writer_public_keys)
+            .await;
+        if data_files.is_err() {
+            return None;
+        }
+
+        let mut data: Option<BitVMClientData> = None;
+        for (writer_public_key, json) in data_files.unwrap() {
+            let writer_data = try_deserialize::<BitVMClientData>(&json);
+            if writer_data.is_err() {
+                continue;
+            }
+
+            let writer_data = writer_data.unwrap();
+            if allowed_writer_public_keys.contains(&writer_public_key) {
+                data = Some(writer_data);
+            } else {
+                let data = data.get_or_insert_with(|| BitVMClientData {
+                    version: 0,
+                    peg_in_graphs: vec![],
+                    peg_out_graphs: vec![],
+                });
+                Self::merge_writer_data(data, writer_data, &writer_public_key);
+            }
+        }
+
+        data
+    }
+
+    // Depositors and withdrawers are only trusted with their own graphs: the peg-in graphs the
+    // depositor created and the peg-out requests of the withdrawer. Everything else they saved is
+    // ignored, the graphs are verified like any other once fetched.
+    fn merge_writer_data(
+        data: &mut BitVMClientData,
+        writer_data: BitVMClientData,
+        writer_public_key: &XOnlyPublicKey,
+    ) {
+        for writer_peg_in_graph in writer_data.peg_in_graphs {
+            if writer_peg_in_graph
+                .depositor_public_key
+                .inner
+                .x_only_public_key()
+                .0
+                .eq(writer_public_key)
+                && !data
+                    .peg_in_graphs
+                    .iter()
+                    .any(|peg_in_graph| peg_in_graph.id().eq(writer_peg_in_graph.id()))
+            {
+                data.peg_in_graphs.push(writer_peg_in_graph);
+            }
+        }
+
+        for writer_peg_out_graph in writer_data.peg_out_graphs {
+            let request = writer_peg_out_graph.peg_out_request();
+            if !request.as_ref().is_some_and(|request| {
+                request
+                    .withdrawer_public_key
+                    .inner
+                    .x_only_public_key()
+                    .0
+                    .eq(writer_public_key)
+            }) {
+                continue;
+            }
+
+            let peg_out_graph = data
+                .peg_out_graphs
+                .iter_mut()
+                .find(|peg_out_graph| peg_out_graph.id().eq(writer_peg_out_graph.id()));
+            if peg_out_graph.is_some() {
+                let result = peg_out_graph.unwrap().request_peg_out(&request.unwrap());
+                if result.is_err() {
+                    println!(
+                        "Ignoring peg-out request of graph {}: {}",
+                        writer_peg_out_graph.id(),
+                        result.unwrap_err()
+                    );
                 }
             }
         }
 
+        data.version = data.version.max(writer_data.version);
+    }
+
+    // Every save adds a new data file and readers only use the latest one, there is no
+    // compare-and-swap. A client saving concurrently can therefore shadow the file written here.
+    // The latest file is read back after writing and, if it is not the one just written, the
+    // remote changes are merged and saved again. A concurrent writer detects the shadowing the
+    // same way, so changes are only lost if both clients run out of attempts.
+    async fn save(&mut self) -> Result<(), BridgeError> {
+        if self.writer_keypair().is_none() {
+            return Err(BridgeError::MissingContext(String::from(
+                "Operator, verifier, depositor or withdrawer",
+            )));
+        }
+
+        for _ in 0..MAX_SAVE_ATTEMPTS {
+            // The local data is based on the version read during the last sync. Merge the remote
+            // changes made in the meantime first, so they are not overwritten.
+            let latest_data = Self::fetch(&self.data_store, &self.allowed_writer_public_keys).await;
+            if latest_data
+                .as_ref()
+                .is_some_and(|latest_data| latest_data.ne(&self.data))
+            {
+                let mut latest_data = latest_data.unwrap();
+                self.verify_data(&mut latest_data);
+                println!(
+                    "Merging remote data version {} into local version {}",
+                    latest_data.version, self.data.version
+                );
+                Self::merge_data(&mut self.data, latest_data)?;
+            }
+
+            self.data.version += 1;
+
+            let json = serialize(&self.data);
+            let (secp, keypair) = self.writer_keypair().unwrap();
+            let result = self
+                .data_store
+                .write_data(json.clone(), secp, &keypair)
+                .await;
+            if result.is_err() {
+                let err = result.unwrap_err();
+                println!("Failed to save: {}", err);
+                return Err(BridgeError::DataStore(err.to_string()));
+            }
+            let key = result.unwrap();
+
+            let data_files = self
+                .data_store
+                .fetch_latest_data_files(&self.allowed_writer_public_keys)
+                .await;
+            if data_files.is_ok_and(|data_files| {
+                data_files
+                    .last()
+                    .is_some_and(|(_, latest_json)| latest_json.eq(&json))
+            }) {
+                println!("Saved successfully to {}", key);
+                return Ok(());
+            }
+            println!(
+                "Saved {}, but it was shadowed by a concurrent save, retrying",
+                key
+            );
+        }
+
+        Err(BridgeError::DataStore(format!(
+            "Client data was shadowed by concurrent saves {} times",
+            MAX_SAVE_ATTEMPTS
+        )))
+    }
+
+    // Client data is signed by the first available key in the order operator, verifier,
+    // depositor, withdrawer. Data signed by a depositor or withdrawer is only trusted with their
+    // own graphs, so they sign with the key of their latest graph. The graphs of their earlier keys
+    // are in the data files signed before.
+    fn writer_keypair(&self) -> Option<(&Secp256k1<All>, Keypair)> {
+        if self.operator_context.is_some() {
+            let context = self.operator_context.as_ref().unwrap();
+            return Some((&context.secp, context.operator_keypair));
+        }
+        if self.verifier_context.is_some() {
+            let context = self.verifier_context.as_ref().unwrap();
+            return Some((&context.secp, context.verifier_keypair));
+        }
+        if self.depositor_key_chain.is_some() {
+            let key_chain = self.depositor_key_chain.as_ref().unwrap();
+            return Self::latest_keypair(
+                key_chain,
+                KeyRole::Depositor,
+                &self.depositor_public_keys(),
+            )
+            .map(|keypair| (key_chain.secp(), keypair));
+        }
+        if self.withdrawer_key_chain.is_some() {
+            let key_chain = self.withdrawer_key_chain.as_ref().unwrap();
+            return Self::latest_keypair(
+                key_chain,
+                KeyRole::Withdrawer,
+                &self.withdrawer_public_keys(),
+            )
+            .map(|keypair| (key_chain.secp(), keypair));
+        }
+
         None
     }
 
-    async fn save(&mut self) {
-        self.data.version += 1;
-
-        let json = serialize(&self.data);
-        let result = self.data_store.write_data(json).await;
-        match result {
-            Ok(key) => println!("Saved successfully to {}", key),
-            Err(err) => println!("Failed to save: {}", err),
-        }
-    }
-
-    // fn verify_data(&self, data: &BitVMClientData) {
-    //     for peg_in_graph in data.peg_in_graphs.iter() {
-    //         self.verify_peg_in_graph(peg_in_graph);
-    //     }
-    //     for peg_out_graph in data.peg_out_graphs.iter() {
-    //         self.verify_peg_out_graph(peg_out_graph);
-    //     }
-    // }
-
-    // fn verify_peg_in_graph(&self, peg_in_graph: &PegInGraph) {}
-
-    // fn verify_peg_out_graph(&self, peg_out_graph: &PegOutGraph) {}
-
-    // fn process(&self) {
-    //     for peg_in_graph in self.data.peg_in_graphs.iter() {
-    //         // match graph.get(outpoint) {
-    //         //     Some(subsequent_txs) => {
-    //         //         for bridge_transaction in subsequent_txs {
-    //         //             // TODO: Check whether the transaction is executable
-    //         //             let tx = bridge_transaction.finalize();
-    //         //             match self.esplora.broadcast(&tx).await {
-    //         //                 Ok(_) => {
-    //         //                     println!(
-    //         //                         "Succesfully broadcast next transaction with id: {}",
-    //         //                         tx.compute_txid()
-    //         //                     );
-    //         //                     remove_utxo = Some(*outpoint);
-    //         //                     break;
-    //         //                 }
-    //         //                 Err(err) => panic!("Tx Broadcast Error: {}", err),
-    //         //             }
-    //         //         }
-    //         //     }
-    //         //     None => continue,
-    //         // }
-    //     }
-    // }
-
-    pub async fn status(&self) {
-        if self.depositor_context.is_some() {
-            self.depositor_status().await;
+    // Index of the first key of the role not among the public keys, the key of the next graph.
+    fn next_key_index(
+        key_chain: &KeyChain,
+        role: KeyRole,
+        public_keys: &Vec<PublicKey>,
+    ) -> Result<u32, BridgeError> {
+        let mut index = 0;
+        while public_keys.contains(&key_chain.derive_public_key(role, index)?) {
+            index += 1;
+        }
+
+        Ok(index)
+    }
+
+    // The keys of the role used by the graphs so far.
+    fn used_public_keys(
+        key_chain: &KeyChain,
+        role: KeyRole,
+        public_keys: &Vec<PublicKey>,
+    ) -> Result<Vec<PublicKey>, BridgeError> {
+        (0..Self::next_key_index(key_chain, role, public_keys)?)
+            .map(|index| key_chain.derive_public_key(role, index))
+            .collect()
+    }
+
+    // The key of the latest graph, or the one of the first graph if there is none yet.
+    fn latest_keypair(
+        key_chain: &KeyChain,
+        role: KeyRole,
+        public_keys: &Vec<PublicKey>,
+    ) -> Option<Keypair> {
+        let index = Self::next_key_index(key_chain, role, public_keys).ok()?;
+
+        key_chain.derive_keypair(role, index.saturating_sub(1)).ok()
+    }
+
+    fn depositor_public_keys(&self) -> Vec<PublicKey> {
+        self.data
+            .peg_in_graphs
+            .iter()
+            .map(|peg_in_graph| peg_in_graph.depositor_public_key)
+            .collect()
+    }
+
+    fn withdrawer_public_keys(&self) -> Vec<PublicKey> {
+        self.data
+            .peg_out_graphs
+            .iter()
+            .filter_map(|peg_out_graph| peg_out_graph.peg_out_request())
+            .map(|request| request.withdrawer_public_key)
+            .collect()
+    }
+
+    fn merge_data(
+        local_data: &mut BitVMClientData,
+        remote_data: BitVMClientData,
+    ) -> Result<(), BridgeError> {
+        for remote_peg_in_graph in remote_data.peg_in_graphs {
+            let local_peg_in_graph = local_data
+                .peg_in_graphs
+                .iter_mut()
+                .find(|peg_in_graph| peg_in_graph.id().eq(remote_peg_in_graph.id()));
+            match local_peg_in_graph {
+                Some(local_peg_in_graph) => local_peg_in_graph.merge(&remote_peg_in_graph)?,
+                None => local_data.peg_in_graphs.push(remote_peg_in_graph),
+            }
+        }
+
+        for remote_peg_out_graph in remote_data.peg_out_graphs {
+            let local_peg_out_graph = local_data
+                .peg_out_graphs
+                .iter_mut()
+                .find(|peg_out_graph| peg_out_graph.id().eq(remote_peg_out_graph.id()));
+            match local_peg_out_graph {
+                Some(local_peg_out_graph) => local_peg_out_graph.merge(&remote_peg_out_graph)?,
+                None => local_data.peg_out_graphs.push(remote_peg_out_graph),
+            }
+        }
+
+        local_data.version = local_data.version.max(remote_data.version);
+
+        Ok(())
+    }
+
+    // Discards every graph that does not match what its public keys would generate, as well as
+    // peg-out graphs whose peg-in graph is missing or invalid. The pre-signing state of every
+    // graph is derived again from its witnesses.
+    fn verify_data(&self, data: &mut BitVMClientData) {
+        for peg_in_graph in data.peg_in_graphs.iter_mut() {
+            peg_in_graph.recompute_pre_signing_state();
+        }
+        for peg_out_graph in data.peg_out_graphs.iter_mut() {
+            peg_out_graph.recompute_pre_signing_state();
+        }
+
+        data.peg_in_graphs
+            .retain(|peg_in_graph| self.verify_peg_in_graph(peg_in_graph));
+
+        let peg_in_graphs = &data.peg_in_graphs;
+        data.peg_out_graphs
+            .retain(|peg_out_graph| self.verify_peg_out_graph(peg_in_graphs, peg_out_graph));
+    }
+
+    fn verify_peg_in_graph(&self, peg_in_graph: &PegInGraph) -> bool {
+        let result = peg_in_graph.verify(&self.n_of_n_taproot_public_key);
+        if result.is_err() {
+            println!(
+                "Discarding peg-in graph {}: {}",
+                peg_in_graph.id(),
+                result.unwrap_err()
+            );
+            return false;
+        }
+
+        true
+    }
+
+    fn verify_peg_out_graph(
+        &self,
+        peg_in_graphs: &Vec<PegInGraph>,
+        peg_out_graph: &PegOutGraph,
+    ) -> bool {
+        let peg_in_graph = peg_in_graphs
+            .iter()
+            .find(|peg_in_graph| peg_in_graph.id().eq(peg_out_graph.peg_in_graph_id()));
+        if peg_in_graph.is_none() {
+            println!(
+                "Discarding peg-out graph {}: peg-in graph {} not found",
+                peg_out_graph.id(),
+                peg_out_graph.peg_in_graph_id()
+            );
+            return false;
+        }
+
+        // the graph is verified against the keys the registry has for its operator
+        let public_context = self
+            .registry
+            .public_context(peg_out_graph.operator_public_key());
+        if public_context.is_none() {
+            println!(
+                "Discarding peg-out graph {}: operator {} not registered",
+                peg_out_graph.id(),
+                peg_out_graph.operator_public_key()
+            );
+            return false;
         }
+
+        let result = peg_out_graph.verify(
+            peg_in_graph.unwrap(),
+            &public_context.unwrap().n_of_n_taproot_public_key,
+        );
+        if result.is_err() {
+            println!(
+                "Discarding peg-out graph {}: {}",
+                peg_out_graph.id(),
+                result.unwrap_err()
+            );
+            return false;
+        }
+
+        true
+    }
+
+    // Polls the chain and executes the actions available to each configured role, as allowed by the
+    // policy. Never returns.
+    pub async fn run(&mut self, policy: &ProcessingPolicy) {
+        loop {
+            self.sync().await;
+
+            match self.process(policy).await {
+                Ok(true) => {
+                    let result = self.flush().await;
+                    if result.is_err() {
+                        println!("Failed to flush client data: {}", result.unwrap_err());
+                    }
+                }
+                Ok(false) => {}
+                Err(err) => println!("Failed to process client data: {}", err),
+            }
+
+            sleep(policy.poll_interval).await;
+        }
+    }
+
+    // Executes one round of automatic processing. Returns whether the client data changed and
+    // should be flushed.
+    pub async fn process(&mut self, policy: &ProcessingPolicy) -> Result<bool, BridgeError> {
+        let mut data_changed = false;
         if self.operator_context.is_some() {
-            self.operator_status().await;
+            data_changed |= self.process_operator(policy).await?;
         }
         if self.verifier_context.is_some() {
-            self.verifier_status().await;
+            data_changed |= self.process_verifier(policy).await?;
         }
+
+        Ok(data_changed)
     }
 
-    async fn depositor_status(&self) {
-        if self.depositor_context.is_none() {
-            panic!("Depositor context must be initialized");
+    async fn process_operator(&mut self, policy: &ProcessingPolicy) -> Result<bool, BridgeError> {
+        if self.operator_context.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Operator")));
         }
 
-        let depositor_public_key = &self
-            .depositor_context
-            .as_ref()
-            .unwrap()
-            .depositor_public_key;
+        for peg_in_graph in self.data.peg_in_graphs.iter_mut() {
+            let status = peg_in_graph.operator_status(self.chain.as_ref()).await;
+            match status {
+                PegInOperatorStatus::PegInConfirmAvailable if policy.peg_in_confirm => {
+                    println!("Graph id: {} executing peg-in confirm", peg_in_graph.id());
+                    if let Err(err) = peg_in_graph.confirm(self.chain.as_ref()).await {
+                        println!(
+                            "Graph id: {} failed to execute peg-in confirm: {}\n",
+                            peg_in_graph.id(),
+                            err
+                        );
+                    }
+                }
+                _ => println!("Graph id: {} status: {}\n", peg_in_graph.id(), status),
+            }
+        }
+
+        let operator_public_key = &self.operator_context.as_ref().unwrap().operator_public_key;
+        let operator_peg_out_graph_ids: Vec<String> = self
+            .data
+            .peg_in_graphs
+            .iter()
+            .map(|peg_in_graph| generate_id(peg_in_graph, operator_public_key))
+            .collect();
+
+        let mut data_changed = false;
+        for peg_out_graph in self.data.peg_out_graphs.iter_mut() {
+            if !operator_peg_out_graph_ids.contains(peg_out_graph.id()) {
+                continue;
+            }
+
+            let status = peg_out_graph.operator_status(self.chain.as_ref()).await;
+            if status.is_err() {
+                println!(
+                    "Graph id: {} failed to get status: {}\n",
+                    peg_out_graph.id(),
+                    status.unwrap_err()
+                );
+                continue;
+            }
+            let status = status.unwrap();
+
+            let result = match status {
+                PegOutOperatorStatus::PegOutKickOffAvailable if policy.kick_off => {
+                    match peg_out_graph.peg_out_claim() {
+                        Some(claim) => {
+                            let commit_y = sign_commit_y(
+                                self.operator_context.as_ref().unwrap(),
+                                peg_out_graph.id(),
+                                &claim,
+                            );
+                            Some(peg_out_graph.kick_off(self.chain.as_ref(), &commit_y).await)
+                        }
+                        None => {
+                            println!(
+                                "Graph id: {} waiting for the peg-out to kick off",
+                                peg_out_graph.id()
+                            );
+                            None
+                        }
+                    }
+                }
+                PegOutOperatorStatus::PegOutAssertAvailable if policy.assert => {
+                    Some(peg_out_graph.assert(self.chain.as_ref()).await)
+                }
+                PegOutOperatorStatus::PegOutTake1Available if policy.take1 => {
+                    Some(peg_out_graph.take1(self.chain.as_ref()).await)
+                }
+                PegOutOperatorStatus::PegOutTake2Available if policy.take2 => {
+                    Some(peg_out_graph.take2(self.chain.as_ref()).await)
+                }
+                _ => None,
+            };
+
+            data_changed |= log_action_result(peg_out_graph.id(), &status, result);
+        }
+
+        Ok(data_changed)
+    }
+
+    async fn process_verifier(&mut self, policy: &ProcessingPolicy) -> Result<bool, BridgeError> {
+        if self.verifier_context.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Verifier")));
+        }
+
+        let mut data_changed = false;
+        let mut secret_nonces_pushed = false;
+        let secret_nonce_count = count_secret_nonces(&self.verifier_secret_nonces);
+        for peg_in_graph in self.data.peg_in_graphs.iter_mut() {
+            let status = peg_in_graph.verifier_status(self.chain.as_ref()).await;
+            match status {
+                PegInVerifierStatus::PegInPresign if policy.pre_sign => {
+                    let verifier_context = self.verifier_context.as_ref().unwrap();
+                    let pre_signing_state = peg_in_graph.pre_signing_state();
+                    if pre_signing_state == PreSigningState::AwaitingNonces
+                        && !peg_in_graph.has_nonces_of(verifier_context)
+                    {
+                        match peg_in_graph.push_nonces(verifier_context) {
+                            Ok(secret_nonces) => {
+                                self.verifier_secret_nonces
+                                    .insert(peg_in_graph.id().clone(), secret_nonces);
+                                secret_nonces_pushed = true;
+                                println!("Graph id: {} nonces pushed\n", peg_in_graph.id());
+                                data_changed = true;
+                            }
+                            Err(err) => println!(
+                                "Graph id: {} failed to push nonces: {}\n",
+                                peg_in_graph.id(),
+                                err
+                            ),
+                        }
+                    } else if pre_signing_state == PreSigningState::AwaitingSignatures
+                        && (!peg_in_graph.has_signatures_of(verifier_context)
+                            || peg_in_graph.has_all_signatures(verifier_context))
+                    {
+                        let mut no_secret_nonces = HashMap::new();
+                        let secret_nonces = self
+                            .verifier_secret_nonces
+                            .get_mut(peg_in_graph.id())
+                            .unwrap_or(&mut no_secret_nonces);
+                        match peg_in_graph.pre_sign(verifier_context, secret_nonces) {
+                            Ok(()) => {
+                                println!("Graph id: {} presigned\n", peg_in_graph.id());
+                                data_changed = true;
+                            }
+                            Err(err) => println!(
+                                "Graph id: {} failed to presign: {}\n",
+                                peg_in_graph.id(),
+                                err
+                            ),
+                        }
+                    } else {
+                        println!("Graph id: {} {}\n", peg_in_graph.id(), pre_signing_state);
+                    }
+                }
+                _ => println!("Graph id: {} status: {}\n", peg_in_graph.id(), status),
+            }
+        }
+
+        let verifier_context = self.verifier_context.as_ref().unwrap();
+        let verifier_address = generate_pay_to_pubkey_script_address(
+            verifier_context.network,
+            &verifier_context.verifier_public_key,
+        );
+        let verifier_script = generate_pay_to_pubkey_script(&verifier_context.verifier_public_key);
+        let reward_script_pubkey = policy
+            .reward_script_pubkey
+            .clone()
+            .unwrap_or(verifier_address.script_pubkey());
+
+        for i in 0..self.data.peg_out_graphs.len() {
+            let graph_state = self.data.peg_out_graphs[i].state(self.chain.as_ref()).await;
+            if graph_state.is_err() {
+                println!(
+                    "Graph id: {} failed to get state: {}\n",
+                    self.data.peg_out_graphs[i].id(),
+                    graph_state.unwrap_err()
+                );
+                continue;
+            }
+            let graph_state = graph_state.unwrap();
+
+            let actions = graph_state.actions_of(GraphRole::Verifier);
+            if actions.is_empty() {
+                println!(
+                    "Graph id: {} state: {}\n",
+                    self.data.peg_out_graphs[i].id(),
+                    graph_state.state
+                );
+            }
+
+            // challenge and burn can both be available, each of them is taken if the policy allows
+            for action in actions {
+                let result = match action {
+                    PegOutAction::PreSign if policy.pre_sign => {
+                        let peg_out_graph = &mut self.data.peg_out_graphs[i];
+                        let pre_signing_state = peg_out_graph.pre_signing_state();
+                        if pre_signing_state == PreSigningState::AwaitingNonces
+                            && !peg_out_graph.has_nonces_of(verifier_context)
+                        {
+                            match peg_out_graph.push_nonces(verifier_context) {
+                                Ok(secret_nonces) => {
+                                    self.verifier_secret_nonces
+                                        .insert(peg_out_graph.id().clone(), secret_nonces);
+                                    secret_nonces_pushed = true;
+                                    Some(Ok(()))
+                                }
+                                Err(err) => Some(Err(err)),
+                            }
+                        } else if pre_signing_state == PreSigningState::AwaitingSignatures
+                            && (!peg_out_graph.has_signatures_of(verifier_context)
+                                || peg_out_graph.has_all_signatures(verifier_context))
+                        {
+                            let mut no_secret_nonces = HashMap::new();
+                            let secret_nonces = self
+                                .verifier_secret_nonces
+                                .get_mut(peg_out_graph.id())
+                                .unwrap_or(&mut no_secret_nonces);
+                            Some(peg_out_graph.pre_sign(verifier_context, secret_nonces))
+                        } else {
+                            None // waiting for the other verifiers
+                        }
+                    }
+                    PegOutAction::Challenge if policy.challenge => {
+                        let is_claim_valid = self.data.peg_out_graphs[i]
+                            .is_kick_off_claim_valid(self.chain.as_ref())
+                            .await;
+                        if is_claim_valid.as_ref().is_ok_and(|is_valid| *is_valid) {
+                            println!(
+                                "Graph id: {} kick-off claim verified, not challenging\n",
+                                self.data.peg_out_graphs[i].id()
+                            );
+                            continue;
+                        }
+
+                        let amount = self.data.peg_out_graphs[i].challenge_crowdfunding_amount();
+                        let utxo = self
+                            .get_initial_utxo(verifier_address.clone(), amount)
+                            .await;
+                        if utxo.is_err() {
+                            Some(Err(utxo.unwrap_err()))
+                        } else if utxo.as_ref().unwrap().is_none() {
+                            println!(
+                                "Graph id: {} cannot challenge, no utxo of {} found at {}",
+                                self.data.peg_out_graphs[i].id(),
+                                amount,
+                                verifier_address
+                            );
+                            None
+                        } else {
+                            let utxo = utxo.unwrap().unwrap();
+                            let challenge_result = self.data.peg_out_graphs[i]
+                                .challenge(
+                                    self.chain.as_ref(),
+                                    verifier_context,
+                                    &vec![InputWithScript {
+                                        outpoint: OutPoint {
+                                            txid: utxo.txid,
+                                            vout: utxo.vout,
+                                        },
+                                        amount: utxo.value,
+                                        script: &verifier_script,
+                                    }],
+                                    &verifier_context.verifier_keypair,
+                                    verifier_address.script_pubkey(),
+                                )
+                                .await;
+                            Some(challenge_result)
+                        }
+                    }
+                    PegOutAction::Burn if policy.burn => Some(
+                        self.data.peg_out_graphs[i]
+                            .burn(self.chain.as_ref(), reward_script_pubkey.clone())
+                            .await,
+                    ),
+                    _ => None,
+                };
+
+                data_changed |=
+                    log_action_result(self.data.peg_out_graphs[i].id(), &action, result);
+            }
+        }
+
+        // Pre-signing removed every secret nonce it used, they must not stay in the store either.
+        self.verifier_secret_nonces
+            .retain(|_, graph_secret_nonces| !graph_secret_nonces.is_empty());
+
+        // The public nonces are only published once the secret nonces are stored, a verifier
+        // restarting in between would not be able to pre-sign otherwise.
+        if (secret_nonces_pushed
+            || count_secret_nonces(&self.verifier_secret_nonces) != secret_nonce_count)
+            && self.verifier_secret_nonce_store.is_some()
+        {
+            self.verifier_secret_nonce_store
+                .as_ref()
+                .unwrap()
+                .save(&self.verifier_secret_nonces)?;
+        }
+
+        Ok(data_changed)
+    }
+
+    // Status reports of every graph the client takes part in, one per role of the client.
+    pub async fn status(&self) -> Result<Vec<GraphStatusReport>, BridgeError> {
+        let mut reports = vec![];
+        if self.depositor_key_chain.is_some() {
+            reports.extend(self.depositor_status().await?);
+        }
+        if self.operator_context.is_some() {
+            reports.extend(self.operator_status().await?);
+        }
+        if self.verifier_context.is_some() {
+            reports.extend(self.verifier_status().await?);
+        }
+        if self.withdrawer_key_chain.is_some() {
+            reports.extend(self.withdrawer_status().await?);
+        }
+
+        Ok(reports)
+    }
+
+    pub async fn depositor_status(&self) -> Result<Vec<GraphStatusReport>, BridgeError> {
+        if self.depositor_key_chain.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Depositor")));
+        }
+
+        let depositor_public_keys = Self::used_public_keys(
+            self.depositor_key_chain.as_ref().unwrap(),
+            KeyRole::Depositor,
+            &self.depositor_public_keys(),
+        )?;
+        let mut reports = vec![];
         for peg_in_graph in self.data.peg_in_graphs.iter() {
-            if peg_in_graph.depositor_public_key.eq(depositor_public_key) {
-                let status = peg_in_graph.depositor_status(&self.esplora).await;
-                println!("Graph id: {} status: {}\n", peg_in_graph.id(), status);
+            if depositor_public_keys.contains(&peg_in_graph.depositor_public_key) {
+                reports.push(
+                    peg_in_graph
+                        .status_report(self.chain.as_ref(), GraphRole::Depositor)
+                        .await,
+                );
             }
         }
+
+        Ok(reports)
     }
 
-    async fn operator_status(&self) {
+    pub async fn operator_status(&self) -> Result<Vec<GraphStatusReport>, BridgeError> {
         if self.operator_context.is_none() {
-            panic!("Operator context must be initialized");
+            return Err(BridgeError::MissingContext(String::from("Operator")));
         }
 
         let mut peg_out_graphs_by_id: HashMap<&String, &PegOutGraph> = HashMap::new();
@@ -240,64 +955,206 @@ impl BitVMClient {
         }
 
         let operator_public_key = &self.operator_context.as_ref().unwrap().operator_public_key;
+        let mut reports = vec![];
         for peg_in_graph in self.data.peg_in_graphs.iter() {
             let peg_out_graph_id = generate_id(peg_in_graph, operator_public_key);
-            if !peg_out_graphs_by_id.contains_key(&peg_out_graph_id) {
-                println!(
-                    "Graph id: {} status: {}\n",
-                    peg_in_graph.id(),
-                    "Missing peg out graph"
-                );
-            } else {
-                let peg_out_graph = peg_out_graphs_by_id.get(&peg_out_graph_id).unwrap();
-                let status = peg_out_graph.operator_status(&self.esplora).await;
-                println!("Graph id: {} status: {}\n", peg_out_graph.id(), status);
+            match peg_out_graphs_by_id.get(&peg_out_graph_id) {
+                Some(peg_out_graph) => reports.push(
+                    peg_out_graph
+                        .status_report(self.chain.as_ref(), GraphRole::Operator)
+                        .await,
+                ),
+                None => {
+                    let mut report = GraphStatusReport::new(peg_in_graph.id(), GraphRole::Operator);
+                    report.set_error("Missing peg out graph");
+                    report.next_action = Some(String::from("create peg-out graph"));
+                    reports.push(report);
+                }
             }
         }
+
+        Ok(reports)
     }
 
-    async fn verifier_status(&self) {
+    pub async fn verifier_status(&self) -> Result<Vec<GraphStatusReport>, BridgeError> {
         if self.verifier_context.is_none() {
-            panic!("Verifier context must be initialized");
+            return Err(BridgeError::MissingContext(String::from("Verifier")));
+        }
+
+        let mut reports = vec![];
+        for peg_in_graph in self.data.peg_in_graphs.iter() {
+            reports.extend(
+                self.registered_peg_out_graph_reports(peg_in_graph, GraphRole::Verifier)
+                    .await,
+            );
+        }
+
+        Ok(reports)
+    }
+
+    // Every registered operator is expected to create a peg-out graph for every peg-in, with its
+    // own client: the kick-off and the commitment to y are signed with the operator's keys.
+    async fn registered_peg_out_graph_reports(
+        &self,
+        peg_in_graph: &PegInGraph,
+        role: GraphRole,
+    ) -> Vec<GraphStatusReport> {
+        let mut reports = vec![];
+        for (operator_public_key, peg_out_graph_id) in self.registry.peg_out_graph_ids(peg_in_graph)
+        {
+            let peg_out_graph = self
+                .data
+                .peg_out_graphs
+                .iter()
+                .find(|peg_out_graph| peg_out_graph.id().eq(&peg_out_graph_id));
+            match peg_out_graph {
+                Some(peg_out_graph) => {
+                    reports.push(peg_out_graph.status_report(self.chain.as_ref(), role).await)
+                }
+                None => {
+                    let mut report = GraphStatusReport::new(&peg_out_graph_id, role);
+                    report.set_error(format!(
+                        "Missing peg out graph of operator {}",
+                        operator_public_key
+                    ));
+                    reports.push(report);
+                }
+            }
         }
 
+        reports
+    }
+
+    // The peg-out graphs requested by the withdrawer.
+    pub async fn withdrawer_status(&self) -> Result<Vec<GraphStatusReport>, BridgeError> {
+        if self.withdrawer_key_chain.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Withdrawer")));
+        }
+
+        let withdrawer_public_keys = Self::used_public_keys(
+            self.withdrawer_key_chain.as_ref().unwrap(),
+            KeyRole::Withdrawer,
+            &self.withdrawer_public_keys(),
+        )?;
+        let mut reports = vec![];
         for peg_out_graph in self.data.peg_out_graphs.iter() {
-            let status = peg_out_graph.verifier_status(&self.esplora).await;
-            println!("Graph id: {} status: {}\n", peg_out_graph.id(), status);
+            if peg_out_graph.peg_out_request().is_some_and(|request| {
+                withdrawer_public_keys.contains(&request.withdrawer_public_key)
+            }) {
+                reports.push(
+                    peg_out_graph
+                        .status_report(self.chain.as_ref(), GraphRole::Withdrawer)
+                        .await,
+                );
+            }
         }
+
+        Ok(reports)
     }
 
-    pub async fn create_peg_in_graph(&mut self, input: Input, evm_address: &str) {
-        if self.depositor_context.is_none() {
-            panic!("Depositor context must be initialized");
+    // The depositor key of the next peg-in graph, the input of create_peg_in_graph has to be paid
+    // to it.
+    pub fn next_depositor_public_key(&self) -> Result<PublicKey, BridgeError> {
+        if self.depositor_key_chain.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Depositor")));
         }
 
-        let peg_in_graph =
-            PegInGraph::new(self.depositor_context.as_ref().unwrap(), input, evm_address);
+        let key_chain = self.depositor_key_chain.as_ref().unwrap();
+        let index =
+            Self::next_key_index(key_chain, KeyRole::Depositor, &self.depositor_public_keys())?;
 
-        // TODO broadcast peg in txn
+        key_chain.derive_public_key(KeyRole::Depositor, index)
+    }
+
+    pub async fn create_peg_in_graph(
+        &mut self,
+        input: Input,
+        evm_address: &str,
+    ) -> Result<(), BridgeError> {
+        if self.depositor_key_chain.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Depositor")));
+        }
+
+        let key_chain = self.depositor_key_chain.as_ref().unwrap();
+        let index =
+            Self::next_key_index(key_chain, KeyRole::Depositor, &self.depositor_public_keys())?;
+        let (n_of_n_public_key, n_of_n_taproot_public_key) = self.registry.n_of_n_public_key();
+        let depositor_context = DepositorContext::from_key_chain(
+            key_chain,
+            index,
+            &n_of_n_public_key,
+            &n_of_n_taproot_public_key,
+        )?;
+
+        let peg_in_graph = PegInGraph::new(&depositor_context, input, evm_address);
+
+        // the deposit is broadcast separately, see broadcast_peg_in_deposit
 
         self.data.peg_in_graphs.push(peg_in_graph);
 
         // self.save().await;
+
+        Ok(())
     }
 
-    pub async fn broadcast_peg_in_refund(&mut self, peg_in_graph_id: &str) {
+    pub async fn broadcast_peg_in_deposit(
+        &mut self,
+        peg_in_graph_id: &str,
+    ) -> Result<(), BridgeError> {
         let peg_in_graph = self
             .data
             .peg_in_graphs
-            .iter()
-            .find(|&peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
+            .iter_mut()
+            .find(|peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
+        if peg_in_graph.is_none() {
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
+        }
+
+        peg_in_graph.unwrap().deposit(self.chain.as_ref()).await
+    }
+
+    pub async fn broadcast_peg_in_confirm(
+        &mut self,
+        peg_in_graph_id: &str,
+    ) -> Result<(), BridgeError> {
+        let peg_in_graph = self
+            .data
+            .peg_in_graphs
+            .iter_mut()
+            .find(|peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
+        if peg_in_graph.is_none() {
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
+        }
+
+        peg_in_graph.unwrap().confirm(self.chain.as_ref()).await
+    }
+
+    pub async fn broadcast_peg_in_refund(
+        &mut self,
+        peg_in_graph_id: &str,
+    ) -> Result<(), BridgeError> {
+        let peg_in_graph = self
+            .data
+            .peg_in_graphs
+            .iter_mut()
+            .find(|peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
         if peg_in_graph.is_none() {
-            panic!("Invalid graph id");
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
         }
 
-        // Attempt to broadcast refund tx
+        peg_in_graph.unwrap().refund(self.chain.as_ref()).await
     }
 
-    pub async fn create_peg_out_graph(&mut self, peg_in_graph_id: &str, kickoff_input: Input) {
+    // Creates the peg-out graph of the local operator. The returned reports are those of the peg-out
+    // graphs of every registered operator for the peg-in, the other operators create theirs with
+    // their own clients.
+    pub async fn create_peg_out_graph(
+        &mut self,
+        peg_in_graph_id: &str,
+        kickoff_input: Input,
+    ) -> Result<Vec<GraphStatusReport>, BridgeError> {
         if self.operator_context.is_none() {
-            panic!("Operator context must be initialized");
+            return Err(BridgeError::MissingContext(String::from("Operator")));
         }
         let operator_public_key = &self.operator_context.as_ref().unwrap().operator_public_key;
 
@@ -307,7 +1164,7 @@ impl BitVMClient {
             .iter()
             .find(|&peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
         if peg_in_graph.is_none() {
-            panic!("Invalid graph id");
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
         }
 
         let peg_out_graph_id = generate_id(peg_in_graph.unwrap(), operator_public_key);
@@ -317,7 +1174,7 @@ impl BitVMClient {
             .iter()
             .find(|&peg_out_graph| peg_out_graph.id().eq(&peg_out_graph_id));
         if peg_out_graph.is_some() {
-            panic!("Peg out graph already exists");
+            return Err(BridgeError::GraphAlreadyExists(peg_out_graph_id));
         }
 
         let peg_out_graph = PegOutGraph::new(
@@ -326,123 +1183,231 @@ impl BitVMClient {
             kickoff_input,
         );
 
-        // peg_out_graph.kick_off(&self.esplora).await;
+        // peg_out_graph.kick_off(self.chain.as_ref()).await;
 
         self.data.peg_out_graphs.push(peg_out_graph);
 
         // self.save().await;
+
+        let peg_in_graph = self
+            .data
+            .peg_in_graphs
+            .iter()
+            .find(|&peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id))
+            .unwrap();
+
+        Ok(self
+            .registered_peg_out_graph_reports(peg_in_graph, GraphRole::Operator)
+            .await)
     }
 
-    pub async fn get_initial_utxo(&self, address: Address, amount: Amount) -> Option<Utxo> {
-        let utxos = self.esplora.get_address_utxo(address).await.unwrap();
+    // Moves the operator's funds to the Commit Y address of the operator's peg-out graph of the
+    // peg-in graph. The returned input is the kick-off input of create_peg_out_graph.
+    pub async fn broadcast_commit_y_funding(
+        &self,
+        peg_in_graph_id: &str,
+        operator_input: Input,
+    ) -> Result<Input, BridgeError> {
+        if self.operator_context.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Operator")));
+        }
+        let operator_context = self.operator_context.as_ref().unwrap();
+
+        let peg_in_graph = self
+            .data
+            .peg_in_graphs
+            .iter()
+            .find(|&peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id));
+        if peg_in_graph.is_none() {
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
+        }
+
+        let peg_out_graph_id =
+            generate_id(peg_in_graph.unwrap(), &operator_context.operator_public_key);
+        let commit_y_funding_transaction = CommitYFundingTransaction::new(
+            operator_context,
+            operator_input,
+            &generate_commit_y_public_key(operator_context, &peg_out_graph_id),
+        );
+        self.chain
+            .broadcast(&commit_y_funding_transaction.finalize())
+            .await?;
+
+        Ok(commit_y_funding_transaction.kick_off_input())
+    }
+
+    // Registers the peg-out request of the withdrawer with every peg-out graph of the peg-in, any
+    // of the operators can pay it to the destination address.
+    pub async fn request_peg_out(
+        &mut self,
+        peg_in_graph_id: &str,
+        evm_address: &str,
+        evm_burn_tx_hash: &str,
+        destination_address: &Address,
+    ) -> Result<(), BridgeError> {
+        if self.withdrawer_key_chain.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Withdrawer")));
+        }
+        if !self
+            .data
+            .peg_in_graphs
+            .iter()
+            .any(|peg_in_graph| peg_in_graph.id().eq(peg_in_graph_id))
+        {
+            return Err(BridgeError::GraphNotFound(peg_in_graph_id.to_string()));
+        }
+
+        // a fresh withdrawer key for every peg-in, a repeated request keeps the key of the first one
+        let key_chain = self.withdrawer_key_chain.as_ref().unwrap();
+        let withdrawer_public_keys = Self::used_public_keys(
+            key_chain,
+            KeyRole::Withdrawer,
+            &self.withdrawer_public_keys(),
+        )?;
+        let requested_public_key = self
+            .data
+            .peg_out_graphs
+            .iter()
+            .filter(|peg_out_graph| peg_out_graph.peg_in_graph_id().eq(peg_in_graph_id))
+            .filter_map(|peg_out_graph| peg_out_graph.peg_out_request())
+            .map(|request| request.withdrawer_public_key)
+            .find(|public_key| withdrawer_public_keys.contains(public_key));
+        let withdrawer_public_key = match requested_public_key {
+            Some(public_key) => public_key,
+            None => key_chain
+                .derive_public_key(KeyRole::Withdrawer, withdrawer_public_keys.len() as u32)?,
+        };
+
+        let request = PegOutRequest {
+            peg_in_graph_id: peg_in_graph_id.to_string(),
+            withdrawer_public_key,
+            withdrawer_evm_address: evm_address.to_string(),
+            evm_burn_tx_hash: evm_burn_tx_hash.to_string(),
+            destination_script_pubkey: destination_address.script_pubkey(),
+        };
+        let peg_out_graphs: Vec<&mut PegOutGraph> = self
+            .data
+            .peg_out_graphs
+            .iter_mut()
+            .filter(|peg_out_graph| peg_out_graph.peg_in_graph_id().eq(peg_in_graph_id))
+            .collect();
+        if peg_out_graphs.is_empty() {
+            return Err(BridgeError::PegOut(format!(
+                "No peg-out graph for peg-in graph {}",
+                peg_in_graph_id
+            )));
+        }
+        // a peg-in is paid to a single withdrawer
+        if peg_out_graphs.iter().any(|peg_out_graph| {
+            peg_out_graph
+                .peg_out_request()
+                .is_some_and(|registered_request| registered_request != request)
+        }) {
+            return Err(BridgeError::PegOut(format!(
+                "Peg-in graph {} has already been requested by another withdrawer",
+                peg_in_graph_id
+            )));
+        }
+
+        for peg_out_graph in peg_out_graphs {
+            peg_out_graph.request_peg_out(&request)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn peg_out(
+        &mut self,
+        peg_out_graph_id: &str,
+        input: Input,
+    ) -> Result<(), BridgeError> {
+        if self.operator_context.is_none() {
+            return Err(BridgeError::MissingContext(String::from("Operator")));
+        }
+
+        let peg_out_graph = self
+            .data
+            .peg_out_graphs
+            .iter_mut()
+            .find(|peg_out_graph| peg_out_graph.id().eq(peg_out_graph_id));
+        if peg_out_graph.is_none() {
+            return Err(BridgeError::GraphNotFound(peg_out_graph_id.to_string()));
+        }
+
+        peg_out_graph
+            .unwrap()
+            .peg_out(
+                self.chain.as_ref(),
+                self.operator_context.as_ref().unwrap(),
+                input,
+            )
+            .await
+    }
+
+    pub async fn get_initial_utxo(
+        &self,
+        address: Address,
+        amount: Amount,
+    ) -> Result<Option<Utxo>, BridgeError> {
+        let utxos = self.chain.get_address_utxo(address).await?;
         let possible_utxos = utxos
             .into_iter()
             .filter(|utxo| utxo.value == amount)
             .collect::<Vec<_>>();
         if !possible_utxos.is_empty() {
-            Some(possible_utxos[0].clone())
+            Ok(Some(possible_utxos[0].clone()))
         } else {
-            None
+            Ok(None)
         }
     }
 
-    pub async fn get_initial_utxos(&self, address: Address, amount: Amount) -> Option<Vec<Utxo>> {
-        let utxos = self.esplora.get_address_utxo(address).await.unwrap();
+    pub async fn get_initial_utxos(
+        &self,
+        address: Address,
+        amount: Amount,
+    ) -> Result<Option<Vec<Utxo>>, BridgeError> {
+        let utxos = self.chain.get_address_utxo(address).await?;
         let possible_utxos = utxos
             .into_iter()
             .filter(|utxo| utxo.value == amount)
             .collect::<Vec<_>>();
         if !possible_utxos.is_empty() {
-            Some(possible_utxos)
+            Ok(Some(possible_utxos))
         } else {
-            None
-        }
-    }
-
-    // pub async fn execute_possible_txs(
-    //     &mut self,
-    //     context: &dyn BaseContext,
-    //     graph: &mut CompiledBitVMGraph,
-    // ) {
-    //     // Iterate through our UTXO set and execute an executable TX
-    //     // TODO: May have to respect an order here.
-    //     let mut remove_utxo = None;
-    //     for (outpoint, _) in self.utxo_set.iter() {
-    //         match graph.get(outpoint) {
-    //             Some(subsequent_txs) => {
-    //                 for bridge_transaction in subsequent_txs {
-    //                     // TODO: Check whether the transaction is executable
-    //                     let tx = bridge_transaction.finalize();
-    //                     match self.esplora.broadcast(&tx).await {
-    //                         Ok(_) => {
-    //                             println!(
-    //                                 "Succesfully broadcast next transaction with id: {}",
-    //                                 tx.compute_txid()
-    //                             );
-    //                             remove_utxo = Some(*outpoint);
-    //                             break;
-    //                         }
-    //                         Err(err) => panic!("Tx Broadcast Error: {}", err),
-    //                     }
-    //                 }
-    //             }
-    //             None => continue,
-    //         }
-    //     }
-
-    //     if let Some(remove_utxo) = remove_utxo {
-    //         self.utxo_set.remove(&remove_utxo);
-    //         graph.remove(&remove_utxo);
-    //     }
-    // }
-
-    // pub async fn listen(
-    //     &mut self,
-    //     context: &dyn BaseContext,
-    //     initial_outpoint: OutPoint,
-    //     graph: &mut CompiledBitVMGraph,
-    // ) {
-    //     let builder = Builder::new(ESPLORA_URL);
-    //     let esplora = builder.build_async().unwrap();
-    //     let mut latest_hash =
-    //         BlockHash::from_str("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
-    //             .unwrap();
-    //     self.utxo_set.insert(initial_outpoint, Height::ZERO);
-
-    //     while !graph.is_empty() {
-    //         if let Ok(block_hash) = esplora.get_tip_hash().await {
-    //             if block_hash == latest_hash {
-    //                 sleep(Duration::from_secs(10));
-    //                 continue;
-    //             }
-    //             latest_hash = block_hash;
-    //             // TODO: This assumes that the tip did not increase. There should be a
-    //             // better API endpoint like /block-height/{block_hash}
-    //             let block_height = esplora.get_height().await.unwrap();
-    //             let block = esplora
-    //                 .get_block_by_hash(&block_hash)
-    //                 .await
-    //                 .unwrap()
-    //                 .unwrap();
-
-    //             // Handle new block received logic
-    //             println!("Received block {}", block_hash);
-
-    //             for tx in block.txdata {
-    //                 for (vout, _) in tx.output.iter().enumerate() {
-    //                     let outpoint = OutPoint {
-    //                         txid: tx.compute_txid(),
-    //                         vout: vout as u32,
-    //                     };
-    //                     if graph.contains_key(&outpoint) {
-    //                         // Update our UTXO set
-    //                         self.utxo_set
-    //                             .insert(outpoint, Height::from_consensus(block_height).unwrap());
-    //                     }
-    //                 }
-    //             }
-    //             self.execute_possible_txs(context, graph).await;
-    //         }
-    //     }
-    // }
+            Ok(None)
+        }
+    }
+}
+
+// Logs the outcome of an automatic action. Returns whether the action was executed.
+fn count_secret_nonces(secret_nonces: &HashMap<String, GraphSecretNonces>) -> usize {
+    secret_nonces
+        .values()
+        .flat_map(|graph_secret_nonces| graph_secret_nonces.values())
+        .map(|tx_secret_nonces| tx_secret_nonces.len())
+        .sum()
+}
+
+fn log_action_result(
+    graph_id: &String,
+    status: &dyn Display,
+    result: Option<Result<(), BridgeError>>,
+) -> bool {
+    match result {
+        Some(Ok(())) => {
+            println!("Graph id: {} executed: {}\n", graph_id, status);
+            true
+        }
+        Some(Err(err)) => {
+            println!(
+                "Graph id: {} failed to execute {}: {}\n",
+                graph_id, status, err
+            );
+            false
+        }
+        None => {
+            println!("Graph id: {} status: {}\n", graph_id, status);
+            false
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use aws_sdk_s3::{
     config::{Credentials, Region},
     error::SdkError,
@@ -7,6 +8,8 @@ use aws_sdk_s3::{
 };
 use dotenv;
 
+use super::base::StorageBackend;
+
 // To use this data store, create a .env file in the base directory with the following values:
 // export BRIDGE_AWS_ACCESS_KEY_ID="..."
 // export BRIDGE_AWS_SECRET_ACCESS_KEY="..."
@@ -44,8 +47,11 @@ impl AwsS3 {
             bucket: bucket.unwrap(),
         })
     }
+}
 
-    pub async fn list_objects(&self) -> Vec<String> {
+#[async_trait]
+impl StorageBackend for AwsS3 {
+    async fn list_objects(&self) -> Result<Vec<String>, String> {
         let mut response = self
             .client
             .list_objects_v2()
@@ -68,10 +74,10 @@ impl AwsS3 {
             }
         }
 
-        keys
+        Ok(keys)
     }
 
-    pub async fn fetch_json(&self, key: &str) -> Result<String, String> {
+    async fn fetch_json(&self, key: &str) -> Result<String, String> {
         let response = self.get_object(key).await;
         match response {
             Ok(buffer) => {
@@ -85,7 +91,7 @@ impl AwsS3 {
         }
     }
 
-    pub async fn upload_json(&self, key: &str, json: String) -> Result<usize, &str> {
+    async fn upload_json(&self, key: &str, json: String) -> Result<usize, String> {
         let bytes = json.as_bytes().to_vec();
         let size = bytes.len();
         let byte_stream = ByteStream::from(bytes);
@@ -95,10 +101,12 @@ impl AwsS3 {
 
         match response {
             Ok(_) => Ok(size),
-            Err(_) => Err("Failed to save json file"),
+            Err(_) => Err(String::from("Failed to save json file")),
         }
     }
+}
 
+impl AwsS3 {
     async fn get_object(&self, key: &str) -> Result<Vec<u8>, String> {
         let object = self
             .client
@@ -1,33 +1,153 @@
+use bitcoin::{
+    hashes::{sha256, Hash},
+    key::{Keypair, Secp256k1},
+    secp256k1::{schnorr::Signature, All, Message},
+    XOnlyPublicKey,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::aws_s3::AwsS3;
+use super::{
+    super::super::serialization::{serialize, try_deserialize},
+    aws_s3::AwsS3,
+    base::StorageBackend,
+};
 
-static CLIENT_MISSING_CREDENTIALS_ERROR: &str = "Bridge client is missing AWS S3 credentials";
+static CLIENT_MISSING_STORAGE_BACKEND_ERROR: &str =
+    "Bridge client is missing a storage backend (AWS S3 credentials not found)";
 
 static CLIENT_DATA_SUFFIX: &str = "-bridge-client-data.json";
 static CLIENT_DATA_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(&format!(r"(\d{{13}}){}", CLIENT_DATA_SUFFIX)).unwrap());
 
+// Envelope written to the data store. The signature commits to the serialized client data, so
+// files uploaded by anyone other than an allowed writer are skipped when fetching.
+#[derive(Serialize, Deserialize)]
+struct SignedData {
+    data: String,
+    public_key: XOnlyPublicKey,
+    signature: Signature,
+}
+
+impl SignedData {
+    fn new(secp: &Secp256k1<All>, keypair: &Keypair, data: String) -> Self {
+        let signature = secp.sign_schnorr_no_aux_rand(&Self::message(&data), keypair);
+
+        SignedData {
+            data,
+            public_key: keypair.x_only_public_key().0,
+            signature,
+        }
+    }
+
+    fn verify(&self, secp: &Secp256k1<All>, allowed_public_keys: &Vec<XOnlyPublicKey>) -> bool {
+        allowed_public_keys.contains(&self.public_key)
+            && secp
+                .verify_schnorr(
+                    &self.signature,
+                    &Self::message(&self.data),
+                    &self.public_key,
+                )
+                .is_ok()
+    }
+
+    fn message(data: &str) -> Message {
+        Message::from_digest(sha256::Hash::hash(data.as_bytes()).to_byte_array())
+    }
+}
+
 pub struct DataStore {
-    aws_s3: Option<AwsS3>,
+    backend: Option<Box<dyn StorageBackend>>,
 }
 
 impl DataStore {
     pub fn new() -> Self {
+        match AwsS3::new() {
+            Some(aws_s3) => Self::with_backend(Box::new(aws_s3)),
+            None => Self { backend: None },
+        }
+    }
+
+    pub fn with_backend(backend: Box<dyn StorageBackend>) -> Self {
         Self {
-            aws_s3: AwsS3::new(),
+            backend: Some(backend),
+        }
+    }
+
+    pub async fn fetch_latest_data(
+        &self,
+        allowed_public_keys: &Vec<XOnlyPublicKey>,
+    ) -> Result<Option<String>, &str> {
+        let mut data_keys = self.list_data_keys().await?;
+
+        let secp = Secp256k1::new();
+        while let Some(key) = data_keys.pop() {
+            let signed_data = self.fetch_signed_data(&key).await;
+            if signed_data.is_none() {
+                continue;
+            }
+
+            let signed_data = signed_data.unwrap();
+            if !signed_data.verify(&secp, allowed_public_keys) {
+                println!("Skipping data file with invalid signature: {}", key);
+                continue;
+            }
+
+            println!("Fetched latest data file: {}", key);
+            return Ok(Some(signed_data.data));
         }
+
+        println!("No data file found");
+        Ok(None)
     }
 
-    pub async fn fetch_latest_data(&self) -> Result<Option<String>, &str> {
-        if self.aws_s3.is_none() {
-            return Err(CLIENT_MISSING_CREDENTIALS_ERROR);
+    // The latest data file of an allowed writer, followed by the newer data files of any other
+    // writer, oldest first and each with the public key of its writer. Other writers can only be
+    // trusted with their own graphs, which the caller has to check.
+    pub async fn fetch_latest_data_files(
+        &self,
+        allowed_public_keys: &Vec<XOnlyPublicKey>,
+    ) -> Result<Vec<(XOnlyPublicKey, String)>, &str> {
+        let mut data_keys = self.list_data_keys().await?;
+
+        let secp = Secp256k1::new();
+        let mut data_files = vec![];
+        while let Some(key) = data_keys.pop() {
+            let signed_data = self.fetch_signed_data(&key).await;
+            if signed_data.is_none() {
+                continue;
+            }
+
+            let signed_data = signed_data.unwrap();
+            if signed_data.verify(&secp, allowed_public_keys) {
+                data_files.push((signed_data.public_key, signed_data.data));
+                break;
+            }
+            if signed_data.verify(&secp, &vec![signed_data.public_key]) {
+                data_files.push((signed_data.public_key, signed_data.data));
+            } else {
+                println!("Skipping data file with invalid signature: {}", key);
+            }
         }
+        data_files.reverse();
 
-        let keys = self.aws_s3.as_ref().unwrap().list_objects().await;
+        Ok(data_files)
+    }
+
+    // Keys of all data files, oldest first.
+    async fn list_data_keys(&self) -> Result<Vec<String>, &str> {
+        if self.backend.is_none() {
+            return Err(CLIENT_MISSING_STORAGE_BACKEND_ERROR);
+        }
+
+        let keys = self.backend.as_ref().unwrap().list_objects().await;
+        if keys.is_err() {
+            return Err("Failed to list data files");
+        }
+        let keys = keys.unwrap();
         let mut data_keys: Vec<String> = keys
             .iter()
             .filter(|key| CLIENT_DATA_REGEX.is_match(key))
@@ -40,21 +160,32 @@ impl DataStore {
             return Ordering::Greater;
         });
 
-        while let Some(key) = data_keys.pop() {
-            let json = self.aws_s3.as_ref().unwrap().fetch_json(&key).await;
-            if json.is_ok() {
-                println!("Fetched latest data file: {}", key);
-                return Ok(Some(json.unwrap()));
-            }
+        Ok(data_keys)
+    }
+
+    async fn fetch_signed_data(&self, key: &str) -> Option<SignedData> {
+        let json = self.backend.as_ref().unwrap().fetch_json(key).await;
+        if json.is_err() {
+            return None;
         }
 
-        println!("No data file found");
-        Ok(None)
+        let signed_data = try_deserialize::<SignedData>(&json.unwrap());
+        if signed_data.is_err() {
+            println!("Skipping unsigned data file: {}", key);
+            return None;
+        }
+
+        Some(signed_data.unwrap())
     }
 
-    pub async fn write_data(&self, json: String) -> Result<String, &str> {
-        if self.aws_s3.is_none() {
-            return Err(CLIENT_MISSING_CREDENTIALS_ERROR);
+    pub async fn write_data(
+        &self,
+        json: String,
+        secp: &Secp256k1<All>,
+        keypair: &Keypair,
+    ) -> Result<String, &str> {
+        if self.backend.is_none() {
+            return Err(CLIENT_MISSING_STORAGE_BACKEND_ERROR);
         }
 
         let time = SystemTime::now()
@@ -63,7 +194,13 @@ impl DataStore {
             .as_millis();
         let key = format!("{}{}", time, CLIENT_DATA_SUFFIX);
 
-        let response = self.aws_s3.as_ref().unwrap().upload_json(&key, json).await;
+        let signed_json = serialize(&SignedData::new(secp, keypair, json));
+        let response = self
+            .backend
+            .as_ref()
+            .unwrap()
+            .upload_json(&key, signed_json)
+            .await;
 
         match response {
             Ok(_) => Ok(key),
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use dotenv;
+use std::path::PathBuf;
+use tokio::fs;
+
+use super::base::StorageBackend;
+
+// To use this data store without a directory argument, add the following value to the .env file:
+// export BRIDGE_DATA_STORE_DIRECTORY="..."
+
+static DEFAULT_DIRECTORY: &str = "bridge_data";
+
+pub struct LocalFile {
+    directory: PathBuf,
+}
+
+impl LocalFile {
+    pub fn new(directory: &str) -> Self {
+        Self {
+            directory: PathBuf::from(directory),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        dotenv::dotenv().ok();
+        let directory = dotenv::var("BRIDGE_DATA_STORE_DIRECTORY");
+
+        match directory {
+            Ok(directory) => Self::new(&directory),
+            Err(_) => Self::new(DEFAULT_DIRECTORY),
+        }
+    }
+
+    fn file_path(&self, key: &str) -> PathBuf { self.directory.join(key) }
+}
+
+#[async_trait]
+impl StorageBackend for LocalFile {
+    async fn list_objects(&self) -> Result<Vec<String>, String> {
+        if !self.directory.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = fs::read_dir(&self.directory)
+            .await
+            .map_err(|err| format!("Failed to read directory: {}", err))?;
+
+        let mut keys: Vec<String> = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|err| format!("Failed to read directory entry: {}", err))?
+        {
+            if let Some(key) = entry.file_name().to_str() {
+                keys.push(key.to_string());
+            }
+        }
+
+        Ok(keys)
+    }
+
+    async fn fetch_json(&self, key: &str) -> Result<String, String> {
+        fs::read_to_string(self.file_path(key))
+            .await
+            .map_err(|err| format!("Failed to get json file: {}", err))
+    }
+
+    async fn upload_json(&self, key: &str, json: String) -> Result<usize, String> {
+        let size = json.len();
+
+        fs::create_dir_all(&self.directory)
+            .await
+            .map_err(|err| format!("Failed to create directory: {}", err))?;
+
+        match fs::write(self.file_path(key), json).await {
+            Ok(_) => Ok(size),
+            Err(_) => Err(String::from("Failed to save json file")),
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod aws_s3;
+pub mod base;
 pub mod data_store;
+pub mod local_file;
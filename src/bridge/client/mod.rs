@@ -1,2 +1,4 @@
+pub mod chain;
 pub mod client;
 pub mod data_store;
+pub mod policy;
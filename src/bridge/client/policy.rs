@@ -0,0 +1,43 @@
+use bitcoin::ScriptBuf;
+use std::time::Duration;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// Controls which actions `BitVMClient::run` executes automatically. Actions that are disabled are
+// only reported, so they can be executed by hand. Disprove is never executed automatically, it
+// needs the failing assert leaf, which cannot be determined yet.
+pub struct ProcessingPolicy {
+    pub poll_interval: Duration,
+
+    // verifier actions
+    pub pre_sign: bool,
+    pub challenge: bool,
+    pub burn: bool,
+    pub reward_script_pubkey: Option<ScriptBuf>, // defaults to the verifier's own address
+
+    // operator actions
+    pub peg_in_confirm: bool,
+    pub kick_off: bool,
+    pub assert: bool,
+    pub take1: bool,
+    pub take2: bool,
+}
+
+impl Default for ProcessingPolicy {
+    fn default() -> Self {
+        ProcessingPolicy {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+
+            pre_sign: true,
+            challenge: false, // spends the verifier's own funds
+            burn: true,
+            reward_script_pubkey: None,
+
+            peg_in_confirm: true,
+            kick_off: true,
+            assert: true,
+            take1: true,
+            take2: true,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::treepp::*;
+use bitcoin::{
+    hashes::{hash160, sha256, Hash},
+    Txid,
+};
+
+use crate::signatures::winternitz::{
+    checksig_verify, checksum, to_digits, PublicKey as WinternitzPublicKeyArray, D, N, N0, N1,
+};
+
+use super::contexts::operator::OperatorContext;
+
+// The claim y is 20 bytes long (N0 digits of 4 bits).
+pub const COMMITMENT_VALUE_LENGTH: usize = (N0 / 2) as usize;
+
+pub type CommitmentValue = [u8; COMMITMENT_VALUE_LENGTH];
+
+// Stored as a Vec since serde only supports arrays of up to 32 elements.
+pub type WinternitzPublicKey = Vec<[u8; 20]>;
+// Witness elements of a Winternitz signature: the hash and the digit of each of the N digits.
+pub type WinternitzSignature = Vec<Vec<u8>>;
+// Upper bound of the witness bytes of a commitment to y: per digit, the hash and the digit with
+// their length prefixes.
+pub const COMMIT_Y_WITNESS_LENGTH: usize = N as usize * (1 + 20 + 1 + 1);
+
+// Winternitz keys are one-time keys, so the kick-off of every peg-out graph commits to its claim
+// y with a key of its own, derived from the operator secret and the id of the graph.
+fn generate_commit_y_secret(context: &OperatorContext, peg_out_graph_id: &str) -> Vec<u8> {
+    let mut data = context.operator_keypair.secret_bytes().to_vec();
+    data.extend_from_slice(b"commit_y");
+    data.extend_from_slice(peg_out_graph_id.as_bytes());
+
+    sha256::Hash::hash(&data).to_byte_array().to_vec()
+}
+
+fn to_message_digits(value: &CommitmentValue) -> [u8; N0 as usize] {
+    let mut message_digits = [0u8; N0 as usize];
+    for (digits, byte) in message_digits.chunks_mut(2).zip(value) {
+        digits[0] = byte & 0b00001111;
+        digits[1] = byte >> 4;
+    }
+
+    message_digits
+}
+
+// Checksum digits followed by the message digits, in the order used by winternitz::sign_digits.
+fn to_signed_digits(value: &CommitmentValue) -> Vec<u8> {
+    let message_digits = to_message_digits(value);
+    let mut digits = to_digits::<N1>(checksum(message_digits)).to_vec();
+    digits.append(&mut message_digits.to_vec());
+
+    digits
+}
+
+// Same hash chain as winternitz::public_key_for_digit, on the secret bytes.
+fn hash_chain(secret: &[u8], digit_index: u32, length: u32) -> hash160::Hash {
+    let mut secret_i = secret.to_vec();
+    secret_i.push(digit_index as u8);
+
+    let mut hash = hash160::Hash::hash(&secret_i);
+    for _ in 0..length {
+        hash = hash160::Hash::hash(&hash[..]);
+    }
+
+    hash
+}
+
+pub fn generate_commit_y_public_key(
+    context: &OperatorContext,
+    peg_out_graph_id: &str,
+) -> WinternitzPublicKey {
+    let secret = generate_commit_y_secret(context, peg_out_graph_id);
+
+    (0..N)
+        .map(|digit_index| hash_chain(&secret, digit_index, D).to_byte_array())
+        .collect()
+}
+
+// Produces the same stack as winternitz::sign, as witness elements.
+pub fn sign_commit_y(
+    context: &OperatorContext,
+    peg_out_graph_id: &str,
+    y: &CommitmentValue,
+) -> WinternitzSignature {
+    let secret = generate_commit_y_secret(context, peg_out_graph_id);
+    let digits = to_signed_digits(y);
+
+    let mut signature = Vec::with_capacity(N as usize * 2);
+    for i in 0..N {
+        let digit = digits[(N - 1 - i) as usize];
+        signature.push(
+            hash_chain(&secret, i, digit as u32)
+                .to_byte_array()
+                .to_vec(),
+        );
+        signature.push(if digit == 0 { vec![] } else { vec![digit] });
+    }
+
+    signature
+}
+
+// Checks the signature against the public key off-chain and returns the committed y.
+pub fn verify_commit_y(
+    public_key: &WinternitzPublicKey,
+    signature: &WinternitzSignature,
+) -> Result<CommitmentValue, String> {
+    if public_key.len() != N as usize || signature.len() != N as usize * 2 {
+        return Err(String::from("Winternitz signature has an invalid length"));
+    }
+
+    let mut digits = vec![0u8; N as usize];
+    for i in 0..N as usize {
+        let digit = match signature[i * 2 + 1].as_slice() {
+            [] => 0,
+            [digit] if *digit as u32 <= D => *digit,
+            _ => return Err(format!("Winternitz signature digit {} is invalid", i)),
+        };
+
+        let mut hash = hash160::Hash::from_slice(&signature[i * 2])
+            .map_err(|_| format!("Winternitz signature hash {} is invalid", i))?;
+        for _ in digit as u32..D {
+            hash = hash160::Hash::hash(&hash[..]);
+        }
+        if *hash.as_byte_array() != public_key[i] {
+            return Err(format!("Winternitz signature digit {} does not verify", i));
+        }
+
+        digits[N as usize - 1 - i] = digit;
+    }
+
+    let mut value = [0u8; COMMITMENT_VALUE_LENGTH];
+    for (byte, nibbles) in value.iter_mut().zip(digits[N1..].chunks(2)) {
+        *byte = nibbles[0] | (nibbles[1] << 4);
+    }
+    if to_signed_digits(&value) != digits {
+        return Err(String::from("Winternitz signature has an invalid checksum"));
+    }
+
+    Ok(value)
+}
+
+// Consumes the commitment to y and leaves nothing on the stack. The public key must have N
+// elements, PegOutGraph::verify rejects graphs with any other commit y key.
+pub fn generate_commit_y_verify_script(public_key: &WinternitzPublicKey) -> Script {
+    let public_key: WinternitzPublicKeyArray = public_key
+        .clone()
+        .try_into()
+        .expect("Winternitz public key has an invalid length");
+
+    script! {
+        { checksig_verify(&public_key) }
+        for _ in 0..COMMITMENT_VALUE_LENGTH / 2 {
+            OP_2DROP
+        }
+    }
+}
+
+// The claim y of a kick-off is the hash of the peg-out transaction the operator fronted.
+pub fn generate_peg_out_claim(peg_out_txid: &Txid) -> CommitmentValue {
+    *hash160::Hash::hash(&peg_out_txid.to_byte_array()).as_byte_array()
+}
@@ -1,6 +1,6 @@
 use bitcoin::{taproot::TaprootSpendInfo, Address, ScriptBuf, Sequence, TxIn, Witness};
 
-use super::super::transactions::base::Input;
+use super::super::{error::BridgeError, transactions::base::Input};
 
 pub fn generate_default_tx_in(input: &Input) -> TxIn {
     TxIn {
@@ -20,9 +20,13 @@ pub trait P2wshConnector {
 }
 
 pub trait TaprootConnector {
-    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> ScriptBuf;
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError>;
 
-    fn generate_taproot_leaf_tx_in(&self, leaf_index: u32, input: &Input) -> TxIn;
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError>;
 
     fn generate_taproot_spend_info(&self) -> TaprootSpendInfo;
 
@@ -1,7 +1,5 @@
 use bitcoin::{Address, Network, PublicKey, ScriptBuf, Sequence, TxIn};
 
-use crate::bridge::constants::NUM_BLOCKS_PER_WEEK;
-
 use super::{
     super::{scripts::*, transactions::base::Input},
     connector::*,
@@ -14,15 +12,15 @@ pub struct Connector1 {
 }
 
 impl Connector1 {
-    pub fn new(network: Network, operator_public_key: &PublicKey) -> Self {
+    pub fn new(
+        network: Network,
+        operator_public_key: &PublicKey,
+        num_blocks_timelock: u32,
+    ) -> Self {
         Connector1 {
             network,
             operator_public_key: operator_public_key.clone(),
-            num_blocks_timelock: if network == Network::Bitcoin {
-                NUM_BLOCKS_PER_WEEK * 2
-            } else {
-                1
-            },
+            num_blocks_timelock,
         }
     }
 }
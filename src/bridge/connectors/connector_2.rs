@@ -1,7 +1,5 @@
 use bitcoin::{Address, Network, PublicKey, ScriptBuf, Sequence, TxIn};
 
-use crate::bridge::constants::NUM_BLOCKS_PER_WEEK;
-
 use super::{
     super::{scripts::*, transactions::base::Input},
     connector::*,
@@ -14,15 +12,15 @@ pub struct Connector2 {
 }
 
 impl Connector2 {
-    pub fn new(network: Network, operator_public_key: &PublicKey) -> Self {
+    pub fn new(
+        network: Network,
+        operator_public_key: &PublicKey,
+        num_blocks_timelock: u32,
+    ) -> Self {
         Connector2 {
             network,
             operator_public_key: operator_public_key.clone(),
-            num_blocks_timelock: if network == Network::Bitcoin {
-                NUM_BLOCKS_PER_WEEK * 2
-            } else {
-                1
-            },
+            num_blocks_timelock,
         }
     }
 }
@@ -42,8 +40,7 @@ impl P2wshConnector for Connector2 {
 
     fn generate_tx_in(&self, input: &Input) -> TxIn {
         let mut tx_in = generate_default_tx_in(input);
-        tx_in.sequence =
-            Sequence(u32::try_from(NUM_BLOCKS_PER_WEEK * 2).ok().unwrap() & 0xFFFFFFFF);
+        tx_in.sequence = Sequence((self.num_blocks_timelock) & 0xFFFFFFFF);
         tx_in
     }
 }
@@ -1,32 +1,68 @@
-use bitcoin::{Address, Network, PublicKey, ScriptBuf, TxIn};
+use bitcoin::{
+    key::Secp256k1,
+    taproot::{TaprootBuilder, TaprootSpendInfo},
+    Address, Network, ScriptBuf, TxIn, XOnlyPublicKey,
+};
+use serde::{Deserialize, Serialize};
 
 use super::{
-    super::{scripts::*, transactions::base::Input},
+    super::{error::BridgeError, scripts::*, transactions::base::Input},
     connector::*,
 };
 
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct Connector3 {
     pub network: Network,
-    pub n_of_n_public_key: PublicKey,
+    pub n_of_n_taproot_public_key: XOnlyPublicKey,
 }
 
 impl Connector3 {
-    pub fn new(network: Network, n_of_n_public_key: &PublicKey) -> Self {
+    pub fn new(network: Network, n_of_n_taproot_public_key: &XOnlyPublicKey) -> Self {
         Connector3 {
             network,
-            n_of_n_public_key: n_of_n_public_key.clone(),
+            n_of_n_taproot_public_key: n_of_n_taproot_public_key.clone(),
         }
     }
+
+    // Leaf[0]: spendable by multisig of VPK[1…N]
+    fn generate_taproot_leaf0_script(&self) -> ScriptBuf {
+        generate_pay_to_pubkey_taproot_script(&self.n_of_n_taproot_public_key)
+    }
+
+    fn generate_taproot_leaf0_tx_in(&self, input: &Input) -> TxIn { generate_default_tx_in(input) }
 }
 
-impl P2wshConnector for Connector3 {
-    fn generate_script(&self) -> ScriptBuf {
-        generate_pay_to_pubkey_script(&self.n_of_n_public_key)
+impl TaprootConnector for Connector3 {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
+        match leaf_index {
+            0 => Ok(self.generate_taproot_leaf0_script()),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
+        }
     }
 
-    fn generate_address(&self) -> Address {
-        generate_pay_to_pubkey_script_address(self.network, &self.n_of_n_public_key)
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
+        match leaf_index {
+            0 => Ok(self.generate_taproot_leaf0_tx_in(input)),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
+        }
+    }
+
+    fn generate_taproot_spend_info(&self) -> TaprootSpendInfo {
+        TaprootBuilder::new()
+            .add_leaf(0, self.generate_taproot_leaf0_script())
+            .expect("Unable to add leaf0")
+            .finalize(&Secp256k1::new(), self.n_of_n_taproot_public_key)
+            .expect("Unable to finalize taproot")
     }
 
-    fn generate_tx_in(&self, input: &Input) -> TxIn { generate_default_tx_in(input) }
+    fn generate_taproot_address(&self) -> Address {
+        Address::p2tr_tweaked(
+            self.generate_taproot_spend_info().output_key(),
+            self.network,
+        )
+    }
 }
@@ -6,7 +6,7 @@ use bitcoin::{
 use serde::{Deserialize, Serialize};
 
 use super::{
-    super::{scripts::*, transactions::base::Input},
+    super::{error::BridgeError, scripts::*, transactions::base::Input},
     connector::*,
 };
 
@@ -46,19 +46,23 @@ impl ConnectorA {
 }
 
 impl TaprootConnector for ConnectorA {
-    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> ScriptBuf {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_script(),
-            1 => self.generate_taproot_leaf1_script(),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_script()),
+            1 => Ok(self.generate_taproot_leaf1_script()),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
-    fn generate_taproot_leaf_tx_in(&self, leaf_index: u32, input: &Input) -> TxIn {
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_tx_in(input),
-            1 => self.generate_taproot_leaf1_tx_in(input),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_tx_in(input)),
+            1 => Ok(self.generate_taproot_leaf1_tx_in(input)),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
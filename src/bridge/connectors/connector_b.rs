@@ -1,4 +1,4 @@
-use crate::{bridge::constants::NUM_BLOCKS_PER_WEEK, treepp::*};
+use crate::treepp::*;
 use bitcoin::{
     key::Secp256k1,
     taproot::{TaprootBuilder, TaprootSpendInfo},
@@ -7,7 +7,7 @@ use bitcoin::{
 use serde::{Deserialize, Serialize};
 
 use super::{
-    super::{scripts::*, transactions::base::Input},
+    super::{error::BridgeError, scripts::*, transactions::base::Input},
     connector::*,
 };
 
@@ -19,15 +19,15 @@ pub struct ConnectorB {
 }
 
 impl ConnectorB {
-    pub fn new(network: Network, n_of_n_taproot_public_key: &XOnlyPublicKey) -> Self {
+    pub fn new(
+        network: Network,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
+        num_blocks_timelock: u32,
+    ) -> Self {
         ConnectorB {
             network,
             n_of_n_taproot_public_key: n_of_n_taproot_public_key.clone(),
-            num_blocks_timelock: if network == Network::Bitcoin {
-                NUM_BLOCKS_PER_WEEK * 4
-            } else {
-                1
-            },
+            num_blocks_timelock,
         }
     }
 
@@ -41,7 +41,7 @@ impl ConnectorB {
     // Leaf[1]: spendable by multisig of OPK and VPK[1…N] plus providing witness to the lock script of Assert
     fn generate_taproot_leaf1_script(&self) -> ScriptBuf {
         script! {
-            // TODO commit to intermediate values
+            // TODO commit to the intermediate values of the chunked Groth16 verifier
             { self.n_of_n_taproot_public_key }
             OP_CHECKSIG
         }
@@ -50,7 +50,7 @@ impl ConnectorB {
 
     fn generate_taproot_leaf1_tx_in(&self, input: &Input) -> TxIn { generate_default_tx_in(input) }
 
-    // Leaf[2]: spendable by Burn after a TimeLock of 4 weeks plus multisig of OPK and VPK[1…N]
+    // Leaf[2]: spendable by Burn after the burn timelock plus multisig of OPK and VPK[1…N]
     fn generate_taproot_leaf2_script(&self) -> ScriptBuf {
         script! {
             { self.num_blocks_timelock }
@@ -70,21 +70,25 @@ impl ConnectorB {
 }
 
 impl TaprootConnector for ConnectorB {
-    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> ScriptBuf {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_script(),
-            1 => self.generate_taproot_leaf1_script(),
-            2 => self.generate_taproot_leaf2_script(),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_script()),
+            1 => Ok(self.generate_taproot_leaf1_script()),
+            2 => Ok(self.generate_taproot_leaf2_script()),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
-    fn generate_taproot_leaf_tx_in(&self, leaf_index: u32, input: &Input) -> TxIn {
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_tx_in(input),
-            1 => self.generate_taproot_leaf1_tx_in(input),
-            2 => self.generate_taproot_leaf2_tx_in(input),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_tx_in(input)),
+            1 => Ok(self.generate_taproot_leaf1_tx_in(input)),
+            2 => Ok(self.generate_taproot_leaf2_tx_in(input)),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
@@ -8,7 +8,10 @@ use bitcoin::{
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
-use super::{super::transactions::base::Input, connector::*};
+use super::{
+    super::{error::BridgeError, transactions::base::Input},
+    connector::*,
+};
 
 // Specialized for assert leaves currently.
 pub type LockScript = fn(index: u32) -> ScriptBuf;
@@ -40,30 +43,37 @@ impl ConnectorC {
         }
     }
 
-    pub fn generate_taproot_leaf_script_witness(&self, leaf_index: u32) -> UnlockWitnessData {
+    pub fn generate_taproot_leaf_script_witness(
+        &self,
+        leaf_index: u32,
+    ) -> Result<UnlockWitnessData, BridgeError> {
         let index = leaf_index.to_usize().unwrap();
         if index >= self.unlock_witnesses.len() {
-            panic!("Invalid leaf index.")
+            return Err(BridgeError::InvalidLeafIndex(leaf_index));
         }
-        self.unlock_witnesses[index].clone()
+        Ok(self.unlock_witnesses[index].clone())
     }
 }
 
 impl TaprootConnector for ConnectorC {
-    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> ScriptBuf {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
         let index = leaf_index.to_usize().unwrap();
         if index >= self.lock_scripts.len() {
-            panic!("Invalid leaf index.")
+            return Err(BridgeError::InvalidLeafIndex(leaf_index));
         }
-        self.lock_scripts[index].clone()
+        Ok(self.lock_scripts[index].clone())
     }
 
-    fn generate_taproot_leaf_tx_in(&self, leaf_index: u32, input: &Input) -> TxIn {
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
         let index = leaf_index.to_usize().unwrap();
         if index >= self.lock_scripts.len() {
-            panic!("Invalid leaf index.")
+            return Err(BridgeError::InvalidLeafIndex(leaf_index));
         }
-        generate_default_tx_in(input)
+        Ok(generate_default_tx_in(input))
     }
 
     fn generate_taproot_spend_info(&self) -> TaprootSpendInfo {
@@ -86,6 +96,11 @@ impl TaprootConnector for ConnectorC {
 }
 
 // Leaf[i] for some i in 1,2,…1000: spendable by multisig of OPK and VPK[1…N] plus the condition that f_{i}(z_{i-1})!=z_i
+//
+// The leaves below are placeholders: anyone knowing the preimage "SECRET_i" can spend them. They do
+// not check the operator's Winternitz commitments to the intermediate values z_i, as there is no
+// chunked Groth16 verifier to generate f_i from yet. A disprove therefore does not prove an assert
+// wrong, and the disprove path must not be relied on until these leaves are replaced.
 fn disprove_leaf() -> DisproveLeaf {
     DisproveLeaf {
         lock: |index| {
@@ -103,7 +118,8 @@ fn disprove_leaf() -> DisproveLeaf {
 }
 
 fn generate_assert_leaves() -> (Vec<ScriptBuf>, Vec<UnlockWitnessData>) {
-    // TODO: Scripts with n_of_n_public_key and one of the commitments disprove leaves in each leaf (Winternitz signatures)
+    // TODO: Scripts with n_of_n_public_key and one of the commitments disprove leaves in each leaf
+    // (Winternitz signatures), generated from the chunks of the Groth16 verifier
     let mut locks = Vec::with_capacity(1000);
     let mut unlocks = Vec::with_capacity(1000);
     let locking_template = disprove_leaf().lock;
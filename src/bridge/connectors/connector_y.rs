@@ -0,0 +1,90 @@
+use crate::treepp::*;
+use bitcoin::{
+    key::Secp256k1,
+    taproot::{TaprootBuilder, TaprootSpendInfo},
+    Address, Network, ScriptBuf, TxIn, XOnlyPublicKey,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    super::{
+        commitments::{generate_commit_y_verify_script, WinternitzPublicKey},
+        error::BridgeError,
+        scripts::UNSPENDABLE_TAPROOT_PUBLIC_KEY,
+        transactions::base::Input,
+    },
+    connector::*,
+};
+
+// Commit Y: funds the kick-off, which has to reveal the operator's commitment to the claim y
+// when spending it. The internal key is unspendable, a key path spend would skip the commitment
+// without changing the kick-off txid the rest of the graph is pre-signed for.
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
+pub struct ConnectorY {
+    pub network: Network,
+    pub operator_taproot_public_key: XOnlyPublicKey,
+    pub commit_y_public_key: WinternitzPublicKey,
+}
+
+impl ConnectorY {
+    pub fn new(
+        network: Network,
+        operator_taproot_public_key: &XOnlyPublicKey,
+        commit_y_public_key: &WinternitzPublicKey,
+    ) -> Self {
+        ConnectorY {
+            network,
+            operator_taproot_public_key: operator_taproot_public_key.clone(),
+            commit_y_public_key: commit_y_public_key.clone(),
+        }
+    }
+
+    // Leaf[0]: spendable by operator plus the operator's commitment to y. The script only checks
+    // the commitment is signed with the key of the graph, whether y is the claim of the graph is
+    // checked off-chain by the verifiers, see PegOutGraph::is_kick_off_claim_valid.
+    fn generate_taproot_leaf0_script(&self) -> ScriptBuf {
+        script! {
+            { generate_commit_y_verify_script(&self.commit_y_public_key) }
+            { self.operator_taproot_public_key }
+            OP_CHECKSIG
+        }
+        .compile()
+    }
+
+    fn generate_taproot_leaf0_tx_in(&self, input: &Input) -> TxIn { generate_default_tx_in(input) }
+}
+
+impl TaprootConnector for ConnectorY {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
+        match leaf_index {
+            0 => Ok(self.generate_taproot_leaf0_script()),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
+        }
+    }
+
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
+        match leaf_index {
+            0 => Ok(self.generate_taproot_leaf0_tx_in(input)),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
+        }
+    }
+
+    fn generate_taproot_spend_info(&self) -> TaprootSpendInfo {
+        TaprootBuilder::new()
+            .add_leaf(0, self.generate_taproot_leaf0_script())
+            .expect("Unable to add leaf0")
+            .finalize(&Secp256k1::new(), *UNSPENDABLE_TAPROOT_PUBLIC_KEY)
+            .expect("Unable to finalize taproot")
+    }
+
+    fn generate_taproot_address(&self) -> Address {
+        Address::p2tr_tweaked(
+            self.generate_taproot_spend_info().output_key(),
+            self.network,
+        )
+    }
+}
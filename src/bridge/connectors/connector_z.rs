@@ -1,4 +1,4 @@
-use crate::{bridge::constants::NUM_BLOCKS_PER_WEEK, treepp::*};
+use crate::treepp::*;
 use bitcoin::{
     key::Secp256k1,
     taproot::{TaprootBuilder, TaprootSpendInfo},
@@ -6,7 +6,10 @@ use bitcoin::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::{super::transactions::base::Input, connector::*};
+use super::{
+    super::{error::BridgeError, transactions::base::Input},
+    connector::*,
+};
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct ConnectorZ {
@@ -14,6 +17,7 @@ pub struct ConnectorZ {
     pub depositor_taproot_public_key: XOnlyPublicKey,
     pub n_of_n_taproot_public_key: XOnlyPublicKey,
     pub evm_address: String,
+    pub num_blocks_timelock: u32,
 }
 
 impl ConnectorZ {
@@ -22,19 +26,21 @@ impl ConnectorZ {
         evm_address: &str,
         depositor_taproot_public_key: &XOnlyPublicKey,
         n_of_n_taproot_public_key: &XOnlyPublicKey,
+        num_blocks_timelock: u32,
     ) -> Self {
         ConnectorZ {
             network,
             depositor_taproot_public_key: depositor_taproot_public_key.clone(),
             n_of_n_taproot_public_key: n_of_n_taproot_public_key.clone(),
             evm_address: evm_address.to_string(),
+            num_blocks_timelock,
         }
     }
 
     // leaf[0] is TimeLock script that the depositor can spend after timelock, if leaf[1] has not been spent
     fn generate_taproot_leaf0_script(&self) -> ScriptBuf {
         script! {
-        { NUM_BLOCKS_PER_WEEK * 2 }
+        { self.num_blocks_timelock }
         OP_CSV
         OP_DROP
         { self.depositor_taproot_public_key }
@@ -45,7 +51,7 @@ impl ConnectorZ {
 
     fn generate_taproot_leaf0_tx_in(&self, input: &Input) -> TxIn {
         let mut tx_in = generate_default_tx_in(input);
-        tx_in.sequence = Sequence(NUM_BLOCKS_PER_WEEK * 2);
+        tx_in.sequence = Sequence(self.num_blocks_timelock);
         tx_in
     }
 
@@ -73,19 +79,23 @@ impl ConnectorZ {
 }
 
 impl TaprootConnector for ConnectorZ {
-    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> ScriptBuf {
+    fn generate_taproot_leaf_script(&self, leaf_index: u32) -> Result<ScriptBuf, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_script(),
-            1 => self.generate_taproot_leaf1_script(),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_script()),
+            1 => Ok(self.generate_taproot_leaf1_script()),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
-    fn generate_taproot_leaf_tx_in(&self, leaf_index: u32, input: &Input) -> TxIn {
+    fn generate_taproot_leaf_tx_in(
+        &self,
+        leaf_index: u32,
+        input: &Input,
+    ) -> Result<TxIn, BridgeError> {
         match leaf_index {
-            0 => self.generate_taproot_leaf0_tx_in(input),
-            1 => self.generate_taproot_leaf1_tx_in(input),
-            _ => panic!("Invalid leaf index."),
+            0 => Ok(self.generate_taproot_leaf0_tx_in(input)),
+            1 => Ok(self.generate_taproot_leaf1_tx_in(input)),
+            _ => Err(BridgeError::InvalidLeafIndex(leaf_index)),
         }
     }
 
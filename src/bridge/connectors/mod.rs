@@ -6,4 +6,5 @@ pub mod connector_3;
 pub mod connector_a;
 pub mod connector_b;
 pub mod connector_c;
+pub mod connector_y;
 pub mod connector_z;
@@ -1,5 +1,3 @@
-//TODO: replace with real value, and delete this comment
-// pub const NUM_BLOCKS_PER_WEEK: u32 = 1008;
-pub const NUM_BLOCKS_PER_WEEK: u32 = 1;
+pub const NUM_BLOCKS_PER_WEEK: u32 = 1008;
 pub const NUM_BLOCKS_PER_2_WEEKS: u32 = NUM_BLOCKS_PER_WEEK * 2;
 pub const NUM_BLOCKS_PER_4_WEEKS: u32 = NUM_BLOCKS_PER_WEEK * 4;
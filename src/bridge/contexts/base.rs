@@ -3,6 +3,7 @@ use bitcoin::{
     secp256k1::All,
     Network, PrivateKey, PublicKey, XOnlyPublicKey,
 };
+use musig2::{secp::Point, KeyAggContext};
 
 pub trait BaseContext {
     fn network(&self) -> Network;
@@ -12,12 +13,46 @@ pub trait BaseContext {
 pub fn generate_keys_from_secret(
     network: Network,
     secret: &str,
+) -> (Secp256k1<All>, Keypair, PublicKey, XOnlyPublicKey) {
+    let keypair = Keypair::from_seckey_str(&Secp256k1::new(), secret).unwrap();
+
+    generate_keys_from_keypair(network, &keypair)
+}
+
+// For keys that never existed as a hex secret, see keys::key_chain::KeyChain.
+pub fn generate_keys_from_keypair(
+    network: Network,
+    keypair: &Keypair,
 ) -> (Secp256k1<All>, Keypair, PublicKey, XOnlyPublicKey) {
     let secp = Secp256k1::new();
-    let keypair = Keypair::from_seckey_str(&secp, secret).unwrap();
     let private_key = PrivateKey::new(keypair.secret_key(), network);
     let public_key = PublicKey::from_private_key(&secp, &private_key);
     let taproot_public_key = keypair.x_only_public_key().0;
 
-    (secp, keypair, public_key, taproot_public_key)
+    (secp, *keypair, public_key, taproot_public_key)
+}
+
+// The verifier keys are sorted before aggregation, so every participant derives the same n-of-n
+// key regardless of the order in which the keys were configured.
+pub fn generate_n_of_n_key_agg_context(n_of_n_public_keys: &Vec<PublicKey>) -> KeyAggContext {
+    let mut public_keys = n_of_n_public_keys.clone();
+    public_keys.sort_by_key(|public_key| public_key.to_bytes());
+
+    KeyAggContext::new(
+        public_keys
+            .iter()
+            .map(|public_key| Point::from_slice(&public_key.to_bytes()).unwrap()),
+    )
+    .expect("Unable to aggregate the n-of-n public keys")
+}
+
+pub fn generate_n_of_n_public_key(
+    n_of_n_public_keys: &Vec<PublicKey>,
+) -> (PublicKey, XOnlyPublicKey) {
+    let aggregated_public_key: Point =
+        generate_n_of_n_key_agg_context(n_of_n_public_keys).aggregated_pubkey();
+    let public_key = PublicKey::from_slice(&aggregated_public_key.serialize()).unwrap();
+    let taproot_public_key = public_key.inner.x_only_public_key().0;
+
+    (public_key, taproot_public_key)
 }
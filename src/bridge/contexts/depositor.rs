@@ -4,7 +4,14 @@ use bitcoin::{
     Network, PublicKey, XOnlyPublicKey,
 };
 
-use super::base::{generate_keys_from_secret, BaseContext};
+use super::{
+    super::{
+        error::BridgeError,
+        keys::key_chain::{KeyChain, KeyRole},
+        parameters::BridgeParameters,
+    },
+    base::{generate_keys_from_keypair, generate_keys_from_secret, BaseContext},
+};
 
 pub struct DepositorContext {
     pub network: Network,
@@ -16,6 +23,9 @@ pub struct DepositorContext {
 
     pub n_of_n_public_key: PublicKey,
     pub n_of_n_taproot_public_key: XOnlyPublicKey,
+
+    // Parameters of the graphs created with this context
+    pub parameters: BridgeParameters,
 }
 
 impl BaseContext for DepositorContext {
@@ -29,9 +39,57 @@ impl DepositorContext {
         depositor_secret: &str,
         n_of_n_public_key: &PublicKey,
         n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Self {
+        let (_, keypair, _, _) = generate_keys_from_secret(network, depositor_secret);
+
+        Self::from_keypair(
+            network,
+            &keypair,
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        )
+    }
+
+    // The depositor key of the graph with the index, see KeyChain.
+    pub fn from_seed(
+        network: Network,
+        seed: &[u8],
+        index: u32,
+        n_of_n_public_key: &PublicKey,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Result<Self, BridgeError> {
+        Self::from_key_chain(
+            &KeyChain::from_seed(network, seed)?,
+            index,
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        )
+    }
+
+    pub fn from_key_chain(
+        key_chain: &KeyChain,
+        index: u32,
+        n_of_n_public_key: &PublicKey,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Result<Self, BridgeError> {
+        let keypair = key_chain.derive_keypair(KeyRole::Depositor, index)?;
+
+        Ok(Self::from_keypair(
+            key_chain.network(),
+            &keypair,
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        ))
+    }
+
+    pub fn from_keypair(
+        network: Network,
+        keypair: &Keypair,
+        n_of_n_public_key: &PublicKey,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
     ) -> Self {
         let (secp, keypair, public_key, taproot_public_key) =
-            generate_keys_from_secret(network, depositor_secret);
+            generate_keys_from_keypair(network, keypair);
 
         DepositorContext {
             network,
@@ -43,6 +101,8 @@ impl DepositorContext {
 
             n_of_n_public_key: n_of_n_public_key.clone(),
             n_of_n_taproot_public_key: n_of_n_taproot_public_key.clone(),
+
+            parameters: BridgeParameters::new(network),
         }
     }
 }
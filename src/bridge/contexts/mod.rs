@@ -1,5 +1,6 @@
 pub mod base;
 pub mod depositor;
 pub mod operator;
+pub mod public;
 pub mod verifier;
 pub mod withdrawer;
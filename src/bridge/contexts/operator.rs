@@ -4,7 +4,14 @@ use bitcoin::{
     Network, PublicKey, XOnlyPublicKey,
 };
 
-use super::base::{generate_keys_from_secret, BaseContext};
+use super::{
+    super::{
+        error::BridgeError,
+        keys::key_chain::{KeyChain, KeyRole},
+        parameters::BridgeParameters,
+    },
+    base::{generate_keys_from_keypair, generate_keys_from_secret, BaseContext},
+};
 
 pub struct OperatorContext {
     pub network: Network,
@@ -16,6 +23,9 @@ pub struct OperatorContext {
 
     pub n_of_n_public_key: PublicKey,
     pub n_of_n_taproot_public_key: XOnlyPublicKey,
+
+    // Parameters of the graphs created with this context
+    pub parameters: BridgeParameters,
 }
 
 impl BaseContext for OperatorContext {
@@ -29,9 +39,42 @@ impl OperatorContext {
         operator_secret: &str,
         n_of_n_public_key: &PublicKey,
         n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Self {
+        let (_, keypair, _, _) = generate_keys_from_secret(network, operator_secret);
+
+        Self::from_keypair(
+            network,
+            &keypair,
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        )
+    }
+
+    // Operators are known by a single key, the one at index 0 of the seed.
+    pub fn from_seed(
+        network: Network,
+        seed: &[u8],
+        n_of_n_public_key: &PublicKey,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Result<Self, BridgeError> {
+        let keypair = KeyChain::from_seed(network, seed)?.derive_keypair(KeyRole::Operator, 0)?;
+
+        Ok(Self::from_keypair(
+            network,
+            &keypair,
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        ))
+    }
+
+    pub fn from_keypair(
+        network: Network,
+        keypair: &Keypair,
+        n_of_n_public_key: &PublicKey,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
     ) -> Self {
         let (secp, keypair, public_key, taproot_public_key) =
-            generate_keys_from_secret(network, operator_secret);
+            generate_keys_from_keypair(network, keypair);
 
         OperatorContext {
             network,
@@ -43,6 +86,8 @@ impl OperatorContext {
 
             n_of_n_public_key: n_of_n_public_key.clone(),
             n_of_n_taproot_public_key: n_of_n_taproot_public_key.clone(),
+
+            parameters: BridgeParameters::new(network),
         }
     }
 }
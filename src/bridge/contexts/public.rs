@@ -0,0 +1,49 @@
+use bitcoin::{
+    key::{Secp256k1, XOnlyPublicKey},
+    secp256k1::All,
+    Network, PublicKey,
+};
+
+use super::base::{generate_n_of_n_public_key, BaseContext};
+
+// The public keys of the counterparties of a participant, the operator and the verifiers
+// forming the n-of-n. Holds no secret.
+pub struct PublicContext {
+    pub network: Network,
+    pub secp: Secp256k1<All>,
+
+    pub operator_public_key: PublicKey,
+    pub operator_taproot_public_key: XOnlyPublicKey,
+
+    pub n_of_n_public_keys: Vec<PublicKey>,
+    pub n_of_n_public_key: PublicKey,
+    pub n_of_n_taproot_public_key: XOnlyPublicKey,
+}
+
+impl BaseContext for PublicContext {
+    fn network(&self) -> Network { self.network }
+    fn secp(&self) -> &Secp256k1<All> { &self.secp }
+}
+
+impl PublicContext {
+    pub fn new(
+        network: Network,
+        operator_public_key: &PublicKey,
+        n_of_n_public_keys: &Vec<PublicKey>,
+    ) -> Self {
+        let (n_of_n_public_key, n_of_n_taproot_public_key) =
+            generate_n_of_n_public_key(n_of_n_public_keys);
+
+        PublicContext {
+            network,
+            secp: Secp256k1::new(),
+
+            operator_public_key: *operator_public_key,
+            operator_taproot_public_key: operator_public_key.inner.x_only_public_key().0,
+
+            n_of_n_public_keys: n_of_n_public_keys.clone(),
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        }
+    }
+}
@@ -4,18 +4,28 @@ use bitcoin::{
     Network, PublicKey, XOnlyPublicKey,
 };
 
-use super::base::{generate_keys_from_secret, BaseContext};
+use super::{
+    super::{
+        error::BridgeError,
+        keys::key_chain::{KeyChain, KeyRole},
+    },
+    base::{
+        generate_keys_from_keypair, generate_keys_from_secret, generate_n_of_n_public_key,
+        BaseContext,
+    },
+};
 
 pub struct VerifierContext {
     pub network: Network,
     pub secp: Secp256k1<All>,
 
-    pub n_of_n_keypair: Keypair,
+    pub verifier_keypair: Keypair,
+    pub verifier_public_key: PublicKey,
+    pub verifier_taproot_public_key: XOnlyPublicKey,
+
+    pub n_of_n_public_keys: Vec<PublicKey>,
     pub n_of_n_public_key: PublicKey,
     pub n_of_n_taproot_public_key: XOnlyPublicKey,
-
-    pub operator_public_key: PublicKey,
-    pub operator_taproot_public_key: XOnlyPublicKey,
 }
 
 impl BaseContext for VerifierContext {
@@ -26,23 +36,52 @@ impl BaseContext for VerifierContext {
 impl VerifierContext {
     pub fn new(
         network: Network,
-        n_of_n_secret: &str,
-        operator_public_key: &PublicKey,
-        operator_taproot_public_key: &XOnlyPublicKey,
-    ) -> Self {
+        verifier_secret: &str,
+        n_of_n_public_keys: &Vec<PublicKey>,
+    ) -> Result<Self, BridgeError> {
+        let (_, keypair, _, _) = generate_keys_from_secret(network, verifier_secret);
+
+        Self::from_keypair(network, &keypair, n_of_n_public_keys)
+    }
+
+    // Verifiers are known by a single key, the one at index 0 of the seed.
+    pub fn from_seed(
+        network: Network,
+        seed: &[u8],
+        n_of_n_public_keys: &Vec<PublicKey>,
+    ) -> Result<Self, BridgeError> {
+        let keypair = KeyChain::from_seed(network, seed)?.derive_keypair(KeyRole::Verifier, 0)?;
+
+        Self::from_keypair(network, &keypair, n_of_n_public_keys)
+    }
+
+    pub fn from_keypair(
+        network: Network,
+        keypair: &Keypair,
+        n_of_n_public_keys: &Vec<PublicKey>,
+    ) -> Result<Self, BridgeError> {
         let (secp, keypair, public_key, taproot_public_key) =
-            generate_keys_from_secret(network, n_of_n_secret);
+            generate_keys_from_keypair(network, keypair);
+        if !n_of_n_public_keys.contains(&public_key) {
+            return Err(BridgeError::Registry(String::from(
+                "Verifier public key must be one of the n-of-n public keys",
+            )));
+        }
+
+        let (n_of_n_public_key, n_of_n_taproot_public_key) =
+            generate_n_of_n_public_key(n_of_n_public_keys);
 
-        VerifierContext {
+        Ok(VerifierContext {
             network,
             secp,
 
-            n_of_n_keypair: keypair,
-            n_of_n_public_key: public_key,
-            n_of_n_taproot_public_key: taproot_public_key,
+            verifier_keypair: keypair,
+            verifier_public_key: public_key,
+            verifier_taproot_public_key: taproot_public_key,
 
-            operator_public_key: operator_public_key.clone(),
-            operator_taproot_public_key: operator_taproot_public_key.clone(),
-        }
+            n_of_n_public_keys: n_of_n_public_keys.clone(),
+            n_of_n_public_key,
+            n_of_n_taproot_public_key,
+        })
     }
 }
@@ -0,0 +1,79 @@
+use bitcoin::Txid;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use super::client::chain::base::ChainError;
+
+#[derive(Debug)]
+pub enum BridgeError {
+    Chain(ChainError),
+    TransactionAlreadyMined(Txid),
+    TransactionNotConfirmed(String), // name of the transaction that has to be confirmed first
+    TimelockNotElapsed(String),      // name of the transaction whose output is still timelocked
+    InvalidLeafIndex(u32),
+    MissingContext(String), // name of the context the operation requires
+    GraphNotFound(String),
+    GraphAlreadyExists(String),
+    PreSigning(String),
+    InvalidCommitment(String),
+    Crowdfunding(String),
+    FeeBumping(String),
+    KeyDerivation(String),
+    Keystore(String),
+    Registry(String),
+    PegOut(String),
+    GraphMerge(String),
+    InvalidGraph(String),
+    Psbt(String),
+    DataStore(String),
+}
+
+impl Display for BridgeError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            BridgeError::Chain(err) => write!(f, "Chain error: {}", err),
+            BridgeError::TransactionAlreadyMined(txid) => {
+                write!(f, "Transaction {} already mined", txid)
+            }
+            BridgeError::TransactionNotConfirmed(name) => {
+                write!(f, "{} tx has not been confirmed yet", name)
+            }
+            BridgeError::TimelockNotElapsed(name) => {
+                write!(f, "{} tx timelock has not elapsed yet", name)
+            }
+            BridgeError::InvalidLeafIndex(leaf_index) => {
+                write!(f, "Invalid leaf index: {}", leaf_index)
+            }
+            BridgeError::MissingContext(name) => write!(f, "{} context must be initialized", name),
+            BridgeError::GraphNotFound(id) => write!(f, "Graph {} not found", id),
+            BridgeError::GraphAlreadyExists(id) => write!(f, "Graph {} already exists", id),
+            BridgeError::PreSigning(err) => write!(f, "Pre-signing failed: {}", err),
+            BridgeError::InvalidCommitment(err) => write!(f, "Invalid commitment: {}", err),
+            BridgeError::Crowdfunding(err) => write!(f, "Challenge crowdfunding failed: {}", err),
+            BridgeError::FeeBumping(err) => write!(f, "Fee bumping failed: {}", err),
+            BridgeError::KeyDerivation(err) => write!(f, "Key derivation failed: {}", err),
+            BridgeError::Keystore(err) => write!(f, "Keystore error: {}", err),
+            BridgeError::Registry(err) => write!(f, "Invalid participant registry: {}", err),
+            BridgeError::PegOut(err) => write!(f, "Peg-out failed: {}", err),
+            BridgeError::GraphMerge(err) => write!(f, "Unable to merge diverged graphs: {}", err),
+            BridgeError::InvalidGraph(err) => write!(f, "Invalid graph: {}", err),
+            BridgeError::Psbt(err) => write!(f, "Invalid PSBT: {}", err),
+            BridgeError::DataStore(err) => write!(f, "Data store error: {}", err),
+        }
+    }
+}
+
+impl Error for BridgeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BridgeError::Chain(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ChainError> for BridgeError {
+    fn from(err: ChainError) -> Self { BridgeError::Chain(err) }
+}
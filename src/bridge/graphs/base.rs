@@ -1,58 +1,285 @@
-use bitcoin::{Network, Txid};
-use esplora_client::{AsyncClient, Error};
+use bitcoin::{Network, OutPoint, PublicKey, ScriptBuf, Transaction, Txid};
+use esplora_client::TxStatus;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
-pub const GRAPH_VERSION: &str = "0.1";
+use super::super::{
+    client::chain::base::{ChainBackend, ChainError},
+    contexts::verifier::VerifierContext,
+    error::BridgeError,
+    transactions::{
+        pre_signed::{verify_pre_signed_input_witnesses, PreSignedTransaction},
+        pre_signed_musig2::{
+            get_missing_nonces, get_missing_signatures, is_musig2_input_finalized,
+            PreSignedMusig2Transaction,
+        },
+    },
+};
+
+// Bumped whenever the serialized graphs change incompatibly. Graphs of another version are
+// discarded instead of being read with missing or misinterpreted fields.
+pub const GRAPH_VERSION: &str = "0.2";
 
 pub const INITIAL_AMOUNT: u64 = 100_000;
 pub const FEE_AMOUNT: u64 = 1_000;
+// Minimum relay fee rate (in sat/vB), presigned transactions are bumped once they are stuck
+pub const FEE_RATE: u64 = 1;
 pub const DUST_AMOUNT: u64 = 10_000;
+// Operator-controlled output of presigned transactions the operator has no other output in,
+// spent by a child transaction to bump the fee (CPFP). Dust limit of a p2wsh output.
+pub const ANCHOR_AMOUNT: u64 = 330;
 pub const ONE_HUNDRED: u64 = 100_000_000;
-
-// TODO delete
-// DEMO SECRETS
-pub const OPERATOR_SECRET: &str =
-    "d898098e09898a0980989b980809809809f09809884324874302975287524398";
-pub const N_OF_N_SECRET: &str = "a9bd8b8ade888ed12301b21318a3a73429232343587049870132987481723497";
-pub const DEPOSITOR_SECRET: &str =
-    "b8f17ea979be24199e7c3fec71ee88914d92fd4ca508443f765d56ce024ef1d7";
-pub const WITHDRAWER_SECRET: &str =
-    "fffd54f6d8f8ad470cb507fd4b6e9b3ea26b4221a4900cc5ad5916ce67c02f1e";
-
-pub const EVM_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+// Crowdfunded by the challengers, covers the operator's cost of publishing the assert
+pub const CHALLENGE_CROWDFUNDING_AMOUNT: u64 = INITIAL_AMOUNT;
 
 pub trait BaseGraph {
     fn network(&self) -> Network;
     fn id(&self) -> &String;
 }
 
-pub async fn get_block_height(client: &AsyncClient) -> u32 {
-    let blockchain_height_result = client.get_height().await;
-    if blockchain_height_result.is_err() {
-        panic!(
-            "Failed to fetch blockchain height! Error occurred {:?}",
-            blockchain_height_result
-        );
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PreSigningState {
+    AwaitingNonces,     // not every verifier has pushed its nonces yet
+    AwaitingSignatures, // not every n-of-n input carries a valid aggregated signature yet
+    Presigned,          // every n-of-n input is signed, final
+}
+
+impl Display for PreSigningState {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PreSigningState::AwaitingNonces => write!(f, "awaiting verifier nonces"),
+            PreSigningState::AwaitingSignatures => {
+                write!(f, "awaiting verifier partial signatures")
+            }
+            PreSigningState::Presigned => write!(f, "presigned"),
+        }
     }
+}
 
-    blockchain_height_result.unwrap()
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct PreSigningInputStatus {
+    pub txid: Txid,
+    pub input_index: usize,
+    pub missing_nonces: Vec<PublicKey>,
+    pub missing_signatures: Vec<PublicKey>,
+    pub finalized: bool,
 }
 
-pub async fn verify_if_not_mined(client: &AsyncClient, txid: Txid) {
-    let tx_status = client.get_tx_status(&txid).await;
-    if tx_status.as_ref().is_ok_and(|status| status.confirmed) {
-        panic!("Transaction already mined!");
-    } else if tx_status.is_err() {
-        panic!(
-            "Failed to get transaction status, error occurred {:?}",
-            tx_status
-        );
+// The verifiers taking part in the pre-signing are only known to the graph once the first of
+// them pushed its nonces, every later verifier must agree on the same n-of-n public keys.
+pub fn register_n_of_n_public_keys(
+    n_of_n_public_keys: &mut Vec<PublicKey>,
+    context: &VerifierContext,
+) -> Result<(), BridgeError> {
+    if n_of_n_public_keys.is_empty() {
+        n_of_n_public_keys.clone_from(&context.n_of_n_public_keys);
+    } else if *n_of_n_public_keys != context.n_of_n_public_keys {
+        return Err(BridgeError::PreSigning(String::from(
+            "Verifier disagrees with the n-of-n public keys of the graph",
+        )));
     }
+
+    Ok(())
 }
 
-pub fn verify_tx_result(tx_result: &Result<(), Error>) {
-    if tx_result.is_ok() {
-        println!("Tx mined successfully.");
+pub fn merge_n_of_n_public_keys(
+    destination: &mut Vec<PublicKey>,
+    source: &Vec<PublicKey>,
+) -> Result<(), BridgeError> {
+    if destination.is_empty() {
+        destination.clone_from(source);
+    } else if !source.is_empty() && destination != source {
+        return Err(BridgeError::GraphMerge(String::from(
+            "Conflicting n-of-n public keys",
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn get_pre_signing_input_statuses(
+    transactions: &[&dyn PreSignedMusig2Transaction],
+    n_of_n_public_keys: &[PublicKey],
+) -> Vec<PreSigningInputStatus> {
+    let mut statuses = Vec::new();
+    for tx in transactions {
+        let txid = tx.tx().compute_txid();
+        for input_index in tx.musig2_input_indices() {
+            statuses.push(PreSigningInputStatus {
+                txid,
+                input_index,
+                missing_nonces: get_missing_nonces(*tx, input_index, n_of_n_public_keys),
+                missing_signatures: get_missing_signatures(*tx, input_index, n_of_n_public_keys),
+                finalized: is_musig2_input_finalized(*tx, input_index),
+            });
+        }
+    }
+
+    statuses
+}
+
+// Moves the pre-signing forward according to the nonces and signatures present in the
+// transactions. Presigned is only reached once every aggregated signature verifies.
+pub fn get_pre_signing_state(
+    state: PreSigningState,
+    transactions: &[&dyn PreSignedMusig2Transaction],
+    n_of_n_public_keys: &[PublicKey],
+) -> PreSigningState {
+    if state == PreSigningState::Presigned {
+        return state;
+    }
+
+    let statuses = get_pre_signing_input_statuses(transactions, n_of_n_public_keys);
+    if n_of_n_public_keys.is_empty()
+        || statuses
+            .iter()
+            .any(|status| !status.missing_nonces.is_empty())
+    {
+        PreSigningState::AwaitingNonces
+    } else if statuses.iter().all(|status| status.finalized)
+        && transactions
+            .iter()
+            .all(|tx| verify_pre_signed_input_witnesses(*tx).is_ok())
+    {
+        PreSigningState::Presigned
     } else {
-        panic!("Error occurred {:?}", tx_result);
+        PreSigningState::AwaitingSignatures
+    }
+}
+
+pub async fn get_block_height(client: &dyn ChainBackend) -> Result<u32, BridgeError> {
+    Ok(client.get_height().await?)
+}
+
+pub async fn verify_if_not_mined(client: &dyn ChainBackend, txid: Txid) -> Result<(), BridgeError> {
+    let tx_status = client.get_tx_status(&txid).await?;
+    if tx_status.confirmed {
+        return Err(BridgeError::TransactionAlreadyMined(txid));
+    }
+
+    Ok(())
+}
+
+// Txid of the transaction spending the output, unless it is one of the given transactions.
+// Transactions completed by whoever broadcasts them, like the challenge, are only known by the
+// output they spend.
+pub async fn get_other_spending_txid(
+    client: &dyn ChainBackend,
+    outpoint: OutPoint,
+    txids: &[Txid],
+) -> Result<Option<Txid>, ChainError> {
+    Ok(client
+        .get_spending_txid(&outpoint)
+        .await?
+        .filter(|txid| !txids.contains(txid)))
+}
+
+// Status of the transaction spending the output, unless it is one of the given transactions.
+// Unconfirmed while no other transaction spends the output.
+pub async fn get_other_spending_tx_status(
+    client: &dyn ChainBackend,
+    outpoint: OutPoint,
+    txids: &[Txid],
+) -> Result<TxStatus, ChainError> {
+    match get_other_spending_txid(client, outpoint, txids).await? {
+        Some(txid) => client.get_tx_status(&txid).await,
+        None => Ok(TxStatus {
+            confirmed: false,
+            block_height: None,
+            block_hash: None,
+            block_time: None,
+        }),
+    }
+}
+
+pub fn verify_tx_result(tx_result: Result<(), ChainError>) -> Result<(), BridgeError> {
+    tx_result?;
+    println!("Tx mined successfully.");
+
+    Ok(())
+}
+
+pub fn verify_version(graph_name: &str, id: &str, version: &str) -> Result<(), BridgeError> {
+    if version != GRAPH_VERSION {
+        return Err(BridgeError::InvalidGraph(format!(
+            "{} graph {} has version {}, expected {}",
+            graph_name, id, version, GRAPH_VERSION
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn verify_output_script_pubkey(
+    transaction_name: &str,
+    tx: &Transaction,
+    vout: usize,
+    expected_script_pubkey: &ScriptBuf,
+) -> Result<(), BridgeError> {
+    let output = tx.output.get(vout);
+    if output.is_none() || output.unwrap().script_pubkey != *expected_script_pubkey {
+        return Err(BridgeError::InvalidGraph(format!(
+            "{} tx output {} does not match the expected connector",
+            transaction_name, vout
+        )));
     }
+
+    Ok(())
+}
+
+pub fn verify_prev_out_script_pubkey<T: PreSignedTransaction>(
+    transaction_name: &str,
+    transaction: &T,
+    input_index: usize,
+    expected_script_pubkey: &ScriptBuf,
+) -> Result<(), BridgeError> {
+    let prev_out = transaction.prev_outs().get(input_index);
+    if prev_out.is_none() || prev_out.unwrap().script_pubkey != *expected_script_pubkey {
+        return Err(BridgeError::InvalidGraph(format!(
+            "{} tx input {} does not spend the expected output",
+            transaction_name, input_index
+        )));
+    }
+
+    Ok(())
+}
+
+// Checks that the input spends the given output of the source transaction, using the txid
+// recomputed from the source transaction rather than any stored value.
+pub fn verify_input<T: PreSignedTransaction>(
+    transaction_name: &str,
+    transaction: &T,
+    input_index: usize,
+    source_tx: &Transaction,
+    vout: u32,
+) -> Result<(), BridgeError> {
+    let input = transaction.tx().input.get(input_index);
+    let prev_out = transaction.prev_outs().get(input_index);
+    let source_output = source_tx.output.get(vout as usize);
+    let outpoint = OutPoint {
+        txid: source_tx.compute_txid(),
+        vout,
+    };
+
+    if input.is_none()
+        || prev_out.is_none()
+        || source_output.is_none()
+        || input.unwrap().previous_output != outpoint
+        || prev_out.unwrap() != source_output.unwrap()
+    {
+        return Err(BridgeError::InvalidGraph(format!(
+            "{} tx input {} does not spend output {}",
+            transaction_name, input_index, outpoint
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn verify_witnesses<T: PreSignedTransaction>(
+    transaction_name: &str,
+    transaction: &T,
+) -> Result<(), BridgeError> {
+    verify_pre_signed_input_witnesses(transaction)
+        .map_err(|err| BridgeError::InvalidGraph(format!("{} tx: {}", transaction_name, err)))
 }
@@ -1,3 +1,4 @@
 pub mod base;
 pub mod peg_in;
 pub mod peg_out;
+pub mod status;
@@ -1,27 +1,51 @@
 use bitcoin::{
     hex::{Case::Upper, DisplayHex},
-    Network, OutPoint, PublicKey, XOnlyPublicKey,
+    Network, OutPoint, PublicKey, Txid, XOnlyPublicKey,
 };
-use esplora_client::{AsyncClient, Error, TxStatus};
+use esplora_client::TxStatus;
+use musig2::SecNonce;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 
-use crate::bridge::{constants::NUM_BLOCKS_PER_2_WEEKS, graphs::base::get_block_height};
+use crate::bridge::graphs::base::get_block_height;
 
 use super::{
     super::{
+        client::chain::base::{ChainBackend, ChainError},
+        connectors::{
+            connector::TaprootConnector, connector_0::Connector0, connector_z::ConnectorZ,
+        },
         contexts::{depositor::DepositorContext, verifier::VerifierContext},
+        error::BridgeError,
+        parameters::BridgeParameters,
+        scripts::generate_pay_to_pubkey_script_address,
         transactions::{
-            base::Input, peg_in_confirm::PegInConfirmTransaction,
-            peg_in_deposit::PegInDepositTransaction, peg_in_refund::PegInRefundTransaction,
+            base::{BaseTransaction, Input},
+            peg_in_confirm::PegInConfirmTransaction,
+            peg_in_deposit::PegInDepositTransaction,
+            peg_in_refund::PegInRefundTransaction,
             pre_signed::PreSignedTransaction,
+            pre_signed_musig2::{
+                has_all_nonces, has_all_signatures, has_nonces_of, has_signatures_of,
+                PreSignedMusig2Transaction,
+            },
         },
     },
-    base::{BaseGraph, GRAPH_VERSION},
+    base::{
+        get_pre_signing_input_statuses, get_pre_signing_state, merge_n_of_n_public_keys,
+        register_n_of_n_public_keys, verify_input, verify_output_script_pubkey,
+        verify_prev_out_script_pubkey, verify_tx_result, verify_version, verify_witnesses,
+        BaseGraph, PreSigningInputStatus, PreSigningState, GRAPH_VERSION,
+    },
+    status::{get_transaction_status_report, GraphRole, GraphStatus, GraphStatusReport},
 };
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegInDepositorStatus {
     PegInDepositWait,     // peg-in deposit not yet confirmed
     PegInConfirmWait, // peg-in confirm not yet confirmed, wait for operator to complete peg-in, refund not available yet
@@ -42,6 +66,7 @@ impl Display for PegInDepositorStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegInVerifierStatus {
     PegInWait,     // no action required, wait
     PegInPresign,  // should presign peg-in confirm
@@ -58,6 +83,7 @@ impl Display for PegInVerifierStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegInOperatorStatus {
     PegInWait,             // peg-in not yet complete, no action required yet, wait
     PegInConfirmAvailable, // should execute peg-in confirm
@@ -83,12 +109,14 @@ pub struct PegInGraph {
     version: String,
     network: Network,
     id: String,
+    parameters: BridgeParameters,
 
     peg_in_deposit_transaction: PegInDepositTransaction,
     peg_in_refund_transaction: PegInRefundTransaction,
     peg_in_confirm_transaction: PegInConfirmTransaction,
 
-    n_of_n_presigned: bool,
+    n_of_n_public_keys: Vec<PublicKey>,
+    n_of_n_pre_signing_state: PreSigningState,
 
     pub depositor_public_key: PublicKey,
     depositor_taproot_public_key: XOnlyPublicKey,
@@ -137,27 +165,222 @@ impl PegInGraph {
             version: GRAPH_VERSION.to_string(),
             network: context.network,
             id: generate_id(&peg_in_deposit_transaction),
+            parameters: context.parameters,
             peg_in_deposit_transaction,
             peg_in_refund_transaction,
             peg_in_confirm_transaction,
-            n_of_n_presigned: false,
+            n_of_n_public_keys: Vec::new(),
+            n_of_n_pre_signing_state: PreSigningState::AwaitingNonces,
             depositor_public_key: context.depositor_public_key,
             depositor_taproot_public_key: context.depositor_taproot_public_key,
             depositor_evm_address: evm_address.to_string(),
         }
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) {
-        self.peg_in_confirm_transaction.pre_sign(context);
+    pub fn parameters(&self) -> &BridgeParameters { &self.parameters }
+
+    fn musig2_transactions(&self) -> Vec<&dyn PreSignedMusig2Transaction> {
+        vec![&self.peg_in_confirm_transaction]
+    }
+
+    pub fn has_nonces_of(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_nonces_of(*tx, context))
+    }
+
+    pub fn has_all_nonces(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_all_nonces(*tx, context))
+    }
+
+    pub fn has_signatures_of(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_signatures_of(*tx, context))
+    }
+
+    pub fn has_all_signatures(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_all_signatures(*tx, context))
+    }
+
+    pub fn pre_signing_state(&self) -> PreSigningState { self.n_of_n_pre_signing_state }
+
+    // Lists every n-of-n input together with the verifiers it still needs nonces or partial
+    // signatures from.
+    pub fn pre_signing_status(&self) -> Vec<PreSigningInputStatus> {
+        get_pre_signing_input_statuses(&self.musig2_transactions(), &self.n_of_n_public_keys)
+    }
+
+    fn update_pre_signing_state(&mut self) {
+        self.n_of_n_pre_signing_state = get_pre_signing_state(
+            self.n_of_n_pre_signing_state,
+            &self.musig2_transactions(),
+            &self.n_of_n_public_keys,
+        );
+    }
+
+    // Derives the pre-signing state from the nonces and witnesses alone, the state stored in a
+    // graph fetched from another participant is not trusted.
+    pub fn recompute_pre_signing_state(&mut self) {
+        self.n_of_n_pre_signing_state = get_pre_signing_state(
+            PreSigningState::AwaitingNonces,
+            &self.musig2_transactions(),
+            &self.n_of_n_public_keys,
+        );
+    }
+
+    // Returns the secret nonces, which must be kept by the verifier until it pre-signs the graph.
+    pub fn push_nonces(
+        &mut self,
+        context: &VerifierContext,
+    ) -> Result<HashMap<Txid, HashMap<usize, SecNonce>>, BridgeError> {
+        register_n_of_n_public_keys(&mut self.n_of_n_public_keys, context)?;
+
+        let mut secret_nonces = HashMap::new();
+        secret_nonces.insert(
+            self.peg_in_confirm_transaction.tx().compute_txid(),
+            self.peg_in_confirm_transaction.push_nonces(context),
+        );
+
+        self.update_pre_signing_state();
+
+        Ok(secret_nonces)
+    }
+
+    // Requires the nonces of all verifiers. The n-of-n signatures are aggregated by whichever
+    // verifier pre-signs last, or by any verifier calling this again once all have signed. Every
+    // secret nonce used for a partial signature is removed from the secret nonces.
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<Txid, HashMap<usize, SecNonce>>,
+    ) -> Result<(), BridgeError> {
+        register_n_of_n_public_keys(&mut self.n_of_n_public_keys, context)?;
+
+        let peg_in_confirm_txid = self.peg_in_confirm_transaction.tx().compute_txid();
+        let result = self.peg_in_confirm_transaction.pre_sign(
+            context,
+            secret_nonces.entry(peg_in_confirm_txid).or_default(),
+        );
+        secret_nonces.retain(|_, secret_nonces| !secret_nonces.is_empty());
+        result?;
+
+        self.update_pre_signing_state();
+
+        Ok(())
+    }
+
+    pub fn merge(&mut self, source_peg_in_graph: &PegInGraph) -> Result<(), BridgeError> {
+        if self.id != source_peg_in_graph.id {
+            return Err(BridgeError::GraphMerge(format!(
+                "Cannot merge peg-in graph {} into peg-in graph {}",
+                source_peg_in_graph.id, self.id
+            )));
+        }
+
+        self.peg_in_deposit_transaction
+            .merge(&source_peg_in_graph.peg_in_deposit_transaction)?;
+        self.peg_in_refund_transaction
+            .merge(&source_peg_in_graph.peg_in_refund_transaction)?;
+        self.peg_in_confirm_transaction
+            .merge(&source_peg_in_graph.peg_in_confirm_transaction)?;
+
+        merge_n_of_n_public_keys(
+            &mut self.n_of_n_public_keys,
+            &source_peg_in_graph.n_of_n_public_keys,
+        )?;
+        self.update_pre_signing_state();
+
+        Ok(())
+    }
+
+    pub fn verify(&self, n_of_n_taproot_public_key: &XOnlyPublicKey) -> Result<(), BridgeError> {
+        verify_version("Peg-in", &self.id, &self.version)?;
+        if self.id != generate_id(&self.peg_in_deposit_transaction) {
+            return Err(BridgeError::InvalidGraph(format!(
+                "Peg-in graph {} has an invalid id",
+                self.id
+            )));
+        }
+
+        let connector_0 = Connector0::new(self.network, n_of_n_taproot_public_key);
+        let connector_z = ConnectorZ::new(
+            self.network,
+            &self.depositor_evm_address,
+            &self.depositor_taproot_public_key,
+            n_of_n_taproot_public_key,
+            self.parameters.peg_in_refund_timelock,
+        );
+        let depositor_script_pubkey =
+            generate_pay_to_pubkey_script_address(self.network, &self.depositor_public_key)
+                .script_pubkey();
+
+        let peg_in_deposit_tx = self.peg_in_deposit_transaction.tx();
+        verify_prev_out_script_pubkey(
+            "Peg-in deposit",
+            &self.peg_in_deposit_transaction,
+            0,
+            &depositor_script_pubkey,
+        )?;
+        verify_output_script_pubkey(
+            "Peg-in deposit",
+            peg_in_deposit_tx,
+            0,
+            &connector_z.generate_taproot_address().script_pubkey(),
+        )?;
+
+        verify_input(
+            "Peg-in refund",
+            &self.peg_in_refund_transaction,
+            0,
+            peg_in_deposit_tx,
+            0,
+        )?;
+        verify_output_script_pubkey(
+            "Peg-in refund",
+            self.peg_in_refund_transaction.tx(),
+            0,
+            &depositor_script_pubkey,
+        )?;
+
+        verify_input(
+            "Peg-in confirm",
+            &self.peg_in_confirm_transaction,
+            0,
+            peg_in_deposit_tx,
+            0,
+        )?;
+        verify_output_script_pubkey(
+            "Peg-in confirm",
+            self.peg_in_confirm_transaction.tx(),
+            0,
+            &connector_0.generate_taproot_address().script_pubkey(),
+        )?;
+
+        verify_witnesses("Peg-in deposit", &self.peg_in_deposit_transaction)?;
+        verify_witnesses("Peg-in refund", &self.peg_in_refund_transaction)?;
+        verify_witnesses("Peg-in confirm", &self.peg_in_confirm_transaction)?;
+
+        Ok(())
+    }
 
-        self.n_of_n_presigned = true; // TODO: set to true after collecting all n of n signatures
+    pub fn peg_in_deposit_transaction_ref(&self) -> &PegInDepositTransaction {
+        &self.peg_in_deposit_transaction
     }
 
     pub fn peg_in_confirm_transaction_ref(&self) -> &PegInConfirmTransaction {
         &self.peg_in_confirm_transaction
     }
 
-    pub async fn verifier_status(&self, client: &AsyncClient) -> PegInVerifierStatus {
+    pub fn peg_in_refund_transaction_ref(&self) -> &PegInRefundTransaction {
+        &self.peg_in_refund_transaction
+    }
+
+    pub async fn verifier_status(&self, client: &dyn ChainBackend) -> PegInVerifierStatus {
         let (peg_in_deposit_status, peg_in_confirm_status, _) =
             Self::get_peg_in_statuses(self, client).await;
 
@@ -166,7 +389,7 @@ impl PegInGraph {
                 // peg in complete
                 return PegInVerifierStatus::PegInComplete;
             } else {
-                if self.n_of_n_presigned {
+                if self.n_of_n_pre_signing_state == PreSigningState::Presigned {
                     // peg-in confirm presigned, wait
                     return PegInVerifierStatus::PegInWait;
                 } else {
@@ -180,7 +403,7 @@ impl PegInGraph {
         }
     }
 
-    pub async fn operator_status(&self, client: &AsyncClient) -> PegInOperatorStatus {
+    pub async fn operator_status(&self, client: &dyn ChainBackend) -> PegInOperatorStatus {
         let (peg_in_deposit_status, peg_in_confirm_status, _) =
             Self::get_peg_in_statuses(self, client).await;
 
@@ -189,7 +412,7 @@ impl PegInGraph {
                 // peg in complete
                 return PegInOperatorStatus::PegInComplete;
             } else {
-                if self.n_of_n_presigned {
+                if self.n_of_n_pre_signing_state == PreSigningState::Presigned {
                     // should execute peg-in confirm
                     return PegInOperatorStatus::PegInConfirmAvailable;
                 } else {
@@ -203,11 +426,14 @@ impl PegInGraph {
         }
     }
 
-    pub async fn depositor_status(&self, client: &AsyncClient) -> PegInDepositorStatus {
+    pub async fn depositor_status(
+        &self,
+        client: &dyn ChainBackend,
+    ) -> Result<PegInDepositorStatus, BridgeError> {
         let (peg_in_deposit_status, peg_in_confirm_status, peg_in_refund_status) =
             Self::get_peg_in_statuses(self, client).await;
 
-        let blockchain_height = get_block_height(client).await;
+        let blockchain_height = get_block_height(client).await?;
 
         if peg_in_deposit_status
             .as_ref()
@@ -215,40 +441,170 @@ impl PegInGraph {
         {
             if peg_in_confirm_status.is_ok_and(|status| status.confirmed) {
                 // peg-in complete
-                return PegInDepositorStatus::PegInConfirmComplete;
+                return Ok(PegInDepositorStatus::PegInConfirmComplete);
             } else {
                 if peg_in_deposit_status
                     .unwrap()
                     .block_height
                     .is_some_and(|block_height| {
-                        block_height + NUM_BLOCKS_PER_2_WEEKS <= blockchain_height
+                        block_height + self.parameters.peg_in_refund_timelock <= blockchain_height
                     })
                 {
                     if peg_in_refund_status.is_ok_and(|status| status.confirmed) {
                         // peg-in refund complete
-                        return PegInDepositorStatus::PegInRefundComplete;
+                        return Ok(PegInDepositorStatus::PegInRefundComplete);
                     } else {
                         // peg-in refund available
-                        return PegInDepositorStatus::PegInRefundAvailable;
+                        return Ok(PegInDepositorStatus::PegInRefundAvailable);
                     }
                 } else {
                     // peg-in confirm not confirmed yet, refund not available yet, wait
-                    return PegInDepositorStatus::PegInConfirmWait;
+                    return Ok(PegInDepositorStatus::PegInConfirmWait);
                 }
             }
         } else {
             // peg-in deposit not confirmed yet, wait
-            return PegInDepositorStatus::PegInDepositWait;
+            return Ok(PegInDepositorStatus::PegInDepositWait);
         }
     }
 
+    pub async fn deposit(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.peg_in_deposit_transaction.tx().compute_txid()).await?;
+
+        // complete deposit tx
+        let peg_in_deposit_tx = self.peg_in_deposit_transaction.finalize();
+
+        // broadcast deposit tx
+        let peg_in_deposit_result = client.broadcast(&peg_in_deposit_tx).await;
+
+        // verify deposit tx result
+        verify_tx_result(peg_in_deposit_result)
+    }
+
+    pub async fn confirm(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.peg_in_confirm_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.peg_in_refund_transaction.tx().compute_txid()).await?;
+
+        if self.n_of_n_pre_signing_state != PreSigningState::Presigned {
+            return Err(BridgeError::PreSigning(String::from(
+                "Peg-in confirm has not been pre-signed by every verifier",
+            )));
+        }
+        if !self.peg_in_confirm_transaction.has_depositor_signature() {
+            return Err(BridgeError::PreSigning(String::from(
+                "Peg-in confirm is missing the depositor signature",
+            )));
+        }
+
+        let peg_in_deposit_status = client
+            .get_tx_status(&self.peg_in_deposit_transaction.tx().compute_txid())
+            .await?;
+
+        if peg_in_deposit_status.confirmed {
+            // complete confirm tx
+            let peg_in_confirm_tx = self.peg_in_confirm_transaction.finalize();
+
+            // broadcast confirm tx
+            let peg_in_confirm_result = client.broadcast(&peg_in_confirm_tx).await;
+
+            // verify confirm tx result
+            verify_tx_result(peg_in_confirm_result)
+        } else {
+            Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Peg-in deposit",
+            )))
+        }
+    }
+
+    // Returns the deposit to the depositor once the peg-in refund timelock elapsed without the
+    // operator confirming the peg-in. The refund was signed by the depositor when creating the graph.
+    pub async fn refund(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        match self.depositor_status(client).await? {
+            PegInDepositorStatus::PegInRefundAvailable => {
+                // complete refund tx
+                let peg_in_refund_tx = self.peg_in_refund_transaction.finalize();
+
+                // broadcast refund tx
+                let peg_in_refund_result = client.broadcast(&peg_in_refund_tx).await;
+
+                // verify refund tx result
+                verify_tx_result(peg_in_refund_result)
+            }
+            PegInDepositorStatus::PegInDepositWait => Err(BridgeError::TransactionNotConfirmed(
+                String::from("Peg-in deposit"),
+            )),
+            PegInDepositorStatus::PegInConfirmWait => Err(BridgeError::TimelockNotElapsed(
+                String::from("Peg-in deposit"),
+            )),
+            PegInDepositorStatus::PegInConfirmComplete => {
+                Err(BridgeError::TransactionAlreadyMined(
+                    self.peg_in_confirm_transaction.tx().compute_txid(),
+                ))
+            }
+            PegInDepositorStatus::PegInRefundComplete => Err(BridgeError::TransactionAlreadyMined(
+                self.peg_in_refund_transaction.tx().compute_txid(),
+            )),
+        }
+    }
+
+    // Status of the graph for the role, together with the transactions, the timelocks and the
+    // action the role is expected to take next.
+    pub async fn status_report(
+        &self,
+        client: &dyn ChainBackend,
+        role: GraphRole,
+    ) -> GraphStatusReport {
+        let mut report = GraphStatusReport::new(&self.id, role);
+        report.block_height = get_block_height(client).await.ok();
+
+        for (name, txid) in [
+            (
+                "Peg-in deposit",
+                self.peg_in_deposit_transaction.tx().compute_txid(),
+            ),
+            (
+                "Peg-in confirm",
+                self.peg_in_confirm_transaction.tx().compute_txid(),
+            ),
+            (
+                "Peg-in refund",
+                self.peg_in_refund_transaction.tx().compute_txid(),
+            ),
+        ] {
+            report
+                .transactions
+                .push(get_transaction_status_report(client, name, txid).await);
+        }
+        report.add_timelock(
+            "Peg-in refund",
+            "Peg-in deposit",
+            self.parameters.peg_in_refund_timelock,
+        );
+
+        match role {
+            GraphRole::Depositor => match self.depositor_status(client).await {
+                Ok(status) => report.set_status(GraphStatus::PegInDepositor(status)),
+                Err(err) => report.set_error(err),
+            },
+            GraphRole::Operator => report.set_status(GraphStatus::PegInOperator(
+                self.operator_status(client).await,
+            )),
+            GraphRole::Verifier => report.set_status(GraphStatus::PegInVerifier(
+                self.verifier_status(client).await,
+            )),
+            GraphRole::Withdrawer => report.set_error("A withdrawer only takes part in peg-outs"),
+        }
+
+        report
+    }
+
     async fn get_peg_in_statuses(
         &self,
-        client: &AsyncClient,
+        client: &dyn ChainBackend,
     ) -> (
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
     ) {
         let peg_in_deposit_status = client
             .get_tx_status(&self.peg_in_deposit_transaction.tx().compute_txid())
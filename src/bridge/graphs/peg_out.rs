@@ -1,34 +1,80 @@
 use bitcoin::{
     hex::{Case::Upper, DisplayHex},
     key::Keypair,
-    Amount, Network, OutPoint, PublicKey, ScriptBuf, Txid, XOnlyPublicKey,
+    Address, Amount, FeeRate, Network, OutPoint, Psbt, PublicKey, ScriptBuf, Transaction, Txid,
+    XOnlyPublicKey,
 };
-use esplora_client::{AsyncClient, Error, TxStatus};
+use esplora_client::TxStatus;
+use musig2::SecNonce;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 
-use crate::bridge::{
-    constants::{NUM_BLOCKS_PER_2_WEEKS, NUM_BLOCKS_PER_4_WEEKS},
-    contexts::{base::BaseContext, verifier::VerifierContext},
-    transactions::base::{BaseTransaction, InputWithScript},
+use crate::{
+    bridge::{
+        contexts::{base::BaseContext, verifier::VerifierContext},
+        transactions::base::{BaseTransaction, InputWithScript},
+    },
+    signatures::winternitz::N,
 };
 
 use super::{
     super::{
+        client::chain::base::{ChainBackend, ChainError},
+        commitments::{
+            generate_commit_y_public_key, generate_peg_out_claim, CommitmentValue,
+            WinternitzPublicKey, WinternitzSignature,
+        },
+        connectors::{
+            connector::{P2wshConnector, TaprootConnector},
+            connector_1::Connector1,
+            connector_2::Connector2,
+            connector_3::Connector3,
+            connector_a::ConnectorA,
+            connector_b::ConnectorB,
+            connector_c::ConnectorC,
+            connector_y::ConnectorY,
+        },
         contexts::operator::OperatorContext,
+        error::BridgeError,
+        parameters::BridgeParameters,
+        scripts::generate_pay_to_pubkey_script_address,
         transactions::{
-            assert::AssertTransaction, base::Input, burn::BurnTransaction,
-            challenge::ChallengeTransaction, disprove::DisproveTransaction,
-            kick_off::KickOffTransaction, peg_out::PegOutTransaction,
-            pre_signed::PreSignedTransaction, take1::Take1Transaction, take2::Take2Transaction,
+            assert::AssertTransaction,
+            base::Input,
+            burn::BurnTransaction,
+            challenge::ChallengeTransaction,
+            cpfp::CpfpTransaction,
+            disprove::DisproveTransaction,
+            fee::get_fee,
+            kick_off::{extract_commit_y, KickOffTransaction},
+            peg_out::PegOutTransaction,
+            pre_signed::PreSignedTransaction,
+            pre_signed_musig2::{
+                has_all_nonces, has_all_signatures, has_nonces_of, has_signatures_of,
+                PreSignedMusig2Transaction,
+            },
+            take1::Take1Transaction,
+            take2::Take2Transaction,
         },
     },
-    base::{get_block_height, verify_if_not_mined, verify_tx_result, BaseGraph, GRAPH_VERSION},
+    base::{
+        get_block_height, get_other_spending_tx_status, get_other_spending_txid,
+        get_pre_signing_input_statuses, get_pre_signing_state, merge_n_of_n_public_keys,
+        register_n_of_n_public_keys, verify_if_not_mined, verify_input,
+        verify_output_script_pubkey, verify_prev_out_script_pubkey, verify_tx_result,
+        verify_version, verify_witnesses, BaseGraph, PreSigningInputStatus, PreSigningState,
+        GRAPH_VERSION,
+    },
     peg_in::PegInGraph,
+    status::{get_transaction_status_report, GraphRole, GraphStatus, GraphStatusReport},
 };
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegOutDepositorStatus {
     PegOutNotStarted, // peg-out transaction not created yet
     PegOutWait,       // peg-out not confirmed yet, wait
@@ -47,35 +93,69 @@ impl Display for PegOutDepositorStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PegOutWithdrawerStatus {
+    PegOutNotRequested, // peg-out not requested yet
+    PegOutRequested,    // peg-out requested, wait for an operator to pay the withdrawer
+    PegOutWait,         // peg-out not confirmed yet, wait
+    PegOutComplete,     // peg-out complete
+}
+
+impl Display for PegOutWithdrawerStatus {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PegOutWithdrawerStatus::PegOutNotRequested => write!(f, "peg-out not requested yet"),
+            PegOutWithdrawerStatus::PegOutRequested => {
+                write!(
+                    f,
+                    "peg-out requested, wait for an operator to pay the withdrawer"
+                )
+            }
+            PegOutWithdrawerStatus::PegOutWait => write!(f, "peg-out not confirmed yet, wait"),
+            PegOutWithdrawerStatus::PegOutComplete => write!(f, "peg-out complete"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegOutVerifierStatus {
     PegOutPresign,           // should presign peg-out graph
-    PegOutComplete,          // peg-out complete
+    PegOutCompletedByTake1,  // peg-out complete, operator took 1
+    PegOutCompletedByTake2,  // peg-out complete, operator took 2
     PegOutWait,              // no action required, wait
     PegOutChallengeAvailabe, // can challenge
-    PegOutBurnAvailable,
-    PegOutDisproveAvailable,
-    PegOutFailed, // burn or disprove executed
+    PegOutBurnAvailable,     // can burn, possibly challenge as well
+    PegOutDisproveAvailable, // can disprove
+    PegOutFailedByBurn,      // operator burnt
+    PegOutFailedByDisprove,  // operator disproven
 }
 
 impl Display for PegOutVerifierStatus {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
-            PegOutVerifierStatus::PegOutPresign => write!(f, "peg-out transaction not created yet"),
-            PegOutVerifierStatus::PegOutComplete => write!(f, "peg-out not confirmed yet, wait"),
+            PegOutVerifierStatus::PegOutPresign => write!(f, "should presign peg-out graph"),
+            PegOutVerifierStatus::PegOutCompletedByTake1 => write!(f, "peg-out complete, take1"),
+            PegOutVerifierStatus::PegOutCompletedByTake2 => write!(f, "peg-out complete, take2"),
             PegOutVerifierStatus::PegOutWait => write!(f, "no action required, wait"),
             PegOutVerifierStatus::PegOutChallengeAvailabe => write!(f, "can challenge"),
             PegOutVerifierStatus::PegOutBurnAvailable => write!(f, "can burn"),
             PegOutVerifierStatus::PegOutDisproveAvailable => write!(f, "can disprove"),
-            PegOutVerifierStatus::PegOutFailed => write!(f, "burn or disprove executed"),
+            PegOutVerifierStatus::PegOutFailedByBurn => write!(f, "peg-out failed, burn executed"),
+            PegOutVerifierStatus::PegOutFailedByDisprove => {
+                write!(f, "peg-out failed, disprove executed")
+            }
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum PegOutOperatorStatus {
     PegOutWait,
-    PegOutComplete,    // peg-out complete
-    PegOutFailed,      // burn or disprove executed
-    PegOutStartPegOut, // should execute peg-out tx
+    PegOutCompletedByTake1, // peg-out complete, took 1
+    PegOutCompletedByTake2, // peg-out complete, took 2
+    PegOutFailedByBurn,     // burnt
+    PegOutFailedByDisprove, // disproven
+    PegOutStartPegOut,      // should execute peg-out tx
     PegOutKickOffAvailable,
     PegOutAssertAvailable,
     PegOutTake1Available,
@@ -86,8 +166,12 @@ impl Display for PegOutOperatorStatus {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
             PegOutOperatorStatus::PegOutWait => write!(f, "no action required, wait"),
-            PegOutOperatorStatus::PegOutComplete => write!(f, "peg-out complete"),
-            PegOutOperatorStatus::PegOutFailed => write!(f, "burn or disprove executed"),
+            PegOutOperatorStatus::PegOutCompletedByTake1 => write!(f, "peg-out complete, take1"),
+            PegOutOperatorStatus::PegOutCompletedByTake2 => write!(f, "peg-out complete, take2"),
+            PegOutOperatorStatus::PegOutFailedByBurn => write!(f, "peg-out failed, burn executed"),
+            PegOutOperatorStatus::PegOutFailedByDisprove => {
+                write!(f, "peg-out failed, disprove executed")
+            }
             PegOutOperatorStatus::PegOutStartPegOut => write!(f, "should execute peg-out tx"),
             PegOutOperatorStatus::PegOutKickOffAvailable => write!(f, "can kick off"),
             PegOutOperatorStatus::PegOutAssertAvailable => write!(f, "can assert"),
@@ -97,15 +181,124 @@ impl Display for PegOutOperatorStatus {
     }
 }
 
+// Where the graph stands on chain. Every terminal state records which transaction ended it.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PegOutState {
+    PreSigning,       // the verifiers have not presigned the graph yet
+    PegOutPending,    // the operator has not paid the withdrawer yet
+    KickOffPending,   // peg-out confirmed, kick-off not confirmed yet
+    KickedOff,        // kick-off confirmed, neither challenged nor taken nor burnt
+    Challenged,       // challenge confirmed, neither asserted nor burnt
+    Asserted,         // assert confirmed, neither taken nor disproven
+    CompletedByTake1, // final
+    CompletedByTake2, // final
+    FailedByBurn,     // final
+    FailedByDisprove, // final
+}
+
+impl Display for PegOutState {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PegOutState::PreSigning => write!(f, "pre-signing"),
+            PegOutState::PegOutPending => write!(f, "peg-out pending"),
+            PegOutState::KickOffPending => write!(f, "kick-off pending"),
+            PegOutState::KickedOff => write!(f, "kicked off"),
+            PegOutState::Challenged => write!(f, "challenged"),
+            PegOutState::Asserted => write!(f, "asserted"),
+            PegOutState::CompletedByTake1 => write!(f, "completed by take1"),
+            PegOutState::CompletedByTake2 => write!(f, "completed by take2"),
+            PegOutState::FailedByBurn => write!(f, "failed by burn"),
+            PegOutState::FailedByDisprove => write!(f, "failed by disprove"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PegOutAction {
+    PreSign,
+    PegOut,
+    KickOff,
+    Take1,
+    Challenge,
+    Assert,
+    Take2,
+    Disprove,
+    Burn,
+}
+
+impl PegOutAction {
+    pub fn role(&self) -> GraphRole {
+        match self {
+            PegOutAction::PreSign
+            | PegOutAction::Challenge
+            | PegOutAction::Disprove
+            | PegOutAction::Burn => GraphRole::Verifier,
+            PegOutAction::PegOut
+            | PegOutAction::KickOff
+            | PegOutAction::Take1
+            | PegOutAction::Assert
+            | PegOutAction::Take2 => GraphRole::Operator,
+        }
+    }
+}
+
+impl Display for PegOutAction {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            PegOutAction::PreSign => write!(f, "presign"),
+            PegOutAction::PegOut => write!(f, "peg-out"),
+            PegOutAction::KickOff => write!(f, "kick-off"),
+            PegOutAction::Take1 => write!(f, "take1"),
+            PegOutAction::Challenge => write!(f, "challenge"),
+            PegOutAction::Assert => write!(f, "assert"),
+            PegOutAction::Take2 => write!(f, "take2"),
+            PegOutAction::Disprove => write!(f, "disprove"),
+            PegOutAction::Burn => write!(f, "burn"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct PegOutGraphState {
+    pub state: PegOutState,
+    pub available_actions: Vec<PegOutAction>,
+}
+
+impl PegOutGraphState {
+    pub fn actions_of(&self, role: GraphRole) -> Vec<PegOutAction> {
+        self.available_actions
+            .iter()
+            .filter(|action| action.role() == role)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_available(&self, action: PegOutAction) -> bool {
+        self.available_actions.contains(&action)
+    }
+}
+
+// A withdrawer burnt wrapped BTC on the EVM chain and asks to be paid the peg-in on bitcoin. Every
+// operator of the peg-in can front the peg-out and is later reimbursed by its own peg-out graph.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct PegOutRequest {
+    pub peg_in_graph_id: String,
+    pub withdrawer_public_key: PublicKey, // the withdrawer saves the request with this key
+    pub withdrawer_evm_address: String,
+    pub evm_burn_tx_hash: String,
+    pub destination_script_pubkey: ScriptBuf, // the peg-out pays to this script
+}
+
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct PegOutGraph {
     version: String,
     network: Network,
     id: String,
+    parameters: BridgeParameters,
 
     // state: State,
-    // n_of_n_pre_signing_state: PreSigningState,
-    n_of_n_presigned: bool,
+    n_of_n_public_keys: Vec<PublicKey>,
+    n_of_n_pre_signing_state: PreSigningState,
 
     peg_in_graph_id: String,
     peg_in_confirm_txid: Txid,
@@ -119,10 +312,14 @@ pub struct PegOutGraph {
 
     operator_public_key: PublicKey,
     operator_taproot_public_key: XOnlyPublicKey,
+    // Winternitz public key of this graph committing to the claim y in the kick-off
+    operator_commit_y_public_key: WinternitzPublicKey,
 
     withdrawer_public_key: Option<PublicKey>,
     withdrawer_taproot_public_key: Option<XOnlyPublicKey>,
     withdrawer_evm_address: Option<String>,
+    withdrawer_evm_burn_tx_hash: Option<String>,
+    withdrawer_destination_script_pubkey: Option<ScriptBuf>,
 
     peg_out_transaction: Option<PegOutTransaction>,
 }
@@ -134,8 +331,13 @@ impl BaseGraph for PegOutGraph {
 }
 
 impl PegOutGraph {
+    // The kick-off input is funded with CommitYFundingTransaction, from the Commit Y address of
+    // the graph.
     pub fn new(context: &OperatorContext, peg_in_graph: &PegInGraph, kickoff_input: Input) -> Self {
-        let kick_off_transaction = KickOffTransaction::new(context, kickoff_input);
+        let id = generate_id(peg_in_graph, &context.operator_public_key);
+        let commit_y_public_key = generate_commit_y_public_key(context, &id);
+        let kick_off_transaction =
+            KickOffTransaction::new(context, kickoff_input, &commit_y_public_key);
         let kick_off_txid = kick_off_transaction.tx().compute_txid();
 
         let peg_in_confirm_transaction = peg_in_graph.peg_in_confirm_transaction_ref();
@@ -176,7 +378,8 @@ impl PegOutGraph {
             },
         );
 
-        let input_amount_crowdfunding = Amount::from_btc(1.0).unwrap(); // TODO replace placeholder
+        let input_amount_crowdfunding =
+            Amount::from_sat(context.parameters.challenge_crowdfunding_amount);
         let challenge_vout0 = 1;
         let challenge_transaction = ChallengeTransaction::new(
             context,
@@ -201,7 +404,7 @@ impl PegOutGraph {
                 amount: kick_off_transaction.tx().output[assert_vout0].value,
             },
         );
-        let assert_txid = kick_off_transaction.tx().compute_txid();
+        let assert_txid = assert_transaction.tx().compute_txid();
 
         let take2_vout0 = 0;
         let take2_vout1 = 0;
@@ -268,8 +471,10 @@ impl PegOutGraph {
         PegOutGraph {
             version: GRAPH_VERSION.to_string(),
             network: context.network,
-            id: generate_id(peg_in_graph, &context.operator_public_key),
-            n_of_n_presigned: false,
+            id,
+            parameters: context.parameters,
+            n_of_n_public_keys: Vec::new(),
+            n_of_n_pre_signing_state: PreSigningState::AwaitingNonces,
             peg_in_graph_id: peg_in_graph.id().clone(),
             peg_in_confirm_txid,
             kick_off_transaction,
@@ -281,162 +486,565 @@ impl PegOutGraph {
             burn_transaction,
             operator_public_key: context.operator_public_key,
             operator_taproot_public_key: context.operator_taproot_public_key,
+            operator_commit_y_public_key: commit_y_public_key,
             withdrawer_public_key: None,
             withdrawer_taproot_public_key: None,
             withdrawer_evm_address: None,
+            withdrawer_evm_burn_tx_hash: None,
+            withdrawer_destination_script_pubkey: None,
             peg_out_transaction: None,
         }
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) {
-        self.assert_transaction.pre_sign(context);
-        self.burn_transaction.pre_sign(context);
-        self.disprove_transaction.pre_sign(context);
-        self.take1_transaction.pre_sign(context);
-        self.take2_transaction.pre_sign(context);
+    fn musig2_transactions(&self) -> Vec<&dyn PreSignedMusig2Transaction> {
+        vec![
+            &self.take1_transaction,
+            &self.assert_transaction,
+            &self.take2_transaction,
+            &self.disprove_transaction,
+            &self.burn_transaction,
+        ]
+    }
 
-        self.n_of_n_presigned = true; // TODO: set to true after collecting all n of n signatures
+    pub fn has_nonces_of(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_nonces_of(*tx, context))
     }
 
-    pub async fn verifier_status(&self, client: &AsyncClient) -> PegOutVerifierStatus {
-        if self.n_of_n_presigned {
-            let (
-                kick_off_status,
-                challenge_status,
-                assert_status,
-                disprove_status,
-                burn_status,
-                take1_status,
-                take2_status,
-                _,
-            ) = Self::get_peg_out_statuses(self, client).await;
-            let blockchain_height = get_block_height(client).await;
+    pub fn has_all_nonces(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_all_nonces(*tx, context))
+    }
 
-            if kick_off_status
-                .as_ref()
-                .is_ok_and(|status| status.confirmed)
-            {
-                // check take1 and take2
-                if take1_status.as_ref().is_ok_and(|status| status.confirmed)
-                    || take2_status.as_ref().is_ok_and(|status| status.confirmed)
-                {
-                    return PegOutVerifierStatus::PegOutComplete;
-                }
+    pub fn has_signatures_of(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_signatures_of(*tx, context))
+    }
 
-                // check burn and disprove
-                if burn_status.as_ref().is_ok_and(|status| status.confirmed)
-                    || disprove_status
-                        .as_ref()
-                        .is_ok_and(|status| status.confirmed)
-                {
-                    return PegOutVerifierStatus::PegOutFailed; // TODO: can be also `PegOutVerifierStatus::PegOutComplete`
-                }
+    pub fn has_all_signatures(&self, context: &VerifierContext) -> bool {
+        self.musig2_transactions()
+            .iter()
+            .all(|tx| has_all_signatures(*tx, context))
+    }
 
-                if kick_off_status
-                    .as_ref()
+    pub fn pre_signing_state(&self) -> PreSigningState { self.n_of_n_pre_signing_state }
+
+    // Lists every n-of-n input together with the verifiers it still needs nonces or partial
+    // signatures from.
+    pub fn pre_signing_status(&self) -> Vec<PreSigningInputStatus> {
+        get_pre_signing_input_statuses(&self.musig2_transactions(), &self.n_of_n_public_keys)
+    }
+
+    fn update_pre_signing_state(&mut self) {
+        self.n_of_n_pre_signing_state = get_pre_signing_state(
+            self.n_of_n_pre_signing_state,
+            &self.musig2_transactions(),
+            &self.n_of_n_public_keys,
+        );
+    }
+
+    // Derives the pre-signing state from the nonces and witnesses alone, the state stored in a
+    // graph fetched from another participant is not trusted.
+    pub fn recompute_pre_signing_state(&mut self) {
+        self.n_of_n_pre_signing_state = get_pre_signing_state(
+            PreSigningState::AwaitingNonces,
+            &self.musig2_transactions(),
+            &self.n_of_n_public_keys,
+        );
+    }
+
+    // Returns the secret nonces, which must be kept by the verifier until it pre-signs the graph.
+    pub fn push_nonces(
+        &mut self,
+        context: &VerifierContext,
+    ) -> Result<HashMap<Txid, HashMap<usize, SecNonce>>, BridgeError> {
+        register_n_of_n_public_keys(&mut self.n_of_n_public_keys, context)?;
+
+        let mut secret_nonces = HashMap::new();
+        secret_nonces.insert(
+            self.take1_transaction.tx().compute_txid(),
+            self.take1_transaction.push_nonces(context),
+        );
+        secret_nonces.insert(
+            self.assert_transaction.tx().compute_txid(),
+            self.assert_transaction.push_nonces(context),
+        );
+        secret_nonces.insert(
+            self.take2_transaction.tx().compute_txid(),
+            self.take2_transaction.push_nonces(context),
+        );
+        secret_nonces.insert(
+            self.disprove_transaction.tx().compute_txid(),
+            self.disprove_transaction.push_nonces(context),
+        );
+        secret_nonces.insert(
+            self.burn_transaction.tx().compute_txid(),
+            self.burn_transaction.push_nonces(context),
+        );
+
+        self.update_pre_signing_state();
+
+        Ok(secret_nonces)
+    }
+
+    // Requires the nonces of all verifiers. The n-of-n signatures are aggregated by whichever
+    // verifier pre-signs last, or by any verifier calling this again once all have signed. Every
+    // secret nonce used for a partial signature is removed from the secret nonces.
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<Txid, HashMap<usize, SecNonce>>,
+    ) -> Result<(), BridgeError> {
+        register_n_of_n_public_keys(&mut self.n_of_n_public_keys, context)?;
+
+        let result = self.pre_sign_transactions(context, secret_nonces);
+        secret_nonces.retain(|_, secret_nonces| !secret_nonces.is_empty());
+        result?;
+
+        self.update_pre_signing_state();
+
+        Ok(())
+    }
+
+    fn pre_sign_transactions(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<Txid, HashMap<usize, SecNonce>>,
+    ) -> Result<(), BridgeError> {
+        self.take1_transaction.pre_sign(
+            context,
+            secret_nonces
+                .entry(self.take1_transaction.tx().compute_txid())
+                .or_default(),
+        )?;
+        self.assert_transaction.pre_sign(
+            context,
+            secret_nonces
+                .entry(self.assert_transaction.tx().compute_txid())
+                .or_default(),
+        )?;
+        self.take2_transaction.pre_sign(
+            context,
+            secret_nonces
+                .entry(self.take2_transaction.tx().compute_txid())
+                .or_default(),
+        )?;
+        self.disprove_transaction.pre_sign(
+            context,
+            secret_nonces
+                .entry(self.disprove_transaction.tx().compute_txid())
+                .or_default(),
+        )?;
+        self.burn_transaction.pre_sign(
+            context,
+            secret_nonces
+                .entry(self.burn_transaction.tx().compute_txid())
+                .or_default(),
+        )
+    }
+
+    pub fn merge(&mut self, source_peg_out_graph: &PegOutGraph) -> Result<(), BridgeError> {
+        if self.id != source_peg_out_graph.id {
+            return Err(BridgeError::GraphMerge(format!(
+                "Cannot merge peg-out graph {} into peg-out graph {}",
+                source_peg_out_graph.id, self.id
+            )));
+        }
+
+        self.kick_off_transaction
+            .merge(&source_peg_out_graph.kick_off_transaction)?;
+        self.take1_transaction
+            .merge(&source_peg_out_graph.take1_transaction)?;
+        self.challenge_transaction
+            .merge(&source_peg_out_graph.challenge_transaction)?;
+        self.assert_transaction
+            .merge(&source_peg_out_graph.assert_transaction)?;
+        self.take2_transaction
+            .merge(&source_peg_out_graph.take2_transaction)?;
+        self.disprove_transaction
+            .merge(&source_peg_out_graph.disprove_transaction)?;
+        self.burn_transaction
+            .merge(&source_peg_out_graph.burn_transaction)?;
+
+        merge_n_of_n_public_keys(
+            &mut self.n_of_n_public_keys,
+            &source_peg_out_graph.n_of_n_public_keys,
+        )?;
+        self.update_pre_signing_state();
+
+        if self.withdrawer_public_key.is_none() {
+            self.withdrawer_public_key = source_peg_out_graph.withdrawer_public_key;
+            self.withdrawer_taproot_public_key = source_peg_out_graph.withdrawer_taproot_public_key;
+            self.withdrawer_evm_address = source_peg_out_graph.withdrawer_evm_address.clone();
+            self.withdrawer_evm_burn_tx_hash =
+                source_peg_out_graph.withdrawer_evm_burn_tx_hash.clone();
+            self.withdrawer_destination_script_pubkey = source_peg_out_graph
+                .withdrawer_destination_script_pubkey
+                .clone();
+        } else if source_peg_out_graph.withdrawer_public_key.is_some()
+            && (self.withdrawer_public_key != source_peg_out_graph.withdrawer_public_key
+                || self.withdrawer_destination_script_pubkey
+                    != source_peg_out_graph.withdrawer_destination_script_pubkey)
+        {
+            return Err(BridgeError::GraphMerge(format!(
+                "Peg-out graph {} has conflicting withdrawers",
+                self.id
+            )));
+        }
+
+        if source_peg_out_graph.peg_out_transaction.is_some() {
+            let source_peg_out_transaction =
+                source_peg_out_graph.peg_out_transaction.as_ref().unwrap();
+            if self.peg_out_transaction.is_some() {
+                self.peg_out_transaction
+                    .as_mut()
                     .unwrap()
-                    .block_height
-                    .is_some_and(|block_height| {
-                        block_height + NUM_BLOCKS_PER_4_WEEKS > blockchain_height
-                    })
-                {
-                    if challenge_status
-                        .as_ref()
-                        .is_ok_and(|status| !status.confirmed)
-                    {
-                        return PegOutVerifierStatus::PegOutChallengeAvailabe;
-                    } else if assert_status.as_ref().is_ok_and(|status| status.confirmed) {
-                        return PegOutVerifierStatus::PegOutDisproveAvailable;
-                    } else {
-                        return PegOutVerifierStatus::PegOutWait;
-                    }
-                } else {
-                    if assert_status.is_ok_and(|status| !status.confirmed) {
-                        return PegOutVerifierStatus::PegOutBurnAvailable; // TODO: challange and burn available here
-                    } else {
-                        return PegOutVerifierStatus::PegOutDisproveAvailable;
-                    }
-                }
+                    .merge(source_peg_out_transaction)?;
             } else {
-                return PegOutVerifierStatus::PegOutWait;
+                self.peg_out_transaction = Some(source_peg_out_transaction.clone());
             }
-        } else {
-            return PegOutVerifierStatus::PegOutPresign;
-        }
-    }
-
-    pub async fn operator_status(&self, client: &AsyncClient) -> PegOutOperatorStatus {
-        if self.n_of_n_presigned {
-            let (
-                kick_off_status,
-                challenge_status,
-                assert_status,
-                disprove_status,
-                burn_status,
-                take1_status,
-                take2_status,
-                peg_out_status,
-            ) = Self::get_peg_out_statuses(self, client).await;
-            let blockchain_height = get_block_height(client).await;
-
-            if peg_out_status.is_some_and(|status| status.unwrap().confirmed) {
-                if kick_off_status
-                    .as_ref()
-                    .is_ok_and(|status| status.confirmed)
-                {
-                    // check take1 and take2
-                    if take1_status.as_ref().is_ok_and(|status| status.confirmed)
-                        || take2_status.as_ref().is_ok_and(|status| status.confirmed)
-                    {
-                        return PegOutOperatorStatus::PegOutComplete;
-                    }
+        }
 
-                    // check burn and disprove
-                    if burn_status.as_ref().is_ok_and(|status| status.confirmed)
-                        || disprove_status
-                            .as_ref()
-                            .is_ok_and(|status| status.confirmed)
-                    {
-                        return PegOutOperatorStatus::PegOutFailed; // TODO: can be also `PegOutOperatorStatus::PegOutComplete`
-                    }
+        Ok(())
+    }
+
+    pub fn peg_in_graph_id(&self) -> &String { &self.peg_in_graph_id }
+
+    pub fn operator_public_key(&self) -> &PublicKey { &self.operator_public_key }
+
+    pub fn parameters(&self) -> &BridgeParameters { &self.parameters }
+
+    pub fn challenge_crowdfunding_amount(&self) -> Amount {
+        self.challenge_transaction.input_amount_crowdfunding()
+    }
+
+    pub fn verify(
+        &self,
+        peg_in_graph: &PegInGraph,
+        n_of_n_taproot_public_key: &XOnlyPublicKey,
+    ) -> Result<(), BridgeError> {
+        verify_version("Peg-out", &self.id, &self.version)?;
+        if self.id != generate_id(peg_in_graph, &self.operator_public_key) {
+            return Err(BridgeError::InvalidGraph(format!(
+                "Peg-out graph {} has an invalid id",
+                self.id
+            )));
+        }
 
-                    if challenge_status.is_ok_and(|status| status.confirmed) {
-                        if assert_status.as_ref().is_ok_and(|status| status.confirmed) {
-                            if assert_status.as_ref().unwrap().block_height.is_some_and(
-                                |block_height| {
-                                    block_height + NUM_BLOCKS_PER_2_WEEKS <= blockchain_height
-                                },
-                            ) {
-                                return PegOutOperatorStatus::PegOutTake2Available;
-                            } else {
-                                return PegOutOperatorStatus::PegOutWait;
-                            }
-                        } else {
-                            return PegOutOperatorStatus::PegOutAssertAvailable;
-                        }
-                    } else {
-                        if kick_off_status.as_ref().unwrap().block_height.is_some_and(
-                            |block_height| {
-                                block_height + NUM_BLOCKS_PER_2_WEEKS <= blockchain_height
-                            },
-                        ) {
-                            return PegOutOperatorStatus::PegOutTake1Available;
-                        } else {
-                            return PegOutOperatorStatus::PegOutWait;
-                        }
+        let peg_in_confirm_tx = peg_in_graph.peg_in_confirm_transaction_ref().tx();
+        if self.network != peg_in_graph.network()
+            || self.peg_in_graph_id != *peg_in_graph.id()
+            || self.peg_in_confirm_txid != peg_in_confirm_tx.compute_txid()
+        {
+            return Err(BridgeError::InvalidGraph(format!(
+                "Peg-out graph {} does not belong to peg-in graph {}",
+                self.id,
+                peg_in_graph.id()
+            )));
+        }
+
+        if self.operator_commit_y_public_key.len() != N as usize {
+            return Err(BridgeError::InvalidGraph(format!(
+                "Peg-out graph {} has an invalid commit y public key",
+                self.id
+            )));
+        }
+        let connector_y = ConnectorY::new(
+            self.network,
+            &self.operator_taproot_public_key,
+            &self.operator_commit_y_public_key,
+        );
+        let connector_1 = Connector1::new(
+            self.network,
+            &self.operator_public_key,
+            self.parameters.take1_timelock,
+        );
+        let connector_2 = Connector2::new(
+            self.network,
+            &self.operator_public_key,
+            self.parameters.take2_timelock,
+        );
+        let connector_3 = Connector3::new(self.network, n_of_n_taproot_public_key);
+        let connector_a = ConnectorA::new(
+            self.network,
+            &self.operator_taproot_public_key,
+            n_of_n_taproot_public_key,
+        );
+        let connector_b = ConnectorB::new(
+            self.network,
+            n_of_n_taproot_public_key,
+            self.parameters.burn_timelock,
+        );
+        let connector_c = ConnectorC::new(self.network, n_of_n_taproot_public_key);
+        let operator_script_pubkey =
+            generate_pay_to_pubkey_script_address(self.network, &self.operator_public_key)
+                .script_pubkey();
+
+        let kick_off_tx = self.kick_off_transaction.tx();
+        verify_prev_out_script_pubkey(
+            "Kick-off",
+            &self.kick_off_transaction,
+            0,
+            &connector_y.generate_taproot_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Kick-off",
+            kick_off_tx,
+            0,
+            &connector_1.generate_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Kick-off",
+            kick_off_tx,
+            1,
+            &connector_a.generate_taproot_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Kick-off",
+            kick_off_tx,
+            2,
+            &connector_b.generate_taproot_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Kick-off",
+            kick_off_tx,
+            self.kick_off_transaction.anchor_vout(),
+            &operator_script_pubkey,
+        )?;
+
+        let take1_transaction = &self.take1_transaction;
+        verify_input("Take1", take1_transaction, 0, peg_in_confirm_tx, 0)?;
+        verify_input("Take1", take1_transaction, 1, kick_off_tx, 0)?;
+        verify_input("Take1", take1_transaction, 2, kick_off_tx, 1)?;
+        verify_input("Take1", take1_transaction, 3, kick_off_tx, 2)?;
+        verify_output_script_pubkey("Take1", take1_transaction.tx(), 0, &operator_script_pubkey)?;
+
+        verify_input("Challenge", &self.challenge_transaction, 0, kick_off_tx, 1)?;
+
+        let assert_tx = self.assert_transaction.tx();
+        verify_input("Assert", &self.assert_transaction, 0, kick_off_tx, 2)?;
+        verify_output_script_pubkey(
+            "Assert",
+            assert_tx,
+            0,
+            &connector_2.generate_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Assert",
+            assert_tx,
+            1,
+            &connector_3.generate_taproot_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Assert",
+            assert_tx,
+            2,
+            &connector_c.generate_taproot_address().script_pubkey(),
+        )?;
+        verify_output_script_pubkey(
+            "Assert",
+            assert_tx,
+            self.assert_transaction.anchor_vout(),
+            &operator_script_pubkey,
+        )?;
+
+        let take2_transaction = &self.take2_transaction;
+        verify_input("Take2", take2_transaction, 0, peg_in_confirm_tx, 0)?;
+        verify_input("Take2", take2_transaction, 1, assert_tx, 0)?;
+        verify_input("Take2", take2_transaction, 2, assert_tx, 1)?;
+        verify_output_script_pubkey("Take2", take2_transaction.tx(), 0, &operator_script_pubkey)?;
+
+        verify_input("Disprove", &self.disprove_transaction, 0, assert_tx, 1)?;
+        verify_input("Disprove", &self.disprove_transaction, 1, assert_tx, 2)?;
+
+        verify_input("Burn", &self.burn_transaction, 0, kick_off_tx, 2)?;
+
+        verify_witnesses("Kick-off", &self.kick_off_transaction)?;
+        verify_witnesses("Take1", &self.take1_transaction)?;
+        verify_witnesses("Challenge", &self.challenge_transaction)?;
+        verify_witnesses("Assert", &self.assert_transaction)?;
+        verify_witnesses("Take2", &self.take2_transaction)?;
+        verify_witnesses("Disprove", &self.disprove_transaction)?;
+        verify_witnesses("Burn", &self.burn_transaction)?;
+        if self.peg_out_transaction.is_some() {
+            if self.withdrawer_public_key.is_none() {
+                return Err(BridgeError::InvalidGraph(format!(
+                    "Peg-out graph {} pays a peg-out that was not requested",
+                    self.id
+                )));
+            }
+            let peg_out_transaction = self.peg_out_transaction.as_ref().unwrap();
+            verify_output_script_pubkey(
+                "Peg-out",
+                peg_out_transaction.tx(),
+                0,
+                &self
+                    .withdrawer_destination_script_pubkey
+                    .clone()
+                    .unwrap_or_default(),
+            )?;
+            verify_witnesses("Peg-out", peg_out_transaction)?;
+        }
+
+        Ok(())
+    }
+
+    // Derives the state of the graph from the chain together with every action available in it,
+    // whoever can take it. Actions spending the same output can be available at the same time,
+    // take 1 and challenge spend connector A and take 1 and burn connector B. Whichever is
+    // confirmed first decides the state.
+    pub async fn state(&self, client: &dyn ChainBackend) -> Result<PegOutGraphState, BridgeError> {
+        if self.n_of_n_pre_signing_state != PreSigningState::Presigned {
+            return Ok(PegOutGraphState {
+                state: PegOutState::PreSigning,
+                available_actions: vec![PegOutAction::PreSign],
+            });
+        }
+
+        let (
+            peg_in_confirm_status,
+            kick_off_status,
+            challenge_status,
+            assert_status,
+            disprove_status,
+            burn_status,
+            take1_status,
+            take2_status,
+            peg_out_status,
+        ) = Self::get_peg_out_statuses(self, client).await;
+        let blockchain_height = get_block_height(client).await?;
+
+        let is_confirmed = |status: &Result<TxStatus, ChainError>| {
+            status.as_ref().is_ok_and(|status| status.confirmed)
+        };
+        let is_timelock_elapsed = |status: &Result<TxStatus, ChainError>,
+                                   num_blocks_timelock: u32| {
+            status.as_ref().is_ok_and(|status| {
+                status.block_height.is_some_and(|block_height| {
+                    block_height + num_blocks_timelock <= blockchain_height
+                })
+            })
+        };
+
+        let state = if is_confirmed(&take1_status) {
+            PegOutState::CompletedByTake1
+        } else if is_confirmed(&take2_status) {
+            PegOutState::CompletedByTake2
+        } else if is_confirmed(&burn_status) {
+            PegOutState::FailedByBurn
+        } else if is_confirmed(&disprove_status) {
+            PegOutState::FailedByDisprove
+        } else if is_confirmed(&assert_status) {
+            PegOutState::Asserted
+        } else if is_confirmed(&challenge_status) {
+            PegOutState::Challenged
+        } else if is_confirmed(&kick_off_status) {
+            PegOutState::KickedOff
+        } else if peg_out_status.as_ref().is_some_and(is_confirmed) {
+            PegOutState::KickOffPending
+        } else {
+            PegOutState::PegOutPending
+        };
+
+        let mut available_actions = vec![];
+        match state {
+            PegOutState::PegOutPending => {
+                if self.withdrawer_public_key.is_some() {
+                    available_actions.push(PegOutAction::PegOut);
+                }
+            }
+            PegOutState::KickOffPending => available_actions.push(PegOutAction::KickOff),
+            PegOutState::KickedOff | PegOutState::Challenged => {
+                if state == PegOutState::KickedOff {
+                    // connector A is unspent
+                    available_actions.push(PegOutAction::Challenge);
+                    if is_confirmed(&peg_in_confirm_status)
+                        && is_timelock_elapsed(&kick_off_status, self.parameters.take1_timelock)
+                    {
+                        available_actions.push(PegOutAction::Take1);
                     }
                 } else {
-                    return PegOutOperatorStatus::PegOutKickOffAvailable;
+                    available_actions.push(PegOutAction::Assert);
+                }
+                // connector B is unspent
+                if is_timelock_elapsed(&kick_off_status, self.parameters.burn_timelock) {
+                    available_actions.push(PegOutAction::Burn);
                 }
-            } else {
-                return PegOutOperatorStatus::PegOutStartPegOut;
             }
-        } else {
-            return PegOutOperatorStatus::PegOutWait;
+            PegOutState::Asserted => {
+                // connector 3 is unspent
+                available_actions.push(PegOutAction::Disprove);
+                if is_confirmed(&peg_in_confirm_status)
+                    && is_timelock_elapsed(&assert_status, self.parameters.take2_timelock)
+                {
+                    available_actions.push(PegOutAction::Take2);
+                }
+            }
+            _ => {}
         }
+
+        Ok(PegOutGraphState {
+            state,
+            available_actions,
+        })
     }
 
-    pub async fn depositor_status(&self, client: &AsyncClient) -> PegOutDepositorStatus {
+    pub async fn verifier_status(
+        &self,
+        client: &dyn ChainBackend,
+    ) -> Result<PegOutVerifierStatus, BridgeError> {
+        let graph_state = self.state(client).await?;
+
+        // burning ends the graph, so it takes precedence over challenging
+        Ok(match graph_state.state {
+            PegOutState::PreSigning => PegOutVerifierStatus::PegOutPresign,
+            PegOutState::CompletedByTake1 => PegOutVerifierStatus::PegOutCompletedByTake1,
+            PegOutState::CompletedByTake2 => PegOutVerifierStatus::PegOutCompletedByTake2,
+            PegOutState::FailedByBurn => PegOutVerifierStatus::PegOutFailedByBurn,
+            PegOutState::FailedByDisprove => PegOutVerifierStatus::PegOutFailedByDisprove,
+            _ if graph_state.is_available(PegOutAction::Burn) => {
+                PegOutVerifierStatus::PegOutBurnAvailable
+            }
+            _ if graph_state.is_available(PegOutAction::Disprove) => {
+                PegOutVerifierStatus::PegOutDisproveAvailable
+            }
+            _ if graph_state.is_available(PegOutAction::Challenge) => {
+                PegOutVerifierStatus::PegOutChallengeAvailabe
+            }
+            _ => PegOutVerifierStatus::PegOutWait,
+        })
+    }
+
+    pub async fn operator_status(
+        &self,
+        client: &dyn ChainBackend,
+    ) -> Result<PegOutOperatorStatus, BridgeError> {
+        let graph_state = self.state(client).await?;
+
+        // the operator's actions never compete with each other
+        Ok(match graph_state.state {
+            PegOutState::CompletedByTake1 => PegOutOperatorStatus::PegOutCompletedByTake1,
+            PegOutState::CompletedByTake2 => PegOutOperatorStatus::PegOutCompletedByTake2,
+            PegOutState::FailedByBurn => PegOutOperatorStatus::PegOutFailedByBurn,
+            PegOutState::FailedByDisprove => PegOutOperatorStatus::PegOutFailedByDisprove,
+            _ => graph_state.actions_of(GraphRole::Operator).first().map_or(
+                PegOutOperatorStatus::PegOutWait,
+                |action| match action {
+                    PegOutAction::PegOut => PegOutOperatorStatus::PegOutStartPegOut,
+                    PegOutAction::KickOff => PegOutOperatorStatus::PegOutKickOffAvailable,
+                    PegOutAction::Assert => PegOutOperatorStatus::PegOutAssertAvailable,
+                    PegOutAction::Take1 => PegOutOperatorStatus::PegOutTake1Available,
+                    PegOutAction::Take2 => PegOutOperatorStatus::PegOutTake2Available,
+                    _ => PegOutOperatorStatus::PegOutWait,
+                },
+            ),
+        })
+    }
+
+    pub async fn depositor_status(&self, client: &dyn ChainBackend) -> PegOutDepositorStatus {
         if self.peg_out_transaction.is_some() {
             let peg_out_txid = self
                 .peg_out_transaction
@@ -456,120 +1064,454 @@ impl PegOutGraph {
         }
     }
 
-    pub async fn kick_off(&mut self, client: &AsyncClient) {
-        verify_if_not_mined(&client, self.kick_off_transaction.tx().compute_txid()).await;
+    pub async fn withdrawer_status(&self, client: &dyn ChainBackend) -> PegOutWithdrawerStatus {
+        if self.withdrawer_public_key.is_none() {
+            return PegOutWithdrawerStatus::PegOutNotRequested;
+        }
+
+        match self.depositor_status(client).await {
+            PegOutDepositorStatus::PegOutNotStarted => PegOutWithdrawerStatus::PegOutRequested,
+            PegOutDepositorStatus::PegOutWait => PegOutWithdrawerStatus::PegOutWait,
+            PegOutDepositorStatus::PegOutComplete => PegOutWithdrawerStatus::PegOutComplete,
+        }
+    }
+
+    // Status of the graph for the role, together with the transactions, the timelocks and the
+    // action the role is expected to take next.
+    pub async fn status_report(
+        &self,
+        client: &dyn ChainBackend,
+        role: GraphRole,
+    ) -> GraphStatusReport {
+        let mut report = GraphStatusReport::new(&self.id, role);
+        report.block_height = get_block_height(client).await.ok();
+
+        let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
+        let take1_txid = self.take1_transaction.tx().compute_txid();
+        let assert_txid = self.assert_transaction.tx().compute_txid();
+        let take2_txid = self.take2_transaction.tx().compute_txid();
+
+        // the challenge, disprove and burn on chain, see get_peg_out_statuses, or their templates
+        let challenge_txid = get_other_spending_txid(
+            client,
+            OutPoint {
+                txid: kick_off_txid,
+                vout: 1,
+            },
+            &[take1_txid],
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(self.challenge_transaction.tx().compute_txid());
+        let disprove_txid = get_other_spending_txid(
+            client,
+            OutPoint {
+                txid: assert_txid,
+                vout: 1,
+            },
+            &[take2_txid],
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(self.disprove_transaction.tx().compute_txid());
+        let burn_txid = get_other_spending_txid(
+            client,
+            OutPoint {
+                txid: kick_off_txid,
+                vout: 2,
+            },
+            &[take1_txid, assert_txid],
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(self.burn_transaction.tx().compute_txid());
+
+        let mut transactions = vec![
+            ("Kick-off", kick_off_txid),
+            ("Take1", take1_txid),
+            ("Challenge", challenge_txid),
+            ("Assert", assert_txid),
+            ("Take2", take2_txid),
+            ("Disprove", disprove_txid),
+            ("Burn", burn_txid),
+        ];
+        if self.peg_out_transaction.is_some() {
+            transactions.push((
+                "Peg-out",
+                self.peg_out_transaction
+                    .as_ref()
+                    .unwrap()
+                    .tx()
+                    .compute_txid(),
+            ));
+        }
+        for (name, txid) in transactions {
+            report
+                .transactions
+                .push(get_transaction_status_report(client, name, txid).await);
+        }
+        report.add_timelock("Take1", "Kick-off", self.parameters.take1_timelock);
+        report.add_timelock("Burn", "Kick-off", self.parameters.burn_timelock);
+        report.add_timelock("Take2", "Assert", self.parameters.take2_timelock);
+
+        match role {
+            GraphRole::Depositor => report.set_status(GraphStatus::PegOutDepositor(
+                self.depositor_status(client).await,
+            )),
+            GraphRole::Operator => match self.operator_status(client).await {
+                Ok(status) => report.set_status(GraphStatus::PegOutOperator(status)),
+                Err(err) => report.set_error(err),
+            },
+            GraphRole::Verifier => match self.verifier_status(client).await {
+                Ok(status) => report.set_status(GraphStatus::PegOutVerifier(status)),
+                Err(err) => report.set_error(err),
+            },
+            GraphRole::Withdrawer => report.set_status(GraphStatus::PegOutWithdrawer(
+                self.withdrawer_status(client).await,
+            )),
+        }
+        if let Ok(graph_state) = self.state(client).await {
+            report.available_actions = graph_state
+                .actions_of(role)
+                .iter()
+                .map(|action| action.to_string())
+                .collect();
+        }
+
+        report
+    }
+
+    pub fn peg_out_request(&self) -> Option<PegOutRequest> {
+        if self.withdrawer_public_key.is_none() {
+            return None;
+        }
+
+        Some(PegOutRequest {
+            peg_in_graph_id: self.peg_in_graph_id.clone(),
+            withdrawer_public_key: self.withdrawer_public_key.unwrap(),
+            withdrawer_evm_address: self.withdrawer_evm_address.clone().unwrap_or_default(),
+            evm_burn_tx_hash: self.withdrawer_evm_burn_tx_hash.clone().unwrap_or_default(),
+            destination_script_pubkey: self
+                .withdrawer_destination_script_pubkey
+                .clone()
+                .unwrap_or_default(),
+        })
+    }
+
+    // Registers the withdrawer the operator has to pay. A graph pays a single withdrawer, the same
+    // request can be registered again.
+    pub fn request_peg_out(&mut self, request: &PegOutRequest) -> Result<(), BridgeError> {
+        if request.peg_in_graph_id != self.peg_in_graph_id {
+            return Err(BridgeError::PegOut(format!(
+                "Peg-out graph {} does not belong to peg-in graph {}",
+                self.id, request.peg_in_graph_id
+            )));
+        }
+        if let Some(registered_request) = self.peg_out_request() {
+            if registered_request == *request {
+                return Ok(());
+            }
+            return Err(BridgeError::PegOut(format!(
+                "Peg-out graph {} has already been requested by another withdrawer",
+                self.id
+            )));
+        }
+        if Address::from_script(&request.destination_script_pubkey, self.network).is_err() {
+            return Err(BridgeError::PegOut(format!(
+                "Peg-out destination {} is not a standard output script",
+                request.destination_script_pubkey
+            )));
+        }
+
+        self.withdrawer_public_key = Some(request.withdrawer_public_key);
+        self.withdrawer_taproot_public_key =
+            Some(request.withdrawer_public_key.inner.x_only_public_key().0);
+        self.withdrawer_evm_address = Some(request.withdrawer_evm_address.clone());
+        self.withdrawer_evm_burn_tx_hash = Some(request.evm_burn_tx_hash.clone());
+        self.withdrawer_destination_script_pubkey = Some(request.destination_script_pubkey.clone());
+
+        Ok(())
+    }
+
+    pub fn peg_out_transaction_ref(&self) -> Option<&PegOutTransaction> {
+        self.peg_out_transaction.as_ref()
+    }
+
+    // The operator pays the requested peg-out from the input, which must cover the peg-in amount.
+    // The peg-out is recorded in the graph, as the kick-off commits to it, and can be broadcast
+    // again with the same input as long as it is not mined.
+    pub async fn peg_out(
+        &mut self,
+        client: &dyn ChainBackend,
+        context: &OperatorContext,
+        input: Input,
+    ) -> Result<(), BridgeError> {
+        if self.withdrawer_destination_script_pubkey.is_none() {
+            return Err(BridgeError::PegOut(format!(
+                "No peg-out requested for peg-out graph {}",
+                self.id
+            )));
+        }
+        if context.operator_public_key != self.operator_public_key {
+            return Err(BridgeError::PegOut(format!(
+                "Peg-out graph {} belongs to another operator",
+                self.id
+            )));
+        }
+        // the operator is only reimbursed once the graph is pre-signed and the peg-in confirmed
+        if self.n_of_n_pre_signing_state != PreSigningState::Presigned {
+            return Err(BridgeError::PreSigning(format!(
+                "Peg-out graph {} has not been pre-signed by every verifier",
+                self.id
+            )));
+        }
+        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await?;
+        if !peg_in_confirm_status.confirmed {
+            return Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Peg-in confirm",
+            )));
+        }
+
+        match self.peg_out_transaction.as_ref() {
+            Some(peg_out_transaction) => {
+                if peg_out_transaction.tx().input[0].previous_output != input.outpoint {
+                    return Err(BridgeError::PegOut(format!(
+                        "Peg-out graph {} already pays the peg-out from another input",
+                        self.id
+                    )));
+                }
+            }
+            None => {
+                let peg_in_amount = self.take1_transaction.prev_outs()[0].value;
+                if input.amount < peg_in_amount {
+                    return Err(BridgeError::PegOut(format!(
+                        "Input of {} does not cover the peg-in amount of {}",
+                        input.amount, peg_in_amount
+                    )));
+                }
+
+                self.peg_out_transaction = Some(PegOutTransaction::new(
+                    context,
+                    self.withdrawer_destination_script_pubkey.as_ref().unwrap(),
+                    input,
+                ));
+            }
+        }
+
+        let peg_out_transaction = self.peg_out_transaction.as_ref().unwrap();
+        verify_if_not_mined(client, peg_out_transaction.tx().compute_txid()).await?;
+
+        // complete peg-out tx
+        let peg_out_tx = peg_out_transaction.finalize();
+
+        // broadcast peg-out tx
+        let peg_out_result = client.broadcast(&peg_out_tx).await;
+
+        // verify peg-out tx result
+        verify_tx_result(peg_out_result)
+    }
+
+    pub fn kick_off_transaction_ref(&self) -> &KickOffTransaction { &self.kick_off_transaction }
+
+    // The operator's commitment to the claim y, see commitments::sign_commit_y with the id of this
+    // graph and peg_out_claim.
+    pub async fn kick_off(
+        &mut self,
+        client: &dyn ChainBackend,
+        commit_y: &WinternitzSignature,
+    ) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.kick_off_transaction.tx().compute_txid()).await?;
 
         // complete kick_off tx
+        self.kick_off_transaction.add_commit_y(commit_y)?;
         let kick_off_tx = self.kick_off_transaction.finalize();
 
         // broadcast kick_off tx
         let kick_off_result = client.broadcast(&kick_off_tx).await;
 
         // verify kick_off tx result
-        verify_tx_result(&kick_off_result);
+        verify_tx_result(kick_off_result)
+    }
+
+    // The claim y an honest operator commits to in the kick-off, once the peg-out is known.
+    pub fn peg_out_claim(&self) -> Option<CommitmentValue> {
+        self.peg_out_transaction
+            .as_ref()
+            .map(|peg_out_transaction| {
+                generate_peg_out_claim(&peg_out_transaction.tx().compute_txid())
+            })
+    }
+
+    // Reads the claim y from the kick-off tx on chain.
+    pub async fn kick_off_claim(
+        &self,
+        client: &dyn ChainBackend,
+    ) -> Result<CommitmentValue, BridgeError> {
+        let kick_off_tx = client
+            .get_tx(&self.kick_off_transaction.tx().compute_txid())
+            .await?
+            .ok_or(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )))?;
+
+        extract_commit_y(&kick_off_tx, &self.operator_commit_y_public_key)
+    }
+
+    // A kick-off whose claim does not commit to the peg-out of this graph should be challenged.
+    // The kick-off script only checks the commitment is signed with the key of this graph, any y
+    // is accepted on chain, so every verifier has to run this check off-chain before challenging.
+    // Checking y on chain in the disprove path needs it as a public input of the chunked Groth16
+    // verifier, which the disprove leaves of ConnectorC do not implement yet.
+    pub async fn is_kick_off_claim_valid(
+        &self,
+        client: &dyn ChainBackend,
+    ) -> Result<bool, BridgeError> {
+        let claim = self.kick_off_claim(client).await?;
+
+        Ok(self.peg_out_claim() == Some(claim))
+    }
+
+    // Challengers crowdfund the challenge with the PSBT API of the challenge transaction, see
+    // ChallengeTransaction::crowdfunding_psbt.
+    pub fn challenge_transaction_ref(&self) -> &ChallengeTransaction { &self.challenge_transaction }
+
+    // Broadcasts the challenge crowdfunded by several challengers.
+    pub async fn challenge_with_crowdfunding_psbt(
+        &mut self,
+        client: &dyn ChainBackend,
+        psbt: &Psbt,
+    ) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.challenge_transaction.tx().compute_txid()).await?;
+
+        let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await?;
+
+        if kick_off_status.confirmed {
+            // complete challenge tx
+            self.challenge_transaction
+                .finalize_crowdfunding_psbt(psbt)?;
+            let challenge_tx = self.challenge_transaction.finalize();
+
+            // broadcast challenge tx
+            let challenge_result = client.broadcast(&challenge_tx).await;
+
+            // verify challenge tx result
+            verify_tx_result(challenge_result)
+        } else {
+            Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )))
+        }
     }
 
     pub async fn challenge(
         &mut self,
-        client: &AsyncClient,
+        client: &dyn ChainBackend,
         context: &dyn BaseContext,
         crowdfundng_inputs: &Vec<InputWithScript<'_>>,
         keypair: &Keypair,
         output_script_pubkey: ScriptBuf,
-    ) {
-        verify_if_not_mined(client, self.challenge_transaction.tx().compute_txid()).await;
+    ) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.challenge_transaction.tx().compute_txid()).await?;
 
         let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
-        let kick_off_status = client.get_tx_status(&kick_off_txid).await;
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await?;
 
-        if kick_off_status.is_ok_and(|status| status.confirmed) {
+        if kick_off_status.confirmed {
             // complete challenge tx
             self.challenge_transaction.add_inputs_and_output(
                 context,
                 crowdfundng_inputs,
                 keypair,
                 output_script_pubkey,
-            );
+            )?;
             let challenge_tx = self.challenge_transaction.finalize();
 
             // broadcast challenge tx
             let challenge_result = client.broadcast(&challenge_tx).await;
 
             // verify challenge tx result
-            verify_tx_result(&challenge_result);
+            verify_tx_result(challenge_result)
         } else {
-            panic!("Kick-off tx has not been yet confirmed!");
+            Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )))
         }
     }
 
-    pub async fn assert(&mut self, client: &AsyncClient) {
-        verify_if_not_mined(client, self.assert_transaction.tx().compute_txid()).await;
+    pub async fn assert(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.assert_transaction.tx().compute_txid()).await?;
 
         let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
-        let kick_off_status = client.get_tx_status(&kick_off_txid).await;
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await?;
 
-        if kick_off_status.is_ok_and(|status| status.confirmed) {
+        if kick_off_status.confirmed {
             // complete assert tx
-            // TODO: commit ZK computation result
+            // The assert carries no commitments to the intermediate values of the Groth16
+            // verifier, there is no chunked verifier to commit to yet. Until then the assert only
+            // moves the funds on to take 2 and the disprove leaves of ConnectorC.
             let assert_tx = self.assert_transaction.finalize();
 
             // broadcast assert tx
             let assert_result = client.broadcast(&assert_tx).await;
 
             // verify assert tx result
-            verify_tx_result(&assert_result);
+            verify_tx_result(assert_result)
         } else {
-            panic!("Kick-off tx has not been yet confirmed!");
+            Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )))
         }
     }
 
     pub async fn disprove(
         &mut self,
-        client: &AsyncClient,
+        client: &dyn ChainBackend,
         input_script_index: u32,
         output_script_pubkey: ScriptBuf,
-    ) {
-        verify_if_not_mined(client, self.disprove_transaction.tx().compute_txid()).await;
+    ) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.disprove_transaction.tx().compute_txid()).await?;
 
         let assert_txid = self.assert_transaction.tx().compute_txid();
-        let assert_status = client.get_tx_status(&assert_txid).await;
+        let assert_status = client.get_tx_status(&assert_txid).await?;
 
-        if assert_status.is_ok_and(|status| status.confirmed) {
+        if assert_status.confirmed {
             // complete disprove tx
             self.disprove_transaction
-                .add_input_output(input_script_index, output_script_pubkey);
+                .add_input_output(input_script_index, output_script_pubkey)?;
             let disprove_tx = self.disprove_transaction.finalize();
 
             // broadcast disprove tx
             let disprove_result = client.broadcast(&disprove_tx).await;
 
             // verify disprove tx result
-            verify_tx_result(&disprove_result);
+            verify_tx_result(disprove_result)
         } else {
-            panic!("Assert tx has not been yet confirmed!");
+            Err(BridgeError::TransactionNotConfirmed(String::from("Assert")))
         }
     }
 
-    pub async fn burn(&mut self, client: &AsyncClient, output_script_pubkey: ScriptBuf) {
-        verify_if_not_mined(client, self.burn_transaction.tx().compute_txid()).await;
+    pub fn burn_transaction_ref(&self) -> &BurnTransaction { &self.burn_transaction }
+
+    pub async fn burn(
+        &mut self,
+        client: &dyn ChainBackend,
+        output_script_pubkey: ScriptBuf,
+    ) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.burn_transaction.tx().compute_txid()).await?;
 
         let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
-        let kick_off_status = client.get_tx_status(&kick_off_txid).await;
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await?;
 
-        let blockchain_height = get_block_height(client).await;
+        let blockchain_height = get_block_height(client).await?;
 
-        if kick_off_status
-            .as_ref()
-            .is_ok_and(|status| status.confirmed)
-        {
-            if kick_off_status
-                .as_ref()
-                .unwrap()
-                .block_height
-                .is_some_and(|block_height| {
-                    block_height + NUM_BLOCKS_PER_4_WEEKS <= blockchain_height
-                })
-            {
+        if kick_off_status.confirmed {
+            if kick_off_status.block_height.is_some_and(|block_height| {
+                block_height + self.parameters.burn_timelock <= blockchain_height
+            }) {
                 // complete burn tx
                 self.burn_transaction.add_output(output_script_pubkey);
                 let burn_tx = self.burn_transaction.finalize();
@@ -578,138 +1520,306 @@ impl PegOutGraph {
                 let burn_result = client.broadcast(&burn_tx).await;
 
                 // verify burn tx result
-                verify_tx_result(&burn_result);
+                verify_tx_result(burn_result)
             } else {
-                panic!("Kick-off timelock has not yet elapsed!");
+                Err(BridgeError::TimelockNotElapsed(String::from("Kick-off")))
             }
         } else {
-            panic!("Kick-off tx has not been yet confirmed!");
+            Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )))
         }
     }
 
-    pub async fn take1(&mut self, client: &AsyncClient) {
-        verify_if_not_mined(&client, self.take1_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.challenge_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.assert_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.burn_transaction.tx().compute_txid()).await;
+    pub async fn take1(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.take1_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.challenge_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.assert_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.burn_transaction.tx().compute_txid()).await?;
 
-        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await;
+        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await?;
         let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
-        let kick_off_status = client.get_tx_status(&kick_off_txid).await;
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await?;
 
-        let blockchain_height = get_block_height(client).await;
+        let blockchain_height = get_block_height(client).await?;
 
-        if peg_in_confirm_status.is_ok_and(|status| status.confirmed)
-            && kick_off_status
-                .as_ref()
-                .is_ok_and(|status| status.confirmed)
-        {
-            if kick_off_status
-                .unwrap()
-                .block_height
-                .is_some_and(|block_height| {
-                    block_height + NUM_BLOCKS_PER_2_WEEKS <= blockchain_height
-                })
-            {
-                // complete take1 tx
-                let take1_tx = self.take1_transaction.finalize();
+        if !peg_in_confirm_status.confirmed {
+            return Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Peg-in confirm",
+            )));
+        }
+        if !kick_off_status.confirmed {
+            return Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Kick-off",
+            )));
+        }
 
-                // broadcast take1 tx
-                let take1_result = client.broadcast(&take1_tx).await;
+        if kick_off_status.block_height.is_some_and(|block_height| {
+            block_height + self.parameters.take1_timelock <= blockchain_height
+        }) {
+            // complete take1 tx
+            let take1_tx = self.take1_transaction.finalize();
 
-                // verify take1 tx result
-                verify_tx_result(&take1_result);
-            } else {
-                panic!("Kick-off tx timelock has not yet elapsed!");
-            }
+            // broadcast take1 tx
+            let take1_result = client.broadcast(&take1_tx).await;
+
+            // verify take1 tx result
+            verify_tx_result(take1_result)
         } else {
-            panic!("Neither peg-in confirm tx nor kick-off tx has not been yet confirmed!");
+            Err(BridgeError::TimelockNotElapsed(String::from("Kick-off")))
         }
     }
 
-    pub async fn take2(&mut self, client: &AsyncClient) {
-        verify_if_not_mined(&client, self.take2_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.take1_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.disprove_transaction.tx().compute_txid()).await;
-        verify_if_not_mined(&client, self.burn_transaction.tx().compute_txid()).await;
+    pub async fn take2(&mut self, client: &dyn ChainBackend) -> Result<(), BridgeError> {
+        verify_if_not_mined(client, self.take2_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.take1_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.disprove_transaction.tx().compute_txid()).await?;
+        verify_if_not_mined(client, self.burn_transaction.tx().compute_txid()).await?;
 
-        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await;
+        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await?;
         let assert_txid = self.assert_transaction.tx().compute_txid();
-        let assert_status = client.get_tx_status(&assert_txid).await;
+        let assert_status = client.get_tx_status(&assert_txid).await?;
 
-        let blockchain_height = get_block_height(client).await;
+        let blockchain_height = get_block_height(client).await?;
 
-        if peg_in_confirm_status.is_ok_and(|status| status.confirmed)
-            && assert_status.as_ref().is_ok_and(|status| status.confirmed)
-        {
-            if assert_status
-                .unwrap()
-                .block_height
-                .is_some_and(|block_height| {
-                    block_height + NUM_BLOCKS_PER_2_WEEKS <= blockchain_height
-                })
-            {
-                // complete take2 tx
-                let take2_tx = self.take2_transaction.finalize();
+        if !peg_in_confirm_status.confirmed {
+            return Err(BridgeError::TransactionNotConfirmed(String::from(
+                "Peg-in confirm",
+            )));
+        }
+        if !assert_status.confirmed {
+            return Err(BridgeError::TransactionNotConfirmed(String::from("Assert")));
+        }
 
-                // broadcast take2 tx
-                let take2_result = client.broadcast(&take2_tx).await;
+        if assert_status.block_height.is_some_and(|block_height| {
+            block_height + self.parameters.take2_timelock <= blockchain_height
+        }) {
+            // complete take2 tx
+            let take2_tx = self.take2_transaction.finalize();
 
-                // verify take2 tx result
-                verify_tx_result(&take2_result);
-            } else {
-                panic!("Assert tx timelock has not yet elapsed!");
-            }
+            // broadcast take2 tx
+            let take2_result = client.broadcast(&take2_tx).await;
+
+            // verify take2 tx result
+            verify_tx_result(take2_result)
         } else {
-            panic!("Neither peg-in confirm tx nor assert tx has not been yet confirmed!");
+            Err(BridgeError::TimelockNotElapsed(String::from("Assert")))
+        }
+    }
+
+    // Pays for a kick-off, assert, take 1 or take 2 tx of the graph stuck in the mempool with a
+    // child spending the operator's output of it. Returns the txid of the child.
+    pub async fn bump_fee(
+        &self,
+        client: &dyn ChainBackend,
+        context: &OperatorContext,
+        txid: &Txid,
+        funding_inputs: &Vec<Input>,
+        fee_rate: FeeRate,
+    ) -> Result<Txid, BridgeError> {
+        let (name, parent_fee, parent_vout) =
+            self.operator_output(txid)
+                .ok_or(BridgeError::FeeBumping(format!(
+                    "Transaction {} has no output of the operator",
+                    txid
+                )))?;
+        verify_if_not_mined(client, *txid).await?;
+        let parent_tx = client
+            .get_tx(txid)
+            .await?
+            .ok_or(BridgeError::FeeBumping(format!(
+                "{} tx has not been broadcast",
+                name
+            )))?;
+
+        // complete cpfp tx
+        let cpfp_tx = CpfpTransaction::new(
+            context,
+            &parent_tx,
+            parent_fee,
+            parent_vout,
+            funding_inputs,
+            &context.operator_keypair,
+            fee_rate,
+        )?
+        .finalize();
+
+        // broadcast cpfp tx
+        let cpfp_result = client.broadcast(&cpfp_tx).await;
+
+        // verify cpfp tx result
+        verify_tx_result(cpfp_result)?;
+
+        Ok(cpfp_tx.compute_txid())
+    }
+
+    // Pays for a burn or disprove tx of the graph stuck in the mempool with a child spending its
+    // reward output, which has to pay to the pay-to-pubkey script of the keypair. The reward
+    // output is set by whoever broadcast the tx, so it is looked up in the broadcast tx. Returns
+    // the txid of the child.
+    pub async fn bump_reward_fee(
+        &self,
+        client: &dyn ChainBackend,
+        context: &dyn BaseContext,
+        txid: &Txid,
+        funding_inputs: &Vec<Input>,
+        keypair: &Keypair,
+        fee_rate: FeeRate,
+    ) -> Result<Txid, BridgeError> {
+        verify_if_not_mined(client, *txid).await?;
+        let parent_tx = client
+            .get_tx(txid)
+            .await?
+            .ok_or(BridgeError::FeeBumping(format!(
+                "Transaction {} has not been broadcast",
+                txid
+            )))?;
+        let (parent_fee, parent_vout) =
+            self.reward_output(&parent_tx)
+                .ok_or(BridgeError::FeeBumping(format!(
+                    "Transaction {} is neither the burn nor the disprove tx of the graph",
+                    txid
+                )))?;
+
+        // complete cpfp tx
+        let cpfp_tx = CpfpTransaction::new(
+            context,
+            &parent_tx,
+            parent_fee,
+            parent_vout,
+            funding_inputs,
+            keypair,
+            fee_rate,
+        )?
+        .finalize();
+
+        // broadcast cpfp tx
+        let cpfp_result = client.broadcast(&cpfp_tx).await;
+
+        // verify cpfp tx result
+        verify_tx_result(cpfp_result)?;
+
+        Ok(cpfp_tx.compute_txid())
+    }
+
+    // Name, fee and operator output of the transactions the operator can bump.
+    fn operator_output(&self, txid: &Txid) -> Option<(&str, Amount, u32)> {
+        if *txid == self.kick_off_transaction.tx().compute_txid() {
+            Some((
+                "Kick-off",
+                get_fee(&self.kick_off_transaction),
+                self.kick_off_transaction.anchor_vout().to_u32().unwrap(),
+            ))
+        } else if *txid == self.assert_transaction.tx().compute_txid() {
+            Some((
+                "Assert",
+                get_fee(&self.assert_transaction),
+                self.assert_transaction.anchor_vout().to_u32().unwrap(),
+            ))
+        } else if *txid == self.take1_transaction.tx().compute_txid() {
+            Some(("Take1", get_fee(&self.take1_transaction), 0))
+        } else if *txid == self.take2_transaction.tx().compute_txid() {
+            Some(("Take2", get_fee(&self.take2_transaction), 0))
+        } else {
+            None
+        }
+    }
+
+    // Fee and reward output of a burn or disprove tx of the graph, whichever script the reward
+    // output pays to. Both are identified by the output they spend.
+    fn reward_output(&self, tx: &Transaction) -> Option<(Amount, u32)> {
+        let spends = |template: &Transaction| {
+            tx.input.first().map(|input| input.previous_output)
+                == template.input.first().map(|input| input.previous_output)
+        };
+        if spends(self.burn_transaction.tx()) {
+            Some((
+                get_fee(&self.burn_transaction),
+                self.burn_transaction.reward_vout().to_u32().unwrap(),
+            ))
+        } else if spends(self.disprove_transaction.tx()) {
+            Some((
+                get_fee(&self.disprove_transaction),
+                self.disprove_transaction.reward_vout().to_u32().unwrap(),
+            ))
+        } else {
+            None
         }
     }
 
     async fn get_peg_out_statuses(
         &self,
-        client: &AsyncClient,
+        client: &dyn ChainBackend,
     ) -> (
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Result<TxStatus, Error>,
-        Option<Result<TxStatus, Error>>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Result<TxStatus, ChainError>,
+        Option<Result<TxStatus, ChainError>>,
     ) {
-        let kick_off_status = client
-            .get_tx_status(&self.kick_off_transaction.tx().compute_txid())
-            .await;
-        let challenge_status = client
-            .get_tx_status(&self.challenge_transaction.tx().compute_txid())
-            .await;
-        let assert_status = client
-            .get_tx_status(&self.assert_transaction.tx().compute_txid())
-            .await;
-        let disprove_status = client
-            .get_tx_status(&self.disprove_transaction.tx().compute_txid())
-            .await;
-        let burn_status = client
-            .get_tx_status(&self.burn_transaction.tx().compute_txid())
-            .await;
-        let take1_status = client
-            .get_tx_status(&self.take1_transaction.tx().compute_txid())
-            .await;
-        let take2_status = client
-            .get_tx_status(&self.take2_transaction.tx().compute_txid())
-            .await;
-
-        let mut peg_out_status: Option<Result<TxStatus, Error>> = None;
+        let kick_off_txid = self.kick_off_transaction.tx().compute_txid();
+        let take1_txid = self.take1_transaction.tx().compute_txid();
+        let assert_txid = self.assert_transaction.tx().compute_txid();
+        let take2_txid = self.take2_transaction.tx().compute_txid();
+
+        let peg_in_confirm_status = client.get_tx_status(&self.peg_in_confirm_txid).await;
+        let kick_off_status = client.get_tx_status(&kick_off_txid).await;
+        let assert_status = client.get_tx_status(&assert_txid).await;
+        let take1_status = client.get_tx_status(&take1_txid).await;
+        let take2_status = client.get_tx_status(&take2_txid).await;
+
+        // The challenge, disprove and burn change once they are completed, they are found by the
+        // connector they spend instead: connector A, connector 3 and connector B.
+        let challenge_status = get_other_spending_tx_status(
+            client,
+            OutPoint {
+                txid: kick_off_txid,
+                vout: 1,
+            },
+            &[take1_txid],
+        )
+        .await;
+        let disprove_status = get_other_spending_tx_status(
+            client,
+            OutPoint {
+                txid: assert_txid,
+                vout: 1,
+            },
+            &[take2_txid],
+        )
+        .await;
+        let burn_status = get_other_spending_tx_status(
+            client,
+            OutPoint {
+                txid: kick_off_txid,
+                vout: 2,
+            },
+            &[take1_txid, assert_txid],
+        )
+        .await;
+
+        let mut peg_out_status: Option<Result<TxStatus, ChainError>> = None;
         if self.peg_out_transaction.is_some() {
             peg_out_status = Some(
                 client
-                    .get_tx_status(&self.take2_transaction.tx().compute_txid())
+                    .get_tx_status(
+                        &self
+                            .peg_out_transaction
+                            .as_ref()
+                            .unwrap()
+                            .tx()
+                            .compute_txid(),
+                    )
                     .await,
             );
         }
 
         return (
+            peg_in_confirm_status,
             kick_off_status,
             challenge_status,
             assert_status,
@@ -0,0 +1,222 @@
+use bitcoin::Txid;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::{
+    super::client::chain::base::ChainBackend,
+    peg_in::{PegInDepositorStatus, PegInOperatorStatus, PegInVerifierStatus},
+    peg_out::{
+        PegOutAction, PegOutDepositorStatus, PegOutOperatorStatus, PegOutVerifierStatus,
+        PegOutWithdrawerStatus,
+    },
+};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum GraphRole {
+    Depositor,
+    Operator,
+    Verifier,
+    Withdrawer,
+}
+
+impl Display for GraphRole {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            GraphRole::Depositor => write!(f, "depositor"),
+            GraphRole::Operator => write!(f, "operator"),
+            GraphRole::Verifier => write!(f, "verifier"),
+            GraphRole::Withdrawer => write!(f, "withdrawer"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum GraphStatus {
+    PegInDepositor(PegInDepositorStatus),
+    PegInOperator(PegInOperatorStatus),
+    PegInVerifier(PegInVerifierStatus),
+    PegOutDepositor(PegOutDepositorStatus),
+    PegOutOperator(PegOutOperatorStatus),
+    PegOutVerifier(PegOutVerifierStatus),
+    PegOutWithdrawer(PegOutWithdrawerStatus),
+}
+
+impl GraphStatus {
+    // What the role is expected to do next, None if it only has to wait.
+    pub fn next_action(&self) -> Option<String> {
+        match self {
+            GraphStatus::PegInDepositor(PegInDepositorStatus::PegInRefundAvailable) => {
+                Some(String::from("peg-in refund"))
+            }
+            GraphStatus::PegInOperator(PegInOperatorStatus::PegInConfirmAvailable) => {
+                Some(String::from("peg-in confirm"))
+            }
+            GraphStatus::PegInVerifier(PegInVerifierStatus::PegInPresign) => {
+                Some(PegOutAction::PreSign.to_string())
+            }
+            GraphStatus::PegOutWithdrawer(PegOutWithdrawerStatus::PegOutNotRequested) => {
+                Some(String::from("request peg-out"))
+            }
+            GraphStatus::PegOutOperator(status) => match status {
+                PegOutOperatorStatus::PegOutStartPegOut => Some(PegOutAction::PegOut),
+                PegOutOperatorStatus::PegOutKickOffAvailable => Some(PegOutAction::KickOff),
+                PegOutOperatorStatus::PegOutAssertAvailable => Some(PegOutAction::Assert),
+                PegOutOperatorStatus::PegOutTake1Available => Some(PegOutAction::Take1),
+                PegOutOperatorStatus::PegOutTake2Available => Some(PegOutAction::Take2),
+                _ => None,
+            }
+            .map(|action| action.to_string()),
+            GraphStatus::PegOutVerifier(status) => match status {
+                PegOutVerifierStatus::PegOutPresign => Some(PegOutAction::PreSign),
+                PegOutVerifierStatus::PegOutChallengeAvailabe => Some(PegOutAction::Challenge),
+                PegOutVerifierStatus::PegOutBurnAvailable => Some(PegOutAction::Burn),
+                PegOutVerifierStatus::PegOutDisproveAvailable => Some(PegOutAction::Disprove),
+                _ => None,
+            }
+            .map(|action| action.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GraphStatus {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            GraphStatus::PegInDepositor(status) => write!(f, "{}", status),
+            GraphStatus::PegInOperator(status) => write!(f, "{}", status),
+            GraphStatus::PegInVerifier(status) => write!(f, "{}", status),
+            GraphStatus::PegOutDepositor(status) => write!(f, "{}", status),
+            GraphStatus::PegOutOperator(status) => write!(f, "{}", status),
+            GraphStatus::PegOutVerifier(status) => write!(f, "{}", status),
+            GraphStatus::PegOutWithdrawer(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct TransactionStatusReport {
+    pub name: String,
+    pub txid: Txid,
+    pub confirmed: bool,
+    pub block_height: Option<u32>,
+}
+
+// A relative timelock starting once the transaction is confirmed. The expiry height and the
+// blocks remaining are unknown as long as the transaction is not confirmed.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct TimelockStatusReport {
+    pub name: String,
+    pub txid: Txid,
+    pub num_blocks_timelock: u32,
+    pub expiry_height: Option<u32>,
+    pub blocks_remaining: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct GraphStatusReport {
+    pub graph_id: String,
+    pub role: GraphRole,
+    pub status: Option<GraphStatus>,
+    pub error: Option<String>,
+    pub block_height: Option<u32>,
+    pub transactions: Vec<TransactionStatusReport>,
+    pub timelocks: Vec<TimelockStatusReport>,
+    // Every action the role can take right now, next_action being the recommended one
+    pub available_actions: Vec<String>,
+    pub next_action: Option<String>,
+}
+
+impl GraphStatusReport {
+    pub fn new(graph_id: &str, role: GraphRole) -> Self {
+        GraphStatusReport {
+            graph_id: graph_id.to_string(),
+            role,
+            status: None,
+            error: None,
+            block_height: None,
+            transactions: vec![],
+            timelocks: vec![],
+            available_actions: vec![],
+            next_action: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: GraphStatus) {
+        self.next_action = status.next_action();
+        self.available_actions = self.next_action.iter().cloned().collect();
+        self.status = Some(status);
+    }
+
+    pub fn set_error(&mut self, error: impl Display) { self.error = Some(error.to_string()); }
+
+    pub fn transaction(&self, name: &str) -> Option<&TransactionStatusReport> {
+        self.transactions
+            .iter()
+            .find(|transaction| transaction.name == name)
+    }
+
+    pub fn timelock(&self, name: &str) -> Option<&TimelockStatusReport> {
+        self.timelocks.iter().find(|timelock| timelock.name == name)
+    }
+
+    // Adds a timelock of the given number of blocks, starting at the already reported transaction.
+    pub fn add_timelock(&mut self, name: &str, transaction_name: &str, num_blocks_timelock: u32) {
+        let transaction = self
+            .transaction(transaction_name)
+            .expect("Timelock must start at a reported transaction");
+        let txid = transaction.txid;
+        let expiry_height = transaction
+            .block_height
+            .map(|block_height| block_height + num_blocks_timelock);
+        let blocks_remaining = expiry_height.and_then(|expiry_height| {
+            self.block_height
+                .map(|block_height| expiry_height.saturating_sub(block_height))
+        });
+
+        self.timelocks.push(TimelockStatusReport {
+            name: name.to_string(),
+            txid,
+            num_blocks_timelock,
+            expiry_height,
+            blocks_remaining,
+        });
+    }
+}
+
+impl Display for GraphStatusReport {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match (&self.status, &self.error) {
+            (_, Some(error)) => write!(
+                f,
+                "Graph id: {} failed to get {} status: {}",
+                self.graph_id, self.role, error
+            ),
+            (Some(status), None) => write!(
+                f,
+                "Graph id: {} {} status: {}",
+                self.graph_id, self.role, status
+            ),
+            (None, None) => write!(
+                f,
+                "Graph id: {} {} status unknown",
+                self.graph_id, self.role
+            ),
+        }
+    }
+}
+
+// Unknown transactions and chain errors are both reported as not confirmed.
+pub async fn get_transaction_status_report(
+    client: &dyn ChainBackend,
+    name: &str,
+    txid: Txid,
+) -> TransactionStatusReport {
+    let status = client.get_tx_status(&txid).await.ok();
+
+    TransactionStatusReport {
+        name: name.to_string(),
+        txid,
+        confirmed: status.as_ref().is_some_and(|status| status.confirmed),
+        block_height: status.and_then(|status| status.block_height),
+    }
+}
@@ -0,0 +1,97 @@
+use bitcoin::{
+    bip32::{ChildNumber, DerivationPath, Xpriv},
+    key::{Keypair, Secp256k1},
+    secp256k1::All,
+    Network, PublicKey,
+};
+use serde::{Deserialize, Serialize};
+
+use super::super::error::BridgeError;
+
+// BIP-43 purpose of the bridge keys, which are derived at m/purpose'/coin_type'/role'/index'
+pub const BRIDGE_KEY_PURPOSE: u32 = 7697;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum KeyRole {
+    Depositor,
+    Operator,
+    Verifier,
+    Withdrawer,
+}
+
+impl KeyRole {
+    fn index(&self) -> u32 {
+        match self {
+            KeyRole::Depositor => 0,
+            KeyRole::Operator => 1,
+            KeyRole::Verifier => 2,
+            KeyRole::Withdrawer => 3,
+        }
+    }
+}
+
+// Every key of a participant derived from a single BIP-32 seed. Depositors and withdrawers derive
+// fresh keys for every graph, with the graph index. Operators and verifiers are known to the
+// other participants by their keys, so they keep using the keys at index 0.
+#[derive(Clone)]
+pub struct KeyChain {
+    network: Network,
+    secp: Secp256k1<All>,
+    master_key: Xpriv,
+}
+
+impl KeyChain {
+    pub fn from_seed(network: Network, seed: &[u8]) -> Result<Self, BridgeError> {
+        let master_key = Xpriv::new_master(network, seed)
+            .map_err(|err| BridgeError::KeyDerivation(err.to_string()))?;
+
+        Ok(KeyChain {
+            network,
+            secp: Secp256k1::new(),
+            master_key,
+        })
+    }
+
+    pub fn network(&self) -> Network { self.network }
+
+    pub fn secp(&self) -> &Secp256k1<All> { &self.secp }
+
+    pub fn derivation_path(role: KeyRole, index: u32, network: Network) -> DerivationPath {
+        // BIP-44 coin types, mainnet and every test network
+        let coin_type = match network {
+            Network::Bitcoin => 0,
+            _ => 1,
+        };
+
+        [BRIDGE_KEY_PURPOSE, coin_type, role.index(), index]
+            .iter()
+            .map(|child_index| ChildNumber::Hardened {
+                index: *child_index,
+            })
+            .collect::<Vec<ChildNumber>>()
+            .into()
+    }
+
+    pub fn derive_keypair(&self, role: KeyRole, index: u32) -> Result<Keypair, BridgeError> {
+        if index >= (1 << 31) {
+            return Err(BridgeError::KeyDerivation(format!(
+                "Index {} out of range of hardened derivation",
+                index
+            )));
+        }
+
+        let path = Self::derivation_path(role, index, self.network);
+        let key = self
+            .master_key
+            .derive_priv(&self.secp, &path)
+            .map_err(|err| BridgeError::KeyDerivation(err.to_string()))?;
+
+        Ok(key.to_keypair(&self.secp))
+    }
+
+    pub fn derive_public_key(&self, role: KeyRole, index: u32) -> Result<PublicKey, BridgeError> {
+        let keypair = self.derive_keypair(role, index)?;
+
+        Ok(PublicKey::new(keypair.public_key()))
+    }
+}
@@ -0,0 +1,113 @@
+use bitcoin::Network;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{fs, path::Path};
+
+use super::{super::error::BridgeError, key_chain::KeyChain};
+
+const KEYSTORE_VERSION: u32 = 1;
+// PBKDF2-HMAC-SHA256 iterations stretching the password into the encryption key
+pub const KDF_ROUNDS: u32 = 600_000;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const KEY_LENGTH: usize = 32;
+
+// BIP-32 seed encrypted with ChaCha20-Poly1305 under a key derived from a password. Only the
+// encrypted seed is ever written to disk.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct Keystore {
+    version: u32,
+    network: Network,
+    kdf_rounds: u32,
+    salt: String,
+    nonce: String,
+    encrypted_seed: String,
+}
+
+impl Keystore {
+    pub fn new(network: Network, seed: &[u8], password: &str) -> Self {
+        let mut salt = vec![0u8; SALT_LENGTH];
+        let mut nonce = vec![0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let cipher = generate_cipher(password, &salt, KDF_ROUNDS);
+        let encrypted_seed = cipher
+            .encrypt(Nonce::from_slice(&nonce), seed)
+            .expect("Unable to encrypt the seed");
+
+        Keystore {
+            version: KEYSTORE_VERSION,
+            network,
+            kdf_rounds: KDF_ROUNDS,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            encrypted_seed: hex::encode(encrypted_seed),
+        }
+    }
+
+    // Generates a random 256-bit seed.
+    pub fn generate(network: Network, password: &str) -> Self {
+        let mut seed = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut seed);
+
+        Self::new(network, &seed, password)
+    }
+
+    pub fn network(&self) -> Network { self.network }
+
+    pub fn seed(&self, password: &str) -> Result<Vec<u8>, BridgeError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(BridgeError::Keystore(format!(
+                "Unsupported keystore version {}",
+                self.version
+            )));
+        }
+        let decode = |value: &String| {
+            hex::decode(value).map_err(|err| BridgeError::Keystore(err.to_string()))
+        };
+        let salt = decode(&self.salt)?;
+        let nonce = decode(&self.nonce)?;
+        let encrypted_seed = decode(&self.encrypted_seed)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(BridgeError::Keystore(String::from("Invalid nonce")));
+        }
+
+        let cipher = generate_cipher(password, &salt, self.kdf_rounds);
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), encrypted_seed.as_slice())
+            .map_err(|_| {
+                BridgeError::Keystore(String::from("Wrong password or corrupted keystore"))
+            })
+    }
+
+    pub fn key_chain(&self, password: &str) -> Result<KeyChain, BridgeError> {
+        KeyChain::from_seed(self.network, &self.seed(password)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BridgeError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| BridgeError::Keystore(err.to_string()))?;
+
+        fs::write(path, json).map_err(|err| BridgeError::Keystore(err.to_string()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BridgeError> {
+        let json =
+            fs::read_to_string(path).map_err(|err| BridgeError::Keystore(err.to_string()))?;
+
+        serde_json::from_str(&json).map_err(|err| BridgeError::Keystore(err.to_string()))
+    }
+}
+
+fn generate_cipher(password: &str, salt: &[u8], kdf_rounds: u32) -> ChaCha20Poly1305 {
+    let mut key = [0u8; KEY_LENGTH];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, kdf_rounds, &mut key);
+
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
@@ -0,0 +1,3 @@
+pub mod key_chain;
+pub mod keystore;
+pub mod secret_nonce_store;
@@ -0,0 +1,124 @@
+use bitcoin::{
+    hashes::{sha256, Hash},
+    key::Keypair,
+    Txid,
+};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use musig2::SecNonce;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::super::error::BridgeError;
+
+const SECRET_NONCE_STORE_VERSION: u32 = 1;
+const SECRET_NONCE_STORE_KEY_TAG: &[u8] = b"bitvm-bridge-secret-nonce-store";
+const NONCE_LENGTH: usize = 12;
+
+// Secret MuSig2 nonces of one graph, by transaction and input index
+pub type GraphSecretNonces = HashMap<Txid, HashMap<usize, SecNonce>>;
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedSecretNonces {
+    version: u32,
+    nonce: String,
+    encrypted_secret_nonces: String,
+}
+
+// Secret nonces of a verifier by graph id, written to disk so a verifier restarting between
+// pushing its public nonces and pre-signing can still pre-sign. The nonces are encrypted with
+// ChaCha20-Poly1305 under a key derived from the verifier's secret key, which is high entropy
+// and therefore not stretched like a keystore password.
+pub struct SecretNonceStore {
+    path: PathBuf,
+    cipher: ChaCha20Poly1305,
+}
+
+impl SecretNonceStore {
+    pub fn new(path: &Path, keypair: &Keypair) -> Self {
+        let key = sha256::Hash::hash(
+            &[
+                SECRET_NONCE_STORE_KEY_TAG,
+                &keypair.secret_key().secret_bytes(),
+            ]
+            .concat(),
+        );
+
+        SecretNonceStore {
+            path: path.to_path_buf(),
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key.as_byte_array())),
+        }
+    }
+
+    // No nonces are stored before the first save.
+    pub fn load(&self) -> Result<HashMap<String, GraphSecretNonces>, BridgeError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let json =
+            fs::read_to_string(&self.path).map_err(|err| BridgeError::Keystore(err.to_string()))?;
+        let encrypted: EncryptedSecretNonces =
+            serde_json::from_str(&json).map_err(|err| BridgeError::Keystore(err.to_string()))?;
+        if encrypted.version != SECRET_NONCE_STORE_VERSION {
+            return Err(BridgeError::Keystore(format!(
+                "Unsupported secret nonce store version {}",
+                encrypted.version
+            )));
+        }
+        let decode = |value: &String| {
+            hex::decode(value).map_err(|err| BridgeError::Keystore(err.to_string()))
+        };
+        let nonce = decode(&encrypted.nonce)?;
+        let encrypted_secret_nonces = decode(&encrypted.encrypted_secret_nonces)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(BridgeError::Keystore(String::from("Invalid nonce")));
+        }
+
+        let secret_nonces = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                encrypted_secret_nonces.as_slice(),
+            )
+            .map_err(|_| {
+                BridgeError::Keystore(String::from(
+                    "Secret nonces stored by another verifier or corrupted",
+                ))
+            })?;
+
+        serde_json::from_slice(&secret_nonces).map_err(|err| BridgeError::Keystore(err.to_string()))
+    }
+
+    pub fn save(
+        &self,
+        secret_nonces: &HashMap<String, GraphSecretNonces>,
+    ) -> Result<(), BridgeError> {
+        let secret_nonces = serde_json::to_vec(secret_nonces)
+            .map_err(|err| BridgeError::Keystore(err.to_string()))?;
+        let mut nonce = vec![0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted_secret_nonces = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), secret_nonces.as_slice())
+            .map_err(|_| {
+                BridgeError::Keystore(String::from("Unable to encrypt the secret nonces"))
+            })?;
+
+        let json = serde_json::to_string(&EncryptedSecretNonces {
+            version: SECRET_NONCE_STORE_VERSION,
+            nonce: hex::encode(nonce),
+            encrypted_secret_nonces: hex::encode(encrypted_secret_nonces),
+        })
+        .map_err(|err| BridgeError::Keystore(err.to_string()))?;
+
+        fs::write(&self.path, json).map_err(|err| BridgeError::Keystore(err.to_string()))
+    }
+}
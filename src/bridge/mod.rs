@@ -1,8 +1,13 @@
 pub mod client;
+pub mod commitments;
 pub mod connectors;
 pub mod constants;
 pub mod contexts;
+pub mod error;
 pub mod graphs;
+pub mod keys;
+pub mod parameters;
+pub mod registry;
 pub mod scripts;
 pub mod serialization;
 pub mod transactions;
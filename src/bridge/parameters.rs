@@ -0,0 +1,56 @@
+use bitcoin::{FeeRate, Network};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    constants::{NUM_BLOCKS_PER_2_WEEKS, NUM_BLOCKS_PER_4_WEEKS},
+    graphs::base::{CHALLENGE_CROWDFUNDING_AMOUNT, DUST_AMOUNT, FEE_RATE},
+};
+
+// Timelocks (in blocks) and amounts (in sats) a graph is created with. They are serialized with
+// the graph, so a graph keeps its parameters when the defaults of a network change.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+pub struct BridgeParameters {
+    // Blocks after the peg-in deposit before the depositor can take the deposit back
+    pub peg_in_refund_timelock: u32,
+    // Blocks after the kick-off before an unchallenged operator can take 1
+    pub take1_timelock: u32,
+    // Blocks after the assert before an operator who was not disproven can take 2
+    pub take2_timelock: u32,
+    // Blocks after the kick-off before an operator who did not assert can be burnt
+    pub burn_timelock: u32,
+    // Fee rate (in sat/vB) the presigned transactions pay for their estimated weight, a
+    // transaction stuck below the fee rate of the network has to be bumped
+    pub fee_rate: u64,
+    pub dust_amount: u64,
+    pub challenge_crowdfunding_amount: u64,
+}
+
+impl BridgeParameters {
+    pub fn new(network: Network) -> Self {
+        match network {
+            Network::Bitcoin => BridgeParameters {
+                peg_in_refund_timelock: NUM_BLOCKS_PER_2_WEEKS,
+                take1_timelock: NUM_BLOCKS_PER_2_WEEKS,
+                take2_timelock: NUM_BLOCKS_PER_2_WEEKS,
+                burn_timelock: NUM_BLOCKS_PER_4_WEEKS,
+                fee_rate: FEE_RATE,
+                dust_amount: DUST_AMOUNT,
+                challenge_crowdfunding_amount: CHALLENGE_CROWDFUNDING_AMOUNT,
+            },
+            // a few blocks, so that every path of the graphs can be exercised on test networks
+            _ => BridgeParameters {
+                peg_in_refund_timelock: 2,
+                take1_timelock: 2,
+                take2_timelock: 2,
+                burn_timelock: 4,
+                fee_rate: FEE_RATE,
+                dust_amount: DUST_AMOUNT,
+                challenge_crowdfunding_amount: CHALLENGE_CROWDFUNDING_AMOUNT,
+            },
+        }
+    }
+
+    pub fn presigned_fee_rate(&self) -> FeeRate {
+        FeeRate::from_sat_per_vb(self.fee_rate).expect("Fee rate overflow")
+    }
+}
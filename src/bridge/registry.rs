@@ -0,0 +1,110 @@
+use bitcoin::{Network, PublicKey, XOnlyPublicKey};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use super::{
+    contexts::{base::generate_n_of_n_public_key, public::PublicContext},
+    error::BridgeError,
+    graphs::{peg_in::PegInGraph, peg_out::generate_id},
+};
+
+// Every participant of the bridge besides depositors and withdrawers, as loaded from config. Each
+// registered operator creates its own peg-out graph for every peg-in, and the registered verifiers
+// pre-sign all of them with their aggregated MuSig2 key.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct ParticipantRegistry {
+    pub network: Network,
+    pub operator_public_keys: Vec<PublicKey>,
+    pub verifier_public_keys: Vec<PublicKey>,
+}
+
+impl ParticipantRegistry {
+    pub fn new(
+        network: Network,
+        operator_public_keys: &Vec<PublicKey>,
+        verifier_public_keys: &Vec<PublicKey>,
+    ) -> Result<Self, BridgeError> {
+        let registry = ParticipantRegistry {
+            network,
+            operator_public_keys: operator_public_keys.clone(),
+            verifier_public_keys: verifier_public_keys.clone(),
+        };
+        registry.validate()?;
+
+        Ok(registry)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, BridgeError> {
+        let registry: ParticipantRegistry =
+            serde_json::from_str(json).map_err(|err| BridgeError::Registry(err.to_string()))?;
+        registry.validate()?;
+
+        Ok(registry)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BridgeError> {
+        let json =
+            fs::read_to_string(path).map_err(|err| BridgeError::Registry(err.to_string()))?;
+
+        Self::from_json(&json)
+    }
+
+    fn validate(&self) -> Result<(), BridgeError> {
+        for (name, public_keys) in [
+            ("operator", &self.operator_public_keys),
+            ("verifier", &self.verifier_public_keys),
+        ] {
+            if public_keys.is_empty() {
+                return Err(BridgeError::Registry(format!("No {} registered", name)));
+            }
+            for (i, public_key) in public_keys.iter().enumerate() {
+                if public_keys[..i].contains(public_key) {
+                    return Err(BridgeError::Registry(format!(
+                        "{} {} registered twice",
+                        name, public_key
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_operator(&self, public_key: &PublicKey) -> bool {
+        self.operator_public_keys.contains(public_key)
+    }
+
+    pub fn is_verifier(&self, public_key: &PublicKey) -> bool {
+        self.verifier_public_keys.contains(public_key)
+    }
+
+    pub fn n_of_n_public_key(&self) -> (PublicKey, XOnlyPublicKey) {
+        generate_n_of_n_public_key(&self.verifier_public_keys)
+    }
+
+    // The counterparties of the peg-out graphs of one registered operator.
+    pub fn public_context(&self, operator_public_key: &PublicKey) -> Option<PublicContext> {
+        if !self.is_operator(operator_public_key) {
+            return None;
+        }
+
+        Some(PublicContext::new(
+            self.network,
+            operator_public_key,
+            &self.verifier_public_keys,
+        ))
+    }
+
+    // Ids of the peg-out graphs every registered operator is expected to create for the peg-in.
+    pub fn peg_out_graph_ids(&self, peg_in_graph: &PegInGraph) -> Vec<(PublicKey, String)> {
+        self.operator_public_keys
+            .iter()
+            .map(|operator_public_key| {
+                (
+                    *operator_public_key,
+                    generate_id(peg_in_graph, operator_public_key),
+                )
+            })
+            .collect()
+    }
+}
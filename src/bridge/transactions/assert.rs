@@ -1,5 +1,10 @@
-use bitcoin::{absolute, consensus, Amount, ScriptBuf, TapSighashType, Transaction, TxOut};
+use bitcoin::{
+    absolute, consensus, taproot::TaprootSpendInfo, Amount, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
@@ -8,10 +13,14 @@ use super::{
             connector_b::ConnectorB, connector_c::ConnectorC,
         },
         contexts::{operator::OperatorContext, verifier::VerifierContext},
-        graphs::base::{DUST_AMOUNT, FEE_AMOUNT},
+        error::BridgeError,
+        graphs::base::ANCHOR_AMOUNT,
+        scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
 };
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
@@ -22,6 +31,8 @@ pub struct AssertTransaction {
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
     connector_b: ConnectorB,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for AssertTransaction {
@@ -32,62 +43,148 @@ impl PreSignedTransaction for AssertTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_b.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for AssertTransaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0] }
 }
 
 impl AssertTransaction {
     pub fn new(context: &OperatorContext, input0: Input) -> Self {
-        let connector_2 = Connector2::new(context.network, &context.operator_public_key);
-        let connector_3 = Connector3::new(context.network, &context.n_of_n_public_key);
-        let connector_b = ConnectorB::new(context.network, &context.n_of_n_taproot_public_key);
+        let connector_2 = Connector2::new(
+            context.network,
+            &context.operator_public_key,
+            context.parameters.take2_timelock,
+        );
+        let connector_3 = Connector3::new(context.network, &context.n_of_n_taproot_public_key);
+        let connector_b = ConnectorB::new(
+            context.network,
+            &context.n_of_n_taproot_public_key,
+            context.parameters.burn_timelock,
+        );
         let connector_c = ConnectorC::new(context.network, &context.n_of_n_taproot_public_key);
 
-        let _input0 = connector_b.generate_taproot_leaf_tx_in(1, &input0);
+        let _input0 = connector_b.generate_taproot_leaf_tx_in(1, &input0).unwrap();
 
-        let total_output_amount = input0.amount - Amount::from_sat(FEE_AMOUNT);
+        let dust_amount = Amount::from_sat(context.parameters.dust_amount);
 
         let _output0 = TxOut {
-            value: Amount::from_sat(DUST_AMOUNT),
+            value: dust_amount,
             script_pubkey: connector_2.generate_address().script_pubkey(),
         };
 
         let _output1 = TxOut {
-            value: total_output_amount - Amount::from_sat(DUST_AMOUNT) * 2,
-            script_pubkey: connector_3.generate_address().script_pubkey(),
+            value: input0.amount - dust_amount * 2 - Amount::from_sat(ANCHOR_AMOUNT),
+            script_pubkey: connector_3.generate_taproot_address().script_pubkey(),
         };
 
         let _output2 = TxOut {
-            value: Amount::from_sat(DUST_AMOUNT),
+            value: dust_amount,
             script_pubkey: connector_c.generate_taproot_address().script_pubkey(),
         };
 
-        AssertTransaction {
+        // Operator's anchor for bumping the fee of the assert
+        let _output3 = TxOut {
+            value: Amount::from_sat(ANCHOR_AMOUNT),
+            script_pubkey: generate_pay_to_pubkey_script_address(
+                context.network,
+                &context.operator_public_key,
+            )
+            .script_pubkey(),
+        };
+
+        let mut this = AssertTransaction {
             tx: Transaction {
                 version: bitcoin::transaction::Version(2),
                 lock_time: absolute::LockTime::ZERO,
                 input: vec![_input0],
-                output: vec![_output0, _output1, _output2],
+                output: vec![_output0, _output1, _output2, _output3],
             },
             prev_outs: vec![TxOut {
                 value: input0.amount,
                 script_pubkey: connector_b.generate_taproot_address().script_pubkey(),
             }],
-            prev_scripts: vec![connector_b.generate_taproot_leaf_script(1)],
+            prev_scripts: vec![connector_b.generate_taproot_leaf_script(1).unwrap()],
             connector_b,
-        }
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
+        };
+
+        deduct_fee(&mut this, 1, context.parameters.presigned_fee_rate());
+
+        this
     }
 
-    fn sign_input0(&mut self, context: &VerifierContext) {
-        pre_sign_taproot_input(
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 0;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            0,
+            input_index,
+            TapSighashType::All,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
             TapSighashType::All,
             self.connector_b.generate_taproot_spend_info(),
-            &vec![&context.n_of_n_keypair],
-        );
+        )
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) { self.sign_input0(context); }
+    pub fn anchor_vout(&self) -> usize { 3 }
+
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
+        let input_index = 0;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
+    }
+
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)
+    }
+
+    pub fn merge(&mut self, assert: &AssertTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &assert.tx)?;
+        merge_musig2_nonces_and_signatures(self, assert)
+    }
 }
 
 impl BaseTransaction for AssertTransaction {
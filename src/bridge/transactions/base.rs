@@ -1,4 +1,6 @@
-use bitcoin::{Amount, OutPoint, Script, Transaction};
+use bitcoin::{Amount, OutPoint, Script, Transaction, Witness};
+
+use super::super::error::BridgeError;
 
 pub struct Input {
     pub outpoint: OutPoint,
@@ -21,3 +23,95 @@ pub trait BaseTransaction {
     // TODO: Return a Result with an Error in case the witness can't be created
     fn finalize(&self) -> Transaction;
 }
+
+pub fn merge_transactions(
+    destination: &mut Transaction,
+    source: &Transaction,
+) -> Result<(), BridgeError> {
+    if destination.compute_txid() != source.compute_txid() {
+        return Err(BridgeError::GraphMerge(format!(
+            "Transaction {} conflicts with transaction {}",
+            destination.compute_txid(),
+            source.compute_txid()
+        )));
+    }
+
+    for (input_index, source_input) in source.input.iter().enumerate() {
+        let destination_witness = &destination.input[input_index].witness;
+        let source_witness = &source_input.witness;
+
+        if destination_witness.len() >= source_witness.len() {
+            // destination already contains the source witness (or more)
+            if !is_witness_prefix(source_witness, destination_witness) {
+                return Err(BridgeError::GraphMerge(format!(
+                    "Transaction {} has conflicting witness at input {}",
+                    destination.compute_txid(),
+                    input_index
+                )));
+            }
+        } else if is_witness_prefix(destination_witness, source_witness) {
+            destination.input[input_index].witness = source_witness.clone();
+        } else {
+            return Err(BridgeError::GraphMerge(format!(
+                "Transaction {} has conflicting witness at input {}",
+                destination.compute_txid(),
+                input_index
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Inserts witness elements between the signature and the leaf script of a presigned taproot
+// script path spend. The sighash does not cover the witness, so the signature stays valid.
+pub fn insert_taproot_witness_elements(
+    tx: &mut Transaction,
+    input_index: usize,
+    elements: &[Vec<u8>],
+) {
+    let witness = &tx.input[input_index].witness;
+    let mut new_elements = vec![witness[0].to_vec()];
+    new_elements.extend_from_slice(elements);
+    new_elements.push(witness[witness.len() - 2].to_vec());
+    new_elements.push(witness[witness.len() - 1].to_vec());
+    tx.input[input_index].witness = Witness::from_slice(&new_elements);
+}
+
+// Like merge_transactions, but also takes over witnesses carrying elements inserted with
+// insert_taproot_witness_elements, which do not extend the presigned witness.
+pub fn merge_transactions_with_inserted_witness_elements(
+    destination: &mut Transaction,
+    source: &Transaction,
+) -> Result<(), BridgeError> {
+    let mut source = source.clone();
+    if destination.compute_txid() == source.compute_txid() {
+        for (destination_input, source_input) in
+            destination.input.iter_mut().zip(source.input.iter_mut())
+        {
+            if is_presigned_witness_of(&destination_input.witness, &source_input.witness) {
+                destination_input.witness = source_input.witness.clone();
+            } else if is_presigned_witness_of(&source_input.witness, &destination_input.witness) {
+                source_input.witness = destination_input.witness.clone();
+            }
+        }
+    }
+
+    merge_transactions(destination, &source)
+}
+
+fn is_presigned_witness_of(presigned_witness: &Witness, witness: &Witness) -> bool {
+    presigned_witness.len() == 3
+        && witness.len() > 3
+        && presigned_witness[0] == witness[0]
+        && presigned_witness[1] == witness[witness.len() - 2]
+        && presigned_witness[2] == witness[witness.len() - 1]
+}
+
+fn is_witness_prefix(prefix: &Witness, witness: &Witness) -> bool {
+    prefix.len() <= witness.len()
+        && prefix
+            .iter()
+            .zip(witness.iter())
+            .all(|(prefix_element, element)| prefix_element == element)
+}
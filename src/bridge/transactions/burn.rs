@@ -1,15 +1,22 @@
-use bitcoin::{absolute, consensus, Amount, ScriptBuf, TapSighashType, Transaction, TxOut};
+use bitcoin::{
+    absolute, consensus, taproot::TaprootSpendInfo, Amount, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
         connectors::{connector::*, connector_b::ConnectorB},
         contexts::{operator::OperatorContext, verifier::VerifierContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::estimate_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
 };
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
@@ -21,6 +28,8 @@ pub struct BurnTransaction {
     prev_scripts: Vec<ScriptBuf>,
     connector_b: ConnectorB,
     reward_output_amount: Amount,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for BurnTransaction {
@@ -31,29 +40,58 @@ impl PreSignedTransaction for BurnTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_b.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for BurnTransaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0] }
 }
 
 impl BurnTransaction {
     pub fn new(context: &OperatorContext, input0: Input) -> Self {
-        let connector_b = ConnectorB::new(context.network, &context.n_of_n_taproot_public_key);
-
-        let _input0 = connector_b.generate_taproot_leaf_tx_in(2, &input0);
+        let connector_b = ConnectorB::new(
+            context.network,
+            &context.n_of_n_taproot_public_key,
+            context.parameters.burn_timelock,
+        );
 
-        let total_output_amount = input0.amount - Amount::from_sat(FEE_AMOUNT);
+        let _input0 = connector_b.generate_taproot_leaf_tx_in(2, &input0).unwrap();
 
         // Output[0]: value=V*2%*95% to burn
         let _output0 = TxOut {
-            value: total_output_amount * 95 / 100,
+            value: Amount::ZERO,
             script_pubkey: generate_burn_script_address(context.network).script_pubkey(),
         };
 
-        let reward_output_amount = total_output_amount - (total_output_amount * 95 / 100);
+        // Output[1]: the reward, paid to whoever burns the kick-off
         let _output1 = TxOut {
-            value: reward_output_amount,
+            value: Amount::ZERO,
             script_pubkey: ScriptBuf::default(),
         };
 
-        BurnTransaction {
+        let mut this = BurnTransaction {
             tx: Transaction {
                 version: bitcoin::transaction::Version(2),
                 lock_time: absolute::LockTime::ZERO,
@@ -64,37 +102,78 @@ impl BurnTransaction {
                 value: input0.amount,
                 script_pubkey: connector_b.generate_taproot_address().script_pubkey(),
             }],
-            prev_scripts: vec![connector_b.generate_taproot_leaf_script(2)],
+            prev_scripts: vec![connector_b.generate_taproot_leaf_script(2).unwrap()],
             connector_b,
-            reward_output_amount,
-        }
+            reward_output_amount: Amount::ZERO,
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
+        };
+
+        // The script of the reward output is only set when the kick-off is burnt, the rest of the
+        // fee is paid for with PegOutGraph::bump_reward_fee
+        let total_output_amount =
+            input0.amount - estimate_fee(&this, context.parameters.presigned_fee_rate());
+        this.tx.output[0].value = total_output_amount * 95 / 100;
+        this.reward_output_amount = total_output_amount - this.tx.output[0].value;
+        this.tx.output[1].value = this.reward_output_amount;
+
+        this
     }
 
-    fn sign_input0(&mut self, context: &VerifierContext) {
-        pre_sign_taproot_input(
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 0;
+        pre_sign_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::Single,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
             self,
             context,
-            0,
+            input_index,
             TapSighashType::Single,
             self.connector_b.generate_taproot_spend_info(),
-            &vec![&context.n_of_n_keypair],
-        );
+        )
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) { self.sign_input0(context); }
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
+        let input_index = 0;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
+    }
+
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)
+    }
 
     pub fn add_output(&mut self, output_script_pubkey: ScriptBuf) {
-        let output_index = 1;
+        let output_index = self.reward_vout();
         self.tx.output[output_index].script_pubkey = output_script_pubkey;
     }
-}
 
-impl BaseTransaction for BurnTransaction {
-    fn finalize(&self) -> Transaction {
-        if self.tx.output.len() < 2 {
-            panic!("Missing output. Call add_output before finalizing");
-        }
+    pub fn reward_vout(&self) -> usize { 1 }
 
-        self.tx.clone()
+    pub fn merge(&mut self, burn: &BurnTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &burn.tx)?;
+        merge_musig2_nonces_and_signatures(self, burn)
     }
 }
+
+impl BaseTransaction for BurnTransaction {
+    fn finalize(&self) -> Transaction { self.tx.clone() }
+}
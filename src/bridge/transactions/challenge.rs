@@ -1,5 +1,9 @@
 use bitcoin::{
-    absolute, consensus, key::Keypair, Amount, ScriptBuf, Sequence, TapSighashType, Transaction,
+    absolute, consensus,
+    key::Keypair,
+    psbt::{Input as PsbtInput, Output as PsbtOutput},
+    taproot::TaprootSpendInfo,
+    Amount, EcdsaSighashType, Psbt, PublicKey, ScriptBuf, Sequence, TapSighashType, Transaction,
     TxIn, TxOut, Witness,
 };
 use serde::{Deserialize, Serialize};
@@ -8,12 +12,13 @@ use super::{
     super::{
         connectors::{connector::*, connector_a::ConnectorA},
         contexts::{base::BaseContext, operator::OperatorContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
-    signing::populate_p2wsh_witness,
+    signing::generate_p2wsh_signature,
 };
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
@@ -35,6 +40,13 @@ impl PreSignedTransaction for ChallengeTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_a.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
 }
 
 impl ChallengeTransaction {
@@ -49,10 +61,9 @@ impl ChallengeTransaction {
             &context.n_of_n_taproot_public_key,
         );
 
-        let _input0 = connector_a.generate_taproot_leaf_tx_in(1, &input0);
+        let _input0 = connector_a.generate_taproot_leaf_tx_in(1, &input0).unwrap();
 
-        let total_output_amount =
-            input0.amount + input_amount_crowdfunding - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount + input_amount_crowdfunding;
 
         let _output0 = TxOut {
             value: total_output_amount,
@@ -78,13 +89,16 @@ impl ChallengeTransaction {
                 // input1 will be added later
             ],
             prev_scripts: vec![
-                connector_a.generate_taproot_leaf_script(1),
+                connector_a.generate_taproot_leaf_script(1).unwrap(),
                 // input1's script will be added later
             ],
             input_amount_crowdfunding,
             connector_a,
         };
 
+        // The crowdfunding inputs are not known yet, the fee only covers input 0 and the output
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.sign_input0(context);
 
         this
@@ -101,68 +115,263 @@ impl ChallengeTransaction {
         );
     }
 
-    // allows for aggregating multiple inputs and one refund output
-    pub fn add_inputs_and_output(
-        &mut self,
-        context: &dyn BaseContext,
+    pub fn input_amount_crowdfunding(&self) -> Amount { self.input_amount_crowdfunding }
+
+    // Starts the crowdfunding of the challenge: a PSBT spending the operator's presigned input 0,
+    // to which the challengers add their inputs with add_crowdfunding_inputs. Input 0 is signed
+    // with SINGLE|ANYONECANPAY, so adding inputs and outputs keeps its signature valid.
+    pub fn crowdfunding_psbt(&self) -> Psbt {
+        let mut unsigned_tx = self.tx.clone();
+        unsigned_tx.input.truncate(1);
+        unsigned_tx.output.truncate(1);
+        unsigned_tx.input[0].witness = Witness::default();
+
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(self.prev_outs[0].clone());
+        psbt.inputs[0].final_script_witness = Some(self.tx.input[0].witness.clone());
+
+        psbt
+    }
+
+    // Adds the inputs of one challenger, contributing at most the amount still missing. Any excess
+    // is refunded to the challenger in an output of its own.
+    pub fn add_crowdfunding_inputs(
+        &self,
+        psbt: &mut Psbt,
         inputs: &Vec<InputWithScript>,
-        keypair: &Keypair,
-        output_script_pubkey: ScriptBuf,
-    ) {
-        if self.tx.input.len() > 1 {
-            panic!("Cannot add any more inputs or outputs.");
+        refund_script_pubkey: ScriptBuf,
+    ) -> Result<(), BridgeError> {
+        self.verify_crowdfunding_psbt(psbt)?;
+
+        let missing_amount = self
+            .input_amount_crowdfunding
+            .checked_sub(get_crowdfunded_amount(psbt))
+            .unwrap_or(Amount::ZERO);
+        if missing_amount == Amount::ZERO {
+            return Err(BridgeError::Crowdfunding(String::from(
+                "Challenge is already fully crowdfunded",
+            )));
         }
 
-        // check total input amount
-        let mut total_input_amount = Amount::from_sat(0);
+        let mut total_input_amount = Amount::ZERO;
         for input in inputs {
             total_input_amount += input.amount;
-        }
-        if total_input_amount < self.input_amount_crowdfunding {
-            panic!("Total input amount too low. Add additional input.");
-        } else if total_input_amount > self.input_amount_crowdfunding {
-            // add refund output
-            let _output = TxOut {
-                value: total_input_amount - self.input_amount_crowdfunding,
-                script_pubkey: output_script_pubkey,
-            };
-            self.tx.output.push(_output);
-        }
 
-        // add crowdfunding inputs
-        let sighash_type = bitcoin::EcdsaSighashType::AllPlusAnyoneCanPay;
-        let mut input_index = self.tx.input.len();
-        for input in inputs {
-            let _input = TxIn {
+            psbt.unsigned_tx.input.push(TxIn {
                 previous_output: input.outpoint,
                 script_sig: ScriptBuf::new(),
                 sequence: Sequence::MAX,
                 witness: Witness::default(),
-            };
-            self.tx.input.push(_input);
+            });
+            psbt.inputs.push(PsbtInput {
+                witness_utxo: Some(TxOut {
+                    value: input.amount,
+                    script_pubkey: ScriptBuf::new_p2wsh(&input.script.wscript_hash()),
+                }),
+                witness_script: Some(input.script.to_owned()),
+                sighash_type: Some(EcdsaSighashType::AllPlusAnyoneCanPay.into()),
+                ..Default::default()
+            });
+        }
 
-            // add witness
-            populate_p2wsh_witness(
+        if total_input_amount > missing_amount {
+            // add refund output
+            psbt.unsigned_tx.output.push(TxOut {
+                value: total_input_amount - missing_amount,
+                script_pubkey: refund_script_pubkey,
+            });
+            psbt.outputs.push(PsbtOutput::default());
+        }
+
+        Ok(())
+    }
+
+    // Signs the inputs of the challenger owning the keypair. Only possible once the challenge is
+    // fully crowdfunded, since the signatures commit to all outputs.
+    pub fn sign_crowdfunding_inputs(
+        &self,
+        context: &dyn BaseContext,
+        psbt: &mut Psbt,
+        keypair: &Keypair,
+    ) -> Result<(), BridgeError> {
+        self.verify_crowdfunding_psbt(psbt)?;
+        self.verify_crowdfunded_amount(psbt)?;
+
+        let public_key = PublicKey::new(keypair.public_key());
+        let script = generate_pay_to_pubkey_script(&public_key);
+        let sighash_type = EcdsaSighashType::AllPlusAnyoneCanPay;
+        let mut unsigned_tx = psbt.unsigned_tx.clone();
+        for input_index in 1..psbt.inputs.len() {
+            if psbt.inputs[input_index].witness_script.as_ref() != Some(&script) {
+                continue;
+            }
+
+            let amount = psbt.inputs[input_index]
+                .witness_utxo
+                .as_ref()
+                .unwrap()
+                .value;
+            let signature = generate_p2wsh_signature(
                 context,
-                &mut self.tx,
+                &mut unsigned_tx,
                 input_index,
                 sighash_type,
-                input.script,
-                input.amount,
-                &vec![&keypair],
+                &script,
+                amount,
+                keypair,
             );
+            psbt.inputs[input_index]
+                .partial_sigs
+                .insert(public_key, signature);
+        }
+
+        Ok(())
+    }
+
+    // Completes the challenge from the signed crowdfunding PSBT.
+    pub fn finalize_crowdfunding_psbt(&mut self, psbt: &Psbt) -> Result<(), BridgeError> {
+        self.verify_crowdfunding_psbt(psbt)?;
+        self.verify_crowdfunded_amount(psbt)?;
+
+        let mut tx = psbt.unsigned_tx.clone();
+        let mut prev_outs = vec![self.prev_outs[0].clone()];
+        let mut prev_scripts = vec![self.prev_scripts[0].clone()];
+        tx.input[0].witness = self.tx.input[0].witness.clone();
+        for (input_index, input) in psbt.inputs.iter().enumerate().skip(1) {
+            if input.partial_sigs.is_empty() {
+                return Err(BridgeError::Crowdfunding(format!(
+                    "Input {} has not been signed",
+                    input_index
+                )));
+            }
 
-            input_index += 1;
+            let script = input.witness_script.clone().unwrap();
+            let mut witness = Witness::new();
+            for signature in input.partial_sigs.values() {
+                witness.push_ecdsa_signature(signature);
+            }
+            witness.push(&script);
+            tx.input[input_index].witness = witness;
+
+            prev_outs.push(input.witness_utxo.clone().unwrap());
+            prev_scripts.push(script);
         }
+
+        self.tx = tx;
+        self.prev_outs = prev_outs;
+        self.prev_scripts = prev_scripts;
+
+        Ok(())
     }
-}
 
-impl BaseTransaction for ChallengeTransaction {
-    fn finalize(&self) -> Transaction {
-        if self.tx.input.len() < 2 {
-            panic!("Missing input. Call add_inputs_and_output before finalizing");
+    // Adds the inputs of a single challenger funding the whole challenge and signs them.
+    pub fn add_inputs_and_output(
+        &mut self,
+        context: &dyn BaseContext,
+        inputs: &Vec<InputWithScript>,
+        keypair: &Keypair,
+        output_script_pubkey: ScriptBuf,
+    ) -> Result<(), BridgeError> {
+        if self.tx.input.len() > 1 {
+            return Err(BridgeError::Crowdfunding(String::from(
+                "Challenge has already been crowdfunded",
+            )));
+        }
+
+        let mut psbt = self.crowdfunding_psbt();
+        self.add_crowdfunding_inputs(&mut psbt, inputs, output_script_pubkey)?;
+        self.sign_crowdfunding_inputs(context, &mut psbt, keypair)?;
+        self.finalize_crowdfunding_psbt(&psbt)
+    }
+
+    // The challengers may only add inputs and outputs, the operator's input and output are fixed.
+    fn verify_crowdfunding_psbt(&self, psbt: &Psbt) -> Result<(), BridgeError> {
+        let operator_input = &self.tx.input[0];
+        let is_operator_input_unchanged = psbt.unsigned_tx.input.first().is_some_and(|input| {
+            input.previous_output == operator_input.previous_output
+                && input.sequence == operator_input.sequence
+        });
+        if !is_operator_input_unchanged
+            || psbt.unsigned_tx.output.first() != Some(&self.tx.output[0])
+            || psbt.unsigned_tx.version != self.tx.version
+            || psbt.unsigned_tx.lock_time != self.tx.lock_time
+        {
+            return Err(BridgeError::Crowdfunding(String::from(
+                "PSBT does not spend the operator's input to the operator's output",
+            )));
+        }
+
+        if psbt
+            .inputs
+            .iter()
+            .skip(1)
+            .any(|input| input.witness_utxo.is_none() || input.witness_script.is_none())
+        {
+            return Err(BridgeError::Crowdfunding(String::from(
+                "PSBT is missing the witness utxo or script of a crowdfunding input",
+            )));
         }
 
-        self.tx.clone()
+        Ok(())
     }
+
+    fn verify_crowdfunded_amount(&self, psbt: &Psbt) -> Result<(), BridgeError> {
+        let crowdfunded_amount = get_crowdfunded_amount(psbt);
+        if crowdfunded_amount < self.input_amount_crowdfunding {
+            return Err(BridgeError::Crowdfunding(format!(
+                "Crowdfunded {} of {}, add additional inputs",
+                crowdfunded_amount, self.input_amount_crowdfunding
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn merge(&mut self, challenge: &ChallengeTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &challenge.tx)
+    }
+}
+
+// Amount contributed by the crowdfunding inputs, net of the refund outputs.
+pub fn get_crowdfunded_amount(psbt: &Psbt) -> Amount {
+    let total_input_amount: Amount = psbt
+        .inputs
+        .iter()
+        .skip(1)
+        .filter_map(|input| input.witness_utxo.as_ref())
+        .map(|witness_utxo| witness_utxo.value)
+        .sum();
+    let total_refund_amount: Amount = psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .skip(1)
+        .map(|output| output.value)
+        .sum();
+
+    total_input_amount
+        .checked_sub(total_refund_amount)
+        .unwrap_or(Amount::ZERO)
+}
+
+// Combines the crowdfunding PSBTs signed by the individual challengers.
+pub fn combine_crowdfunding_psbts(psbts: &[Psbt]) -> Result<Psbt, BridgeError> {
+    let (first, others) = psbts
+        .split_first()
+        .ok_or(BridgeError::Crowdfunding(String::from(
+            "No PSBT to combine",
+        )))?;
+
+    let mut combined = first.clone();
+    for psbt in others {
+        combined.combine(psbt.clone()).map_err(|err| {
+            BridgeError::Crowdfunding(format!("Unable to combine PSBTs: {}", err))
+        })?;
+    }
+
+    Ok(combined)
+}
+
+impl BaseTransaction for ChallengeTransaction {
+    fn finalize(&self) -> Transaction { self.tx.clone() }
 }
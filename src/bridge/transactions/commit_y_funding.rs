@@ -0,0 +1,106 @@
+use bitcoin::{absolute, consensus, EcdsaSighashType, OutPoint, ScriptBuf, Transaction, TxOut};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    super::{
+        commitments::WinternitzPublicKey,
+        connectors::{connector::*, connector_y::ConnectorY},
+        contexts::operator::OperatorContext,
+        scripts::*,
+    },
+    base::*,
+    fee::deduct_fee,
+    pre_signed::*,
+};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
+pub struct CommitYFundingTransaction {
+    #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
+    tx: Transaction,
+    #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
+    prev_outs: Vec<TxOut>,
+    prev_scripts: Vec<ScriptBuf>,
+}
+
+impl PreSignedTransaction for CommitYFundingTransaction {
+    fn tx(&self) -> &Transaction { &self.tx }
+
+    fn tx_mut(&mut self) -> &mut Transaction { &mut self.tx }
+
+    fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
+
+    fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+}
+
+impl CommitYFundingTransaction {
+    // Moves the operator's funds to the Commit Y address of a peg-out graph, the output is the
+    // input of its kick-off.
+    pub fn new(
+        context: &OperatorContext,
+        input0: Input,
+        commit_y_public_key: &WinternitzPublicKey,
+    ) -> Self {
+        let connector_y = ConnectorY::new(
+            context.network,
+            &context.operator_taproot_public_key,
+            commit_y_public_key,
+        );
+
+        let _input0 = generate_default_tx_in(&input0);
+
+        let total_output_amount = input0.amount;
+
+        let _output0 = TxOut {
+            value: total_output_amount,
+            script_pubkey: connector_y.generate_taproot_address().script_pubkey(),
+        };
+
+        let mut this = CommitYFundingTransaction {
+            tx: Transaction {
+                version: bitcoin::transaction::Version(2),
+                lock_time: absolute::LockTime::ZERO,
+                input: vec![_input0],
+                output: vec![_output0],
+            },
+            prev_outs: vec![TxOut {
+                value: input0.amount,
+                script_pubkey: generate_pay_to_pubkey_script_address(
+                    context.network,
+                    &context.operator_public_key,
+                )
+                .script_pubkey(),
+            }],
+            prev_scripts: vec![generate_pay_to_pubkey_script(&context.operator_public_key)],
+        };
+
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
+        this.sign_input0(context);
+
+        this
+    }
+
+    fn sign_input0(&mut self, context: &OperatorContext) {
+        pre_sign_p2wsh_input(
+            self,
+            context,
+            0,
+            EcdsaSighashType::All,
+            &vec![&context.operator_keypair],
+        );
+    }
+
+    pub fn kick_off_input(&self) -> Input {
+        Input {
+            outpoint: OutPoint {
+                txid: self.tx.compute_txid(),
+                vout: 0,
+            },
+            amount: self.tx.output[0].value,
+        }
+    }
+}
+
+impl BaseTransaction for CommitYFundingTransaction {
+    fn finalize(&self) -> Transaction { self.tx.clone() }
+}
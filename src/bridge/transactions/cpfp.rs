@@ -0,0 +1,139 @@
+use bitcoin::{
+    absolute, consensus, key::Keypair, Amount, EcdsaSighashType, FeeRate, OutPoint, PublicKey,
+    ScriptBuf, Transaction, TxOut, Txid,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    super::{
+        connectors::connector::*, contexts::base::BaseContext, error::BridgeError, scripts::*,
+    },
+    base::*,
+    fee::{calculate_cpfp_fee, estimate_weight},
+    pre_signed::*,
+};
+
+// Child paying for a bridge transaction stuck in the mempool. Input[0] spends an output of the
+// stuck parent, every further input adds funds. All inputs are pay-to-pubkey outputs of the same
+// key, and the change goes back to it.
+#[derive(Serialize, Deserialize, Eq, PartialEq)]
+pub struct CpfpTransaction {
+    #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
+    tx: Transaction,
+    #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
+    prev_outs: Vec<TxOut>,
+    prev_scripts: Vec<ScriptBuf>,
+}
+
+impl PreSignedTransaction for CpfpTransaction {
+    fn tx(&self) -> &Transaction { &self.tx }
+
+    fn tx_mut(&mut self) -> &mut Transaction { &mut self.tx }
+
+    fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
+
+    fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+}
+
+impl CpfpTransaction {
+    // The parent pays parent_fee, the child pays whatever is missing for parent and child together
+    // to reach the fee rate.
+    pub fn new(
+        context: &dyn BaseContext,
+        parent: &Transaction,
+        parent_fee: Amount,
+        parent_vout: u32,
+        funding_inputs: &Vec<Input>,
+        keypair: &Keypair,
+        fee_rate: FeeRate,
+    ) -> Result<Self, BridgeError> {
+        let public_key = PublicKey::new(keypair.public_key());
+        let script = generate_pay_to_pubkey_script(&public_key);
+        let script_pubkey =
+            generate_pay_to_pubkey_script_address(context.network(), &public_key).script_pubkey();
+
+        let parent_output =
+            parent
+                .output
+                .get(parent_vout as usize)
+                .ok_or(BridgeError::FeeBumping(format!(
+                    "Transaction {} has no output {}",
+                    parent.compute_txid(),
+                    parent_vout
+                )))?;
+        if parent_output.script_pubkey != script_pubkey {
+            return Err(BridgeError::FeeBumping(format!(
+                "Output {} of transaction {} cannot be spent with key {}",
+                parent_vout,
+                parent.compute_txid(),
+                public_key
+            )));
+        }
+
+        let mut inputs = vec![Input {
+            outpoint: OutPoint {
+                txid: parent.compute_txid(),
+                vout: parent_vout,
+            },
+            amount: parent_output.value,
+        }];
+        inputs.extend(funding_inputs.iter().map(|input| Input {
+            outpoint: input.outpoint,
+            amount: input.amount,
+        }));
+        let total_input_amount: Amount = inputs.iter().map(|input| input.amount).sum();
+
+        let mut this = CpfpTransaction {
+            tx: Transaction {
+                version: bitcoin::transaction::Version(2),
+                lock_time: absolute::LockTime::ZERO,
+                input: inputs.iter().map(generate_default_tx_in).collect(),
+                output: vec![TxOut {
+                    value: total_input_amount,
+                    script_pubkey: script_pubkey.clone(),
+                }],
+            },
+            prev_outs: inputs
+                .iter()
+                .map(|input| TxOut {
+                    value: input.amount,
+                    script_pubkey: script_pubkey.clone(),
+                })
+                .collect(),
+            prev_scripts: vec![script; inputs.len()],
+        };
+
+        let fee = calculate_cpfp_fee(
+            parent.weight(),
+            parent_fee,
+            estimate_weight(&this),
+            fee_rate,
+        );
+        let change_amount = total_input_amount
+            .checked_sub(fee)
+            .filter(|change_amount| *change_amount >= script_pubkey.minimal_non_dust())
+            .ok_or(BridgeError::FeeBumping(format!(
+                "Inputs of {} cannot pay a fee of {}",
+                total_input_amount, fee
+            )))?;
+        this.tx.output[0].value = change_amount;
+
+        for input_index in 0..this.tx.input.len() {
+            pre_sign_p2wsh_input(
+                &mut this,
+                context,
+                input_index,
+                EcdsaSighashType::All,
+                &vec![keypair],
+            );
+        }
+
+        Ok(this)
+    }
+
+    pub fn parent_txid(&self) -> Txid { self.tx.input[0].previous_output.txid }
+}
+
+impl BaseTransaction for CpfpTransaction {
+    fn finalize(&self) -> Transaction { self.tx.clone() }
+}
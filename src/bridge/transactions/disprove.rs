@@ -1,15 +1,22 @@
-use bitcoin::{absolute, consensus, Amount, EcdsaSighashType, ScriptBuf, Transaction, TxOut};
+use bitcoin::{
+    absolute, consensus, taproot::TaprootSpendInfo, Amount, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
         connectors::{connector::*, connector_3::Connector3, connector_c::ConnectorC},
         contexts::{operator::OperatorContext, verifier::VerifierContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::estimate_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
     signing::push_taproot_leaf_script_and_control_block_to_witness,
 };
 
@@ -20,8 +27,11 @@ pub struct DisproveTransaction {
     #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
+    connector_3: Connector3,
     connector_c: ConnectorC,
     reward_output_amount: Amount,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for DisproveTransaction {
@@ -32,31 +42,58 @@ impl PreSignedTransaction for DisproveTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_3.generate_taproot_spend_info()),
+            1 => Some(self.connector_c.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for DisproveTransaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0] }
 }
 
 impl DisproveTransaction {
     pub fn new(context: &OperatorContext, input0: Input, input1: Input, script_index: u32) -> Self {
-        let connector_3 = Connector3::new(context.network, &context.n_of_n_public_key);
+        let connector_3 = Connector3::new(context.network, &context.n_of_n_taproot_public_key);
         let connector_c = ConnectorC::new(context.network, &context.n_of_n_taproot_public_key);
 
-        let _input0 = connector_3.generate_tx_in(&input0);
-
-        let _input1 = connector_c.generate_taproot_leaf_tx_in(script_index, &input1);
+        let _input0 = connector_3.generate_taproot_leaf_tx_in(0, &input0).unwrap();
 
-        let total_output_amount = input0.amount + input1.amount - Amount::from_sat(FEE_AMOUNT);
+        let _input1 = connector_c
+            .generate_taproot_leaf_tx_in(script_index, &input1)
+            .unwrap();
 
         let _output0 = TxOut {
-            value: total_output_amount / 2,
+            value: Amount::ZERO,
             script_pubkey: generate_burn_script_address(context.network).script_pubkey(),
         };
 
-        let reward_output_amount = total_output_amount - (total_output_amount / 2);
         let _output1 = TxOut {
-            value: reward_output_amount,
+            value: Amount::ZERO,
             script_pubkey: ScriptBuf::default(),
         };
 
-        DisproveTransaction {
+        let mut this = DisproveTransaction {
             tx: Transaction {
                 version: bitcoin::transaction::Version(2),
                 lock_time: absolute::LockTime::ZERO,
@@ -66,34 +103,88 @@ impl DisproveTransaction {
             prev_outs: vec![
                 TxOut {
                     value: input0.amount,
-                    script_pubkey: connector_3.generate_address().script_pubkey(),
+                    script_pubkey: connector_3.generate_taproot_address().script_pubkey(),
                 },
                 TxOut {
                     value: input1.amount,
                     script_pubkey: connector_c.generate_taproot_address().script_pubkey(),
                 },
             ],
-            prev_scripts: vec![connector_3.generate_script()],
+            prev_scripts: vec![connector_3.generate_taproot_leaf_script(0).unwrap()],
+            connector_3,
             connector_c,
-            reward_output_amount,
-        }
+            reward_output_amount: Amount::ZERO,
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
+        };
+
+        // The witness of input 1 and the script of the reward output are only set at disprove,
+        // the rest of the fee is paid for with PegOutGraph::bump_reward_fee
+        let total_output_amount = input0.amount + input1.amount
+            - estimate_fee(&this, context.parameters.presigned_fee_rate());
+        this.tx.output[0].value = total_output_amount / 2;
+        this.reward_output_amount = total_output_amount - this.tx.output[0].value;
+        this.tx.output[1].value = this.reward_output_amount;
+
+        this
     }
 
-    fn sign_input0(&mut self, context: &VerifierContext) {
-        pre_sign_p2wsh_input(
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 0;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            0,
-            EcdsaSighashType::Single,
-            &vec![&context.n_of_n_keypair],
-        );
+            input_index,
+            TapSighashType::Single,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::Single,
+            self.connector_3.generate_taproot_spend_info(),
+        )
+    }
+
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
+        let input_index = 0;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) { self.sign_input0(context); }
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)
+    }
+
+    pub fn add_input_output(
+        &mut self,
+        input_script_index: u32,
+        output_script_pubkey: ScriptBuf,
+    ) -> Result<(), BridgeError> {
+        // Resolve the leaf before modifying the tx, so an invalid index leaves it untouched
+        let unlock_witness = self
+            .connector_c
+            .generate_taproot_leaf_script_witness(input_script_index)?;
+        let script = self
+            .connector_c
+            .generate_taproot_leaf_script(input_script_index)?;
 
-    pub fn add_input_output(&mut self, input_script_index: u32, output_script_pubkey: ScriptBuf) {
         // Add output
-        let output_index = 1;
+        let output_index = self.reward_vout();
         self.tx.output[output_index].script_pubkey = output_script_pubkey;
 
         let input_index = 1;
@@ -101,15 +192,9 @@ impl DisproveTransaction {
         // TODO: Doesn't this needs to be signed sighash_single or sighash_all? Shouln't leave these input/outputs unsigned
 
         // Push the unlocking witness
-        let unlock_witness = self
-            .connector_c
-            .generate_taproot_leaf_script_witness(input_script_index);
         self.tx.input[input_index].witness.push(unlock_witness);
 
         // Push script + control block
-        let script = self
-            .connector_c
-            .generate_taproot_leaf_script(input_script_index);
         let taproot_spend_info = self.connector_c.generate_taproot_spend_info();
         push_taproot_leaf_script_and_control_block_to_witness(
             &mut self.tx,
@@ -117,15 +202,18 @@ impl DisproveTransaction {
             &taproot_spend_info,
             &script,
         );
+
+        Ok(())
     }
-}
 
-impl BaseTransaction for DisproveTransaction {
-    fn finalize(&self) -> Transaction {
-        if self.tx.input.len() < 2 || self.tx.output.len() < 2 {
-            panic!("Missing input or output. Call add_input_output before finalizing");
-        }
+    pub fn reward_vout(&self) -> usize { 1 }
 
-        self.tx.clone()
+    pub fn merge(&mut self, disprove: &DisproveTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &disprove.tx)?;
+        merge_musig2_nonces_and_signatures(self, disprove)
     }
 }
+
+impl BaseTransaction for DisproveTransaction {
+    fn finalize(&self) -> Transaction { self.tx.clone() }
+}
@@ -0,0 +1,96 @@
+use bitcoin::{taproot::LeafVersion, Amount, FeeRate, Weight, Witness};
+
+use super::{pre_signed::PreSignedTransaction, signing::get_script_public_keys};
+
+// Upper bounds of the signatures pushed to a witness, including the sighash type byte
+const ECDSA_SIGNATURE_LENGTH: usize = 73;
+const SCHNORR_SIGNATURE_LENGTH: usize = 65;
+const COMPRESSED_PUBLIC_KEY_LENGTH: usize = 33;
+// Control block of a taproot output with a single leaf
+const MIN_CONTROL_BLOCK_LENGTH: usize = 33;
+
+pub fn calculate_fee(weight: Weight, fee_rate: FeeRate) -> Amount {
+    fee_rate.fee_wu(weight).expect("Fee amount overflow")
+}
+
+// Fee paid by the transaction, as long as every input spends an output in prev_outs.
+pub fn get_fee<T: PreSignedTransaction + ?Sized>(tx: &T) -> Amount {
+    let input_amount: Amount = tx.prev_outs().iter().map(|prev_out| prev_out.value).sum();
+    let output_amount: Amount = tx.tx().output.iter().map(|output| output.value).sum();
+
+    input_amount - output_amount
+}
+
+// Weight of the transaction once it is signed. Inputs already carrying a witness are counted as
+// they are, every other input spending a known output with one signature per key of its script.
+pub fn estimate_weight<T: PreSignedTransaction + ?Sized>(tx: &T) -> Weight {
+    let mut estimated_tx = tx.tx().clone();
+
+    for (input_index, input) in estimated_tx.input.iter_mut().enumerate() {
+        if !input.witness.is_empty() || input_index >= tx.prev_scripts().len() {
+            continue;
+        }
+
+        let prev_out = &tx.prev_outs()[input_index];
+        let script = &tx.prev_scripts()[input_index];
+        let (public_keys, taproot_public_keys) = get_script_public_keys(script);
+        let mut witness = Witness::new();
+
+        if prev_out.script_pubkey.is_p2tr() {
+            for _ in taproot_public_keys.iter() {
+                witness.push([0u8; SCHNORR_SIGNATURE_LENGTH]);
+            }
+            witness.push(script.as_bytes());
+            let control_block_length = tx
+                .taproot_spend_info(input_index)
+                .and_then(|spend_info| {
+                    spend_info.control_block(&(script.clone(), LeafVersion::TapScript))
+                })
+                .map_or(MIN_CONTROL_BLOCK_LENGTH, |control_block| {
+                    control_block.serialize().len()
+                });
+            witness.push(vec![0u8; control_block_length]);
+        } else if prev_out.script_pubkey.is_p2wpkh() {
+            witness.push([0u8; ECDSA_SIGNATURE_LENGTH]);
+            witness.push([0u8; COMPRESSED_PUBLIC_KEY_LENGTH]);
+        } else {
+            for _ in public_keys.iter() {
+                witness.push([0u8; ECDSA_SIGNATURE_LENGTH]);
+            }
+            witness.push(script.as_bytes());
+        }
+
+        input.witness = witness;
+    }
+
+    estimated_tx.weight()
+}
+
+// Fee the transaction has to pay to reach the fee rate once it is signed.
+pub fn estimate_fee<T: PreSignedTransaction + ?Sized>(tx: &T, fee_rate: FeeRate) -> Amount {
+    calculate_fee(estimate_weight(tx), fee_rate)
+}
+
+// Takes the fee the transaction has to pay to reach the fee rate once it is signed from the
+// output, before any input commits to its value.
+pub fn deduct_fee<T: PreSignedTransaction + ?Sized>(tx: &mut T, vout: usize, fee_rate: FeeRate) {
+    let fee = estimate_fee(tx, fee_rate);
+    tx.tx_mut().output[vout].value -= fee;
+}
+
+// Fee a child transaction has to pay for the parent and the child together to reach the fee rate.
+pub fn calculate_cpfp_fee(
+    parent_weight: Weight,
+    parent_fee: Amount,
+    child_weight: Weight,
+    fee_rate: FeeRate,
+) -> Amount {
+    let package_fee = calculate_fee(parent_weight + child_weight, fee_rate);
+    let child_fee = calculate_fee(child_weight, fee_rate);
+
+    // the child pays at least for itself, even if the parent already pays enough
+    package_fee
+        .checked_sub(parent_fee)
+        .unwrap_or(Amount::ZERO)
+        .max(child_fee)
+}
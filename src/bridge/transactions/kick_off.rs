@@ -1,19 +1,26 @@
 use bitcoin::{
-    absolute, consensus, Amount, EcdsaSighashType, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
-    Witness,
+    absolute, consensus, taproot::TaprootSpendInfo, Amount, ScriptBuf, TapSighashType, Transaction,
+    TxOut, Weight,
 };
 use serde::{Deserialize, Serialize};
 
 use super::{
     super::{
+        commitments::{
+            verify_commit_y, CommitmentValue, WinternitzPublicKey, WinternitzSignature,
+            COMMIT_Y_WITNESS_LENGTH,
+        },
         connectors::{
-            connector::*, connector_1::Connector1, connector_a::ConnectorA, connector_b::ConnectorB,
+            connector::*, connector_1::Connector1, connector_a::ConnectorA,
+            connector_b::ConnectorB, connector_y::ConnectorY,
         },
         contexts::operator::OperatorContext,
-        graphs::base::{DUST_AMOUNT, FEE_AMOUNT},
+        error::BridgeError,
+        graphs::base::ANCHOR_AMOUNT,
         scripts::*,
     },
     base::*,
+    fee::{calculate_fee, estimate_weight},
     pre_signed::*,
 };
 
@@ -24,6 +31,7 @@ pub struct KickOffTransaction {
     #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
+    connector_y: ConnectorY,
 }
 
 impl PreSignedTransaction for KickOffTransaction {
@@ -34,76 +42,150 @@ impl PreSignedTransaction for KickOffTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_y.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
 }
 
 impl KickOffTransaction {
-    pub fn new(context: &OperatorContext, operator_input: Input) -> Self {
-        let connector_1 = Connector1::new(context.network, &context.operator_public_key);
+    // Every peg-out graph commits to y with a key of its own, see
+    // commitments::generate_commit_y_public_key.
+    pub fn new(
+        context: &OperatorContext,
+        operator_input: Input,
+        commit_y_public_key: &WinternitzPublicKey,
+    ) -> Self {
+        let connector_1 = Connector1::new(
+            context.network,
+            &context.operator_public_key,
+            context.parameters.take1_timelock,
+        );
         let connector_a = ConnectorA::new(
             context.network,
             &context.operator_taproot_public_key,
             &context.n_of_n_taproot_public_key,
         );
-        let connector_b = ConnectorB::new(context.network, &context.n_of_n_taproot_public_key);
-
-        // TODO: Include commit y
-        // TODO: doesn't that mean we need to include an inscription for commit Y, so we need another TXN before this one?
-        let _input0 = TxIn {
-            previous_output: operator_input.outpoint,
-            script_sig: ScriptBuf::new(),
-            sequence: Sequence::MAX,
-            witness: Witness::default(),
-        };
+        let connector_b = ConnectorB::new(
+            context.network,
+            &context.n_of_n_taproot_public_key,
+            context.parameters.burn_timelock,
+        );
+
+        let connector_y = ConnectorY::new(
+            context.network,
+            &context.operator_taproot_public_key,
+            commit_y_public_key,
+        );
 
-        let available_input_amount = operator_input.amount - Amount::from_sat(FEE_AMOUNT);
+        // The operator funds the kick-off from the Commit Y address in a prior transaction, see
+        // CommitYFundingTransaction
+        let _input0 = connector_y
+            .generate_taproot_leaf_tx_in(0, &operator_input)
+            .unwrap();
+
+        let dust_amount = Amount::from_sat(context.parameters.dust_amount);
 
         let _output0 = TxOut {
-            value: Amount::from_sat(DUST_AMOUNT),
+            value: dust_amount,
             script_pubkey: connector_1.generate_address().script_pubkey(),
         };
 
         let _output1 = TxOut {
-            value: Amount::from_sat(DUST_AMOUNT),
+            value: dust_amount,
             script_pubkey: connector_a.generate_taproot_address().script_pubkey(),
         };
 
         let _output2 = TxOut {
-            value: available_input_amount - Amount::from_sat(DUST_AMOUNT) * 2,
+            value: operator_input.amount - dust_amount * 2 - Amount::from_sat(ANCHOR_AMOUNT),
             script_pubkey: connector_b.generate_taproot_address().script_pubkey(),
         };
 
+        // Output[3]: operator's anchor for bumping the fee of the kick-off
+        let _output3 = TxOut {
+            value: Amount::from_sat(ANCHOR_AMOUNT),
+            script_pubkey: generate_pay_to_pubkey_script_address(
+                context.network,
+                &context.operator_public_key,
+            )
+            .script_pubkey(),
+        };
+
         let mut this = KickOffTransaction {
             tx: Transaction {
                 version: bitcoin::transaction::Version(2),
                 lock_time: absolute::LockTime::ZERO,
                 input: vec![_input0],
-                output: vec![_output0, _output1, _output2],
+                output: vec![_output0, _output1, _output2, _output3],
             },
             prev_outs: vec![TxOut {
                 value: operator_input.amount,
-                script_pubkey: generate_pay_to_pubkey_script_address(
-                    context.network,
-                    &context.operator_public_key,
-                )
-                .script_pubkey(), // TODO: Add address of Commit y
+                script_pubkey: connector_y.generate_taproot_address().script_pubkey(),
             }],
-            prev_scripts: vec![generate_pay_to_pubkey_script(&context.operator_public_key)],
+            prev_scripts: vec![connector_y.generate_taproot_leaf_script(0).unwrap()],
+            connector_y,
         };
 
+        // The commitment to y is only added to the witness at kick-off
+        let weight = estimate_weight(&this) + Weight::from_wu(COMMIT_Y_WITNESS_LENGTH as u64);
+        this.tx.output[2].value -= calculate_fee(weight, context.parameters.presigned_fee_rate());
+
         this.sign_input0(context);
 
         this
     }
 
     fn sign_input0(&mut self, context: &OperatorContext) {
-        pre_sign_p2wsh_input(
+        pre_sign_taproot_input(
             self,
             context,
             0,
-            EcdsaSighashType::All,
+            TapSighashType::All,
+            self.connector_y.generate_taproot_spend_info(),
             &vec![&context.operator_keypair],
         );
     }
+
+    // Places the operator's commitment to y between the operator's signature and the leaf script.
+    pub fn add_commit_y(&mut self, commit_y: &WinternitzSignature) -> Result<(), BridgeError> {
+        verify_commit_y(&self.connector_y.commit_y_public_key, commit_y)
+            .map_err(|err| BridgeError::InvalidCommitment(format!("Commitment to y: {}", err)))?;
+        insert_taproot_witness_elements(&mut self.tx, 0, commit_y);
+
+        Ok(())
+    }
+
+    pub fn anchor_vout(&self) -> usize { 3 }
+
+    pub fn merge(&mut self, kick_off: &KickOffTransaction) -> Result<(), BridgeError> {
+        merge_transactions_with_inserted_witness_elements(&mut self.tx, &kick_off.tx)
+    }
+}
+
+// Reads the claim y the operator committed to from the witness of a kick-off transaction, for
+// example one fetched from the chain.
+pub fn extract_commit_y(
+    tx: &Transaction,
+    commit_y_public_key: &WinternitzPublicKey,
+) -> Result<CommitmentValue, BridgeError> {
+    let witness = &tx.input[0].witness;
+    // signature, commitment, leaf script and control block
+    if witness.len() < 4 {
+        return Err(BridgeError::InvalidCommitment(String::from(
+            "Kick-off does not carry a commitment to y",
+        )));
+    }
+    let commit_y: WinternitzSignature = witness
+        .iter()
+        .skip(1)
+        .take(witness.len() - 3)
+        .map(|element| element.to_vec())
+        .collect();
+
+    verify_commit_y(commit_y_public_key, &commit_y).map_err(BridgeError::InvalidCommitment)
 }
 
 impl BaseTransaction for KickOffTransaction {
@@ -2,13 +2,19 @@ pub mod assert;
 pub mod base;
 pub mod burn;
 pub mod challenge;
+pub mod commit_y_funding;
+pub mod cpfp;
 pub mod disprove;
+pub mod fee;
 pub mod kick_off;
 pub mod peg_in_confirm;
 pub mod peg_in_deposit;
 pub mod peg_in_refund;
 pub mod peg_out;
 pub mod pre_signed;
+pub mod pre_signed_musig2;
+pub mod psbt;
 pub mod signing;
+pub mod signing_musig2;
 pub mod take1;
 pub mod take2;
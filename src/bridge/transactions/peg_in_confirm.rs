@@ -1,14 +1,21 @@
-use bitcoin::{absolute, consensus, Amount, ScriptBuf, TapSighashType, Transaction, TxOut};
+use bitcoin::{
+    absolute, consensus, taproot::TaprootSpendInfo, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
         connectors::{connector::*, connector_0::Connector0, connector_z::ConnectorZ},
         contexts::{depositor::DepositorContext, verifier::VerifierContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
     signing::*,
 };
 
@@ -20,6 +27,8 @@ pub struct PegInConfirmTransaction {
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
     connector_z: ConnectorZ,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for PegInConfirmTransaction {
@@ -30,25 +39,53 @@ impl PreSignedTransaction for PegInConfirmTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_z.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for PegInConfirmTransaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0] }
 }
 
 impl PegInConfirmTransaction {
     pub fn new(context: &DepositorContext, evm_address: &str, input0: Input) -> Self {
-        let connector_0 = Connector0::new(context.network, &context.n_of_n_public_key);
+        let connector_0 = Connector0::new(context.network, &context.n_of_n_taproot_public_key);
         let connector_z = ConnectorZ::new(
             context.network,
             evm_address,
             &context.depositor_taproot_public_key,
             &context.n_of_n_taproot_public_key,
+            context.parameters.peg_in_refund_timelock,
         );
 
-        let _input0 = connector_z.generate_taproot_leaf_tx_in(1, &input0);
+        let _input0 = connector_z.generate_taproot_leaf_tx_in(1, &input0).unwrap();
 
-        let total_output_amount = input0.amount - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
-            script_pubkey: connector_0.generate_address().script_pubkey(),
+            script_pubkey: connector_0.generate_taproot_address().script_pubkey(),
         };
 
         let mut this = PegInConfirmTransaction {
@@ -62,10 +99,14 @@ impl PegInConfirmTransaction {
                 value: input0.amount,
                 script_pubkey: connector_z.generate_taproot_address().script_pubkey(),
             }],
-            prev_scripts: vec![connector_z.generate_taproot_leaf_script(1)],
+            prev_scripts: vec![connector_z.generate_taproot_leaf_script(1).unwrap()],
             connector_z,
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
         };
 
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.push_depositor_signature_input0(context);
 
         this
@@ -84,32 +125,61 @@ impl PegInConfirmTransaction {
         );
     }
 
-    fn push_n_of_n_signature_input0(&mut self, context: &VerifierContext) {
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
         let input_index = 0;
-        push_taproot_leaf_signature_to_witness(
+        pre_sign_musig2_taproot_input(
+            self,
             context,
-            &mut self.tx,
-            &self.prev_outs,
             input_index,
             TapSighashType::All,
-            &self.prev_scripts[input_index],
-            &context.n_of_n_keypair,
-        );
+            secret_nonces,
+        )?;
+
+        // The aggregated n-of-n signature follows the depositor signature in the witness
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::All,
+            self.connector_z.generate_taproot_spend_info(),
+        )
     }
 
-    fn finalize_input0(&mut self) {
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
         let input_index = 0;
-        push_taproot_leaf_script_and_control_block_to_witness(
-            &mut self.tx,
-            input_index,
-            &self.connector_z.generate_taproot_spend_info(),
-            &self.prev_scripts[input_index],
-        );
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
+    }
+
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)
+    }
+
+    // The witness starts with the depositor signature, followed by the aggregated n-of-n signature,
+    // the leaf script and the control block once the verifiers pre-signed.
+    pub fn has_depositor_signature(&self) -> bool {
+        let witness_len = self.tx.input[0].witness.len();
+        match is_musig2_input_finalized(self, 0) {
+            true => witness_len == 4,
+            false => witness_len == 1,
+        }
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) {
-        self.push_n_of_n_signature_input0(context);
-        self.finalize_input0();
+    pub fn merge(&mut self, peg_in_confirm: &PegInConfirmTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &peg_in_confirm.tx)?;
+        merge_musig2_nonces_and_signatures(self, peg_in_confirm)
     }
 }
 
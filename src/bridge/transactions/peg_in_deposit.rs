@@ -1,14 +1,15 @@
-use bitcoin::{absolute, consensus, Amount, EcdsaSighashType, ScriptBuf, Transaction, TxOut};
+use bitcoin::{absolute, consensus, EcdsaSighashType, ScriptBuf, Transaction, TxOut};
 use serde::{Deserialize, Serialize};
 
 use super::{
     super::{
         connectors::{connector::*, connector_z::ConnectorZ},
         contexts::depositor::DepositorContext,
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
 };
 
@@ -38,11 +39,12 @@ impl PegInDepositTransaction {
             evm_address,
             &context.depositor_taproot_public_key,
             &context.n_of_n_taproot_public_key,
+            context.parameters.peg_in_refund_timelock,
         );
 
         let _input0 = generate_default_tx_in(&input0);
 
-        let total_output_amount = input0.amount - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
@@ -67,6 +69,8 @@ impl PegInDepositTransaction {
             prev_scripts: vec![generate_pay_to_pubkey_script(&context.depositor_public_key)],
         };
 
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.sign_input0(context);
 
         this
@@ -81,6 +85,10 @@ impl PegInDepositTransaction {
             &vec![&context.depositor_keypair],
         );
     }
+
+    pub fn merge(&mut self, peg_in_deposit: &PegInDepositTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &peg_in_deposit.tx)
+    }
 }
 
 impl BaseTransaction for PegInDepositTransaction {
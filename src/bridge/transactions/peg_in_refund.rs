@@ -1,14 +1,17 @@
-use bitcoin::{absolute, consensus, Amount, ScriptBuf, TapSighashType, Transaction, TxOut};
+use bitcoin::{
+    absolute, consensus, taproot::TaprootSpendInfo, ScriptBuf, TapSighashType, Transaction, TxOut,
+};
 use serde::{Deserialize, Serialize};
 
 use super::{
     super::{
         connectors::{connector::*, connector_z::ConnectorZ},
         contexts::depositor::DepositorContext,
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
 };
 
@@ -30,6 +33,13 @@ impl PreSignedTransaction for PegInRefundTransaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_z.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
 }
 
 impl PegInRefundTransaction {
@@ -39,11 +49,12 @@ impl PegInRefundTransaction {
             evm_address,
             &context.depositor_taproot_public_key,
             &context.n_of_n_taproot_public_key,
+            context.parameters.peg_in_refund_timelock,
         );
 
-        let _input0 = connector_z.generate_taproot_leaf_tx_in(0, &input0);
+        let _input0 = connector_z.generate_taproot_leaf_tx_in(0, &input0).unwrap();
 
-        let total_output_amount = input0.amount - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
@@ -65,10 +76,12 @@ impl PegInRefundTransaction {
                 value: input0.amount,
                 script_pubkey: connector_z.generate_taproot_address().script_pubkey(),
             }],
-            prev_scripts: vec![connector_z.generate_taproot_leaf_script(0)],
+            prev_scripts: vec![connector_z.generate_taproot_leaf_script(0).unwrap()],
             connector_z,
         };
 
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.sign_input0(context);
 
         this
@@ -84,6 +97,10 @@ impl PegInRefundTransaction {
             &vec![&context.depositor_keypair],
         );
     }
+
+    pub fn merge(&mut self, peg_in_refund: &PegInRefundTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &peg_in_refund.tx)
+    }
 }
 
 impl BaseTransaction for PegInRefundTransaction {
@@ -1,16 +1,16 @@
 use bitcoin::{
-    absolute, consensus, Amount, EcdsaSighashType, PublicKey, ScriptBuf, Sequence, Transaction,
-    TxIn, TxOut, Witness,
+    absolute, consensus, EcdsaSighashType, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
 };
 use serde::{Deserialize, Serialize};
 
 use super::{
-    super::{contexts::operator::OperatorContext, graphs::base::FEE_AMOUNT, scripts::*},
+    super::{contexts::operator::OperatorContext, error::BridgeError, scripts::*},
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
 };
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct PegOutTransaction {
     #[serde(with = "consensus::serde::With::<consensus::serde::Hex>")]
     tx: Transaction,
@@ -30,13 +30,13 @@ impl PreSignedTransaction for PegOutTransaction {
 }
 
 impl PegOutTransaction {
+    // The operator fronts the peg-out to the destination of the withdrawer from its own funds and
+    // is reimbursed by take1 or take2.
     pub fn new(
         context: &OperatorContext,
-        withdrawer_public_key: &PublicKey,
+        destination_script_pubkey: &ScriptBuf,
         input0: Input,
-        input1: Input,
     ) -> Self {
-        // TODO QUESTION Why do we need this input from Bob?
         let _input0 = TxIn {
             previous_output: input0.outpoint,
             script_sig: ScriptBuf::new(),
@@ -44,69 +44,51 @@ impl PegOutTransaction {
             witness: Witness::default(),
         };
 
-        let _input1 = TxIn {
-            previous_output: input1.outpoint,
-            script_sig: ScriptBuf::new(),
-            sequence: Sequence::MAX,
-            witness: Witness::default(),
-        };
-
-        let total_output_amount = input0.amount + input1.amount - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
-            script_pubkey: generate_pay_to_pubkey_script_address(
-                context.network,
-                &withdrawer_public_key,
-            )
-            .script_pubkey(),
+            script_pubkey: destination_script_pubkey.clone(),
         };
 
         let mut this = PegOutTransaction {
             tx: Transaction {
                 version: bitcoin::transaction::Version(2),
                 lock_time: absolute::LockTime::ZERO,
-                input: vec![_input0, _input1],
+                input: vec![_input0],
                 output: vec![_output0],
             },
-            prev_outs: vec![
-                TxOut {
-                    value: input0.amount,
-                    script_pubkey: generate_pay_to_pubkey_script_address(
-                        context.network,
-                        &withdrawer_public_key,
-                    )
-                    .script_pubkey(),
-                },
-                TxOut {
-                    value: input1.amount,
-                    script_pubkey: generate_pay_to_pubkey_script_address(
-                        context.network,
-                        &context.operator_public_key,
-                    )
-                    .script_pubkey(),
-                },
-            ],
-            prev_scripts: vec![
-                generate_pay_to_pubkey_script(&withdrawer_public_key),
-                generate_pay_to_pubkey_script(&context.operator_public_key),
-            ],
+            prev_outs: vec![TxOut {
+                value: input0.amount,
+                script_pubkey: generate_pay_to_pubkey_script_address(
+                    context.network,
+                    &context.operator_public_key,
+                )
+                .script_pubkey(),
+            }],
+            prev_scripts: vec![generate_pay_to_pubkey_script(&context.operator_public_key)],
         };
 
-        this.sign_input1(context);
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
+        this.sign_input0(context);
 
         this
     }
 
-    fn sign_input1(&mut self, context: &OperatorContext) {
+    fn sign_input0(&mut self, context: &OperatorContext) {
         pre_sign_p2wsh_input(
             self,
             context,
-            1,
+            0,
             EcdsaSighashType::All,
             &vec![&context.operator_keypair],
         );
     }
+
+    pub fn merge(&mut self, peg_out: &PegOutTransaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &peg_out.tx)
+    }
 }
 
 impl BaseTransaction for PegOutTransaction {
@@ -1,11 +1,16 @@
 use bitcoin::{
-    key::Keypair, taproot::TaprootSpendInfo, EcdsaSighashType, PublicKey, ScriptBuf,
-    TapSighashType, Transaction, TxOut,
+    key::{Keypair, Secp256k1},
+    taproot::TaprootSpendInfo,
+    EcdsaSighashType, PublicKey, ScriptBuf, TapSighashType, Transaction, TxOut,
 };
 
 use super::{
     super::contexts::base::BaseContext,
-    signing::{populate_p2wpkh_witness, populate_p2wsh_witness, populate_taproot_input_witness},
+    signing::{
+        populate_p2wpkh_witness, populate_p2wsh_witness, populate_taproot_input_witness,
+        verify_p2wpkh_witness, verify_p2wsh_witness, verify_partial_taproot_input_witness,
+        verify_taproot_input_witness,
+    },
 };
 
 pub trait PreSignedTransaction {
@@ -13,6 +18,8 @@ pub trait PreSignedTransaction {
     fn tx_mut(&mut self) -> &mut Transaction;
     fn prev_outs(&self) -> &Vec<TxOut>;
     fn prev_scripts(&self) -> &Vec<ScriptBuf>;
+    // Spend info of the taproot output spent by the input, None for all other inputs
+    fn taproot_spend_info(&self, _input_index: usize) -> Option<TaprootSpendInfo> { None }
 }
 
 pub fn pre_sign_p2wsh_input<T: PreSignedTransaction>(
@@ -79,3 +86,42 @@ pub fn pre_sign_taproot_input<T: PreSignedTransaction>(
         keypairs,
     );
 }
+
+// Checks that every input that already carries a witness spends the expected script and is signed
+// by every key of it. Inputs that are added when the transaction is executed (and have no entry in
+// prev_scripts) are not checked. Taproot inputs still waiting for the aggregated n-of-n signature
+// may only carry signatures by keys of the expected script so far.
+pub fn verify_pre_signed_input_witnesses<T: PreSignedTransaction + ?Sized>(
+    tx: &T,
+) -> Result<(), String> {
+    let secp = Secp256k1::verification_only();
+
+    for input_index in 0..tx.prev_scripts().len().min(tx.tx().input.len()) {
+        let witness = &tx.tx().input[input_index].witness;
+        if witness.is_empty() {
+            continue;
+        }
+
+        let prev_out = &tx.prev_outs()[input_index];
+        let script = &tx.prev_scripts()[input_index];
+        if prev_out.script_pubkey.is_p2tr() {
+            if witness.len() >= 2 && witness[witness.len() - 2] == *script.as_bytes() {
+                verify_taproot_input_witness(&secp, tx.tx(), tx.prev_outs(), input_index, script)?;
+            } else {
+                verify_partial_taproot_input_witness(
+                    &secp,
+                    tx.tx(),
+                    tx.prev_outs(),
+                    input_index,
+                    script,
+                )?;
+            }
+        } else if prev_out.script_pubkey.is_p2wpkh() {
+            verify_p2wpkh_witness(&secp, tx.tx(), input_index, prev_out)?;
+        } else {
+            verify_p2wsh_witness(&secp, tx.tx(), input_index, script, prev_out)?;
+        }
+    }
+
+    Ok(())
+}
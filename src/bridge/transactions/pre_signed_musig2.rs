@@ -0,0 +1,267 @@
+use bitcoin::{taproot::TaprootSpendInfo, PublicKey, TapSighashType};
+use musig2::{PartialSignature, PubNonce, SecNonce};
+use std::collections::HashMap;
+
+use super::{
+    super::{contexts::verifier::VerifierContext, error::BridgeError},
+    pre_signed::PreSignedTransaction,
+    signing::push_taproot_leaf_script_and_control_block_to_witness,
+    signing_musig2::{
+        aggregate_taproot_leaf_partial_signatures, generate_nonce,
+        generate_taproot_leaf_partial_signature,
+    },
+};
+
+// Inputs spent with the n-of-n key are signed in two rounds: every verifier first publishes a
+// public nonce per input, then a partial signature per input once the nonces of all n verifiers
+// are known. The partial signatures are aggregated into the final witness once all are present.
+pub trait PreSignedMusig2Transaction: PreSignedTransaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>>;
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>>;
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>>;
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>>;
+    // Indices of the inputs spent with the n-of-n key
+    fn musig2_input_indices(&self) -> Vec<usize>;
+}
+
+pub fn push_nonce<T: PreSignedMusig2Transaction>(
+    tx: &mut T,
+    context: &VerifierContext,
+    input_index: usize,
+) -> SecNonce {
+    let secret_nonce = generate_nonce(context);
+    tx.musig2_nonces_mut()
+        .entry(input_index)
+        .or_default()
+        .insert(context.verifier_public_key, secret_nonce.public_nonce());
+
+    secret_nonce
+}
+
+// Consumes the secret nonce of the input, a nonce is never used for a second signature. An input
+// the verifier has signed before is not signed again, even if its signature got lost.
+pub fn pre_sign_musig2_taproot_input<T: PreSignedMusig2Transaction>(
+    tx: &mut T,
+    context: &VerifierContext,
+    input_index: usize,
+    sighash_type: TapSighashType,
+    secret_nonces: &mut HashMap<usize, SecNonce>,
+) -> Result<(), BridgeError> {
+    let has_signed = tx
+        .musig2_signatures()
+        .get(&input_index)
+        .is_some_and(|signatures| signatures.contains_key(&context.verifier_public_key));
+    if has_signed {
+        secret_nonces.remove(&input_index);
+        return Ok(());
+    }
+
+    let public_nonces = get_public_nonces(&*tx, context, input_index)?;
+    let secret_nonce = secret_nonces
+        .remove(&input_index)
+        .ok_or(BridgeError::PreSigning(format!(
+            "No unused secret nonce for input {}",
+            input_index
+        )))?;
+
+    let partial_signature = generate_taproot_leaf_partial_signature(
+        context,
+        tx.tx(),
+        tx.prev_outs(),
+        input_index,
+        sighash_type,
+        &tx.prev_scripts()[input_index],
+        secret_nonce,
+        &public_nonces,
+    )
+    .map_err(BridgeError::PreSigning)?;
+
+    tx.musig2_signatures_mut()
+        .entry(input_index)
+        .or_default()
+        .insert(context.verifier_public_key, partial_signature);
+
+    Ok(())
+}
+
+// Does nothing until every verifier signed the input, or if the input has already been finalized.
+pub fn finalize_musig2_taproot_input<T: PreSignedMusig2Transaction>(
+    tx: &mut T,
+    context: &VerifierContext,
+    input_index: usize,
+    sighash_type: TapSighashType,
+    taproot_spend_info: TaprootSpendInfo,
+) -> Result<(), BridgeError> {
+    if is_musig2_input_finalized(&*tx, input_index) {
+        return Ok(());
+    }
+
+    let partial_signatures = tx.musig2_signatures().get(&input_index);
+    if partial_signatures.is_none()
+        || context
+            .n_of_n_public_keys
+            .iter()
+            .any(|public_key| !partial_signatures.unwrap().contains_key(public_key))
+    {
+        return Ok(());
+    }
+    let partial_signatures: Vec<PartialSignature> = context
+        .n_of_n_public_keys
+        .iter()
+        .map(|public_key| partial_signatures.unwrap()[public_key])
+        .collect();
+
+    let public_nonces = get_public_nonces(&*tx, context, input_index)?;
+    let script = tx.prev_scripts()[input_index].clone();
+    let signature = aggregate_taproot_leaf_partial_signatures(
+        context,
+        tx.tx(),
+        tx.prev_outs(),
+        input_index,
+        sighash_type,
+        &script,
+        &public_nonces,
+        &partial_signatures,
+    )
+    .map_err(BridgeError::PreSigning)?;
+
+    tx.tx_mut().input[input_index]
+        .witness
+        .push(signature.to_vec());
+    push_taproot_leaf_script_and_control_block_to_witness(
+        tx.tx_mut(),
+        input_index,
+        &taproot_spend_info,
+        &script,
+    );
+
+    Ok(())
+}
+
+fn get_public_nonces<T: PreSignedMusig2Transaction + ?Sized>(
+    tx: &T,
+    context: &VerifierContext,
+    input_index: usize,
+) -> Result<Vec<PubNonce>, BridgeError> {
+    let nonces = tx.musig2_nonces().get(&input_index);
+    context
+        .n_of_n_public_keys
+        .iter()
+        .map(|public_key| {
+            nonces
+                .and_then(|nonces| nonces.get(public_key))
+                .cloned()
+                .ok_or(BridgeError::PreSigning(format!(
+                    "Missing nonce of verifier {} for input {}",
+                    public_key, input_index
+                )))
+        })
+        .collect()
+}
+
+pub fn is_musig2_input_finalized<T: PreSignedMusig2Transaction + ?Sized>(
+    tx: &T,
+    input_index: usize,
+) -> bool {
+    let witness = &tx.tx().input[input_index].witness;
+    witness.len() >= 2 && witness[witness.len() - 2] == *tx.prev_scripts()[input_index].as_bytes()
+}
+
+pub fn get_missing_nonces(
+    tx: &dyn PreSignedMusig2Transaction,
+    input_index: usize,
+    public_keys: &[PublicKey],
+) -> Vec<PublicKey> {
+    let nonces = tx.musig2_nonces().get(&input_index);
+    public_keys
+        .iter()
+        .filter(|public_key| nonces.is_none() || !nonces.unwrap().contains_key(public_key))
+        .copied()
+        .collect()
+}
+
+pub fn get_missing_signatures(
+    tx: &dyn PreSignedMusig2Transaction,
+    input_index: usize,
+    public_keys: &[PublicKey],
+) -> Vec<PublicKey> {
+    let signatures = tx.musig2_signatures().get(&input_index);
+    public_keys
+        .iter()
+        .filter(|public_key| signatures.is_none() || !signatures.unwrap().contains_key(public_key))
+        .copied()
+        .collect()
+}
+
+pub fn has_nonces_of(tx: &dyn PreSignedMusig2Transaction, context: &VerifierContext) -> bool {
+    tx.musig2_input_indices().iter().all(|input_index| {
+        get_missing_nonces(tx, *input_index, &[context.verifier_public_key]).is_empty()
+    })
+}
+
+pub fn has_all_nonces(tx: &dyn PreSignedMusig2Transaction, context: &VerifierContext) -> bool {
+    tx.musig2_input_indices().iter().all(|input_index| {
+        get_missing_nonces(tx, *input_index, &context.n_of_n_public_keys).is_empty()
+    })
+}
+
+pub fn has_signatures_of(tx: &dyn PreSignedMusig2Transaction, context: &VerifierContext) -> bool {
+    tx.musig2_input_indices().iter().all(|input_index| {
+        get_missing_signatures(tx, *input_index, &[context.verifier_public_key]).is_empty()
+    })
+}
+
+pub fn has_all_signatures(tx: &dyn PreSignedMusig2Transaction, context: &VerifierContext) -> bool {
+    tx.musig2_input_indices().iter().all(|input_index| {
+        get_missing_signatures(tx, *input_index, &context.n_of_n_public_keys).is_empty()
+    })
+}
+
+pub fn merge_musig2_nonces_and_signatures<T: PreSignedMusig2Transaction>(
+    destination: &mut T,
+    source: &T,
+) -> Result<(), BridgeError> {
+    for (input_index, source_nonces) in source.musig2_nonces() {
+        let destination_nonces = destination
+            .musig2_nonces_mut()
+            .entry(*input_index)
+            .or_default();
+        for (public_key, nonce) in source_nonces {
+            match destination_nonces.get(public_key) {
+                Some(destination_nonce) if destination_nonce != nonce => {
+                    return Err(BridgeError::GraphMerge(format!(
+                        "Conflicting nonces of verifier {} at input {}",
+                        public_key, input_index
+                    )));
+                }
+                _ => {
+                    destination_nonces.insert(*public_key, nonce.clone());
+                }
+            }
+        }
+    }
+
+    for (input_index, source_signatures) in source.musig2_signatures() {
+        let destination_signatures = destination
+            .musig2_signatures_mut()
+            .entry(*input_index)
+            .or_default();
+        for (public_key, signature) in source_signatures {
+            match destination_signatures.get(public_key) {
+                Some(destination_signature) if destination_signature != signature => {
+                    return Err(BridgeError::GraphMerge(format!(
+                        "Conflicting partial signatures of verifier {} at input {}",
+                        public_key, input_index
+                    )));
+                }
+                _ => {
+                    destination_signatures.insert(*public_key, *signature);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
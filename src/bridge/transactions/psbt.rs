@@ -0,0 +1,187 @@
+use bitcoin::{
+    psbt::Input as PsbtInput,
+    taproot::{ControlBlock, LeafVersion},
+    Psbt, ScriptBuf, TapLeafHash, Witness,
+};
+
+use super::{
+    super::error::BridgeError,
+    pre_signed::{verify_pre_signed_input_witnesses, PreSignedTransaction},
+    signing::get_script_public_keys,
+};
+
+// BIP-174 PSBT of the transaction for an external signer. Every input spending a known output
+// carries its witness UTXO, p2wsh inputs their witness script and taproot inputs the leaf script
+// with its control block (BIP-371). Inputs that already carry a witness are exported finalized.
+pub fn export_psbt<T: PreSignedTransaction + ?Sized>(tx: &T) -> Psbt {
+    let mut unsigned_tx = tx.tx().clone();
+    for input in unsigned_tx.input.iter_mut() {
+        input.script_sig = ScriptBuf::new();
+        input.witness = Witness::default();
+    }
+    let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+
+    for (input_index, psbt_input) in psbt.inputs.iter_mut().enumerate() {
+        let witness = &tx.tx().input[input_index].witness;
+        if !witness.is_empty() {
+            psbt_input.final_script_witness = Some(witness.clone());
+        }
+
+        // inputs added when the transaction is executed spend outputs unknown to the transaction
+        if input_index >= tx.prev_outs().len() || input_index >= tx.prev_scripts().len() {
+            continue;
+        }
+        let prev_out = &tx.prev_outs()[input_index];
+        let script = &tx.prev_scripts()[input_index];
+        psbt_input.witness_utxo = Some(prev_out.clone());
+
+        if prev_out.script_pubkey.is_p2tr() {
+            let leaf = (script.clone(), LeafVersion::TapScript);
+            if let Some(spend_info) = tx.taproot_spend_info(input_index) {
+                if let Some(control_block) = spend_info.control_block(&leaf) {
+                    psbt_input.tap_internal_key = Some(spend_info.internal_key());
+                    psbt_input.tap_merkle_root = spend_info.merkle_root();
+                    psbt_input.tap_scripts.insert(control_block, leaf);
+                }
+            }
+        } else if !prev_out.script_pubkey.is_p2wpkh() {
+            psbt_input.witness_script = Some(script.clone());
+        }
+    }
+
+    psbt
+}
+
+// Takes over the signatures of a PSBT signed by an external signer. Inputs of the transaction
+// that already carry a witness are left untouched, inputs of the PSBT are either finalized or
+// signed by every key of the spent script. Every imported witness is verified against the spent
+// output, the transaction is left unchanged if one of them is invalid.
+pub fn import_psbt<T: PreSignedTransaction + ?Sized>(
+    tx: &mut T,
+    psbt: &Psbt,
+) -> Result<(), BridgeError> {
+    if psbt.unsigned_tx.compute_txid() != tx.tx().compute_txid() {
+        return Err(BridgeError::Psbt(format!(
+            "PSBT of transaction {} does not match transaction {}",
+            psbt.unsigned_tx.compute_txid(),
+            tx.tx().compute_txid()
+        )));
+    }
+
+    let mut witnesses = Vec::new();
+    for (input_index, psbt_input) in psbt.inputs.iter().enumerate() {
+        if !tx.tx().input[input_index].witness.is_empty() {
+            continue;
+        }
+
+        let witness = match &psbt_input.final_script_witness {
+            Some(witness) => Some(witness.clone()),
+            None if input_index < tx.prev_scripts().len() => {
+                generate_witness(&*tx, input_index, psbt_input).map_err(BridgeError::Psbt)?
+            }
+            None => None,
+        };
+        if let Some(witness) = witness {
+            witnesses.push((input_index, witness));
+        }
+    }
+
+    for (input_index, witness) in witnesses.iter() {
+        tx.tx_mut().input[*input_index].witness = witness.clone();
+    }
+    if let Err(err) = verify_pre_signed_input_witnesses(&*tx) {
+        for (input_index, _) in witnesses.iter() {
+            tx.tx_mut().input[*input_index].witness = Witness::default();
+        }
+        return Err(BridgeError::Psbt(err));
+    }
+
+    Ok(())
+}
+
+// Returns None if the input has not been signed at all.
+fn generate_witness<T: PreSignedTransaction + ?Sized>(
+    tx: &T,
+    input_index: usize,
+    psbt_input: &PsbtInput,
+) -> Result<Option<Witness>, String> {
+    let prev_out = &tx.prev_outs()[input_index];
+    let script = &tx.prev_scripts()[input_index];
+    let (public_keys, taproot_public_keys) = get_script_public_keys(script);
+    let mut witness = Witness::new();
+
+    if prev_out.script_pubkey.is_p2tr() {
+        // The first key of the script checks the signature on top of the stack
+        let leaf_hash = TapLeafHash::from_script(script, LeafVersion::TapScript);
+        let signatures: Vec<_> = taproot_public_keys
+            .iter()
+            .rev()
+            .filter_map(|public_key| psbt_input.tap_script_sigs.get(&(*public_key, leaf_hash)))
+            .collect();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+        if signatures.len() < taproot_public_keys.len() {
+            return Err(format!("Input {} is missing signatures", input_index));
+        }
+
+        let control_block = psbt_input
+            .tap_scripts
+            .iter()
+            .find(|(_, (leaf_script, _))| leaf_script == script)
+            .map(|(control_block, _)| control_block.clone())
+            .or_else(|| find_control_block(tx, input_index, script))
+            .ok_or(format!(
+                "Input {} is missing the control block",
+                input_index
+            ))?;
+
+        for signature in signatures {
+            witness.push(signature.to_vec());
+        }
+        witness.push(script.as_bytes());
+        witness.push(control_block.serialize());
+    } else if prev_out.script_pubkey.is_p2wpkh() {
+        // Only the signature of the key the output pays to can spend it
+        let signature = psbt_input.partial_sigs.iter().find(|(public_key, _)| {
+            public_key
+                .wpubkey_hash()
+                .is_ok_and(|hash| ScriptBuf::new_p2wpkh(&hash) == prev_out.script_pubkey)
+        });
+        match signature {
+            Some((public_key, signature)) => {
+                witness.push_ecdsa_signature(signature);
+                witness.push(public_key.to_bytes());
+            }
+            None => return Ok(None),
+        }
+    } else {
+        let signatures: Vec<_> = public_keys
+            .iter()
+            .rev()
+            .filter_map(|public_key| psbt_input.partial_sigs.get(public_key))
+            .collect();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+        if signatures.len() < public_keys.len() {
+            return Err(format!("Input {} is missing signatures", input_index));
+        }
+
+        for signature in signatures {
+            witness.push_ecdsa_signature(signature);
+        }
+        witness.push(script.as_bytes());
+    }
+
+    Ok(Some(witness))
+}
+
+fn find_control_block<T: PreSignedTransaction + ?Sized>(
+    tx: &T,
+    input_index: usize,
+    script: &ScriptBuf,
+) -> Option<ControlBlock> {
+    tx.taproot_spend_info(input_index)
+        .and_then(|spend_info| spend_info.control_block(&(script.clone(), LeafVersion::TapScript)))
+}
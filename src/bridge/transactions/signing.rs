@@ -1,12 +1,13 @@
 use bitcoin::{
-    key::Keypair,
-    secp256k1::Message,
+    key::{Keypair, Secp256k1},
+    script::Instruction,
+    secp256k1::{Message, Verification},
     sighash::{Prevouts, SighashCache},
-    taproot::{LeafVersion, TaprootSpendInfo},
+    taproot::{ControlBlock, LeafVersion, TaprootSpendInfo},
     Amount, EcdsaSighashType, PublicKey, Script, ScriptBuf, TapLeafHash, TapSighashType,
-    Transaction, TxOut,
+    Transaction, TxOut, XOnlyPublicKey,
 };
-use std::borrow::Borrow;
+use std::{borrow::Borrow, fmt::Display};
 
 use super::super::{contexts::base::BaseContext, scripts::generate_p2wpkh_address};
 
@@ -282,3 +283,271 @@ pub fn populate_taproot_input_witness(
         script,
     );
 }
+
+// Public keys pushed by the script, in the order they appear in it
+pub fn get_script_public_keys(script: &Script) -> (Vec<PublicKey>, Vec<XOnlyPublicKey>) {
+    let mut public_keys: Vec<PublicKey> = vec![];
+    let mut taproot_public_keys: Vec<XOnlyPublicKey> = vec![];
+    for instruction in script.instructions().flatten() {
+        if let Instruction::PushBytes(bytes) = instruction {
+            let bytes = bytes.as_bytes();
+            if bytes.len() == 32 {
+                if let Ok(public_key) = XOnlyPublicKey::from_slice(bytes) {
+                    taproot_public_keys.push(public_key);
+                }
+            } else if let Ok(public_key) = PublicKey::from_slice(bytes) {
+                public_keys.push(public_key);
+            }
+        }
+    }
+
+    (public_keys, taproot_public_keys)
+}
+
+pub fn verify_p2wsh_witness<C: Verification>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    input_index: usize,
+    script: &Script,
+    prev_out: &TxOut,
+) -> Result<(), String> {
+    let witness = &tx.input[input_index].witness;
+    let witness_script = witness.last();
+    if witness_script.is_none() || witness_script.unwrap() != script.as_bytes() {
+        return Err(format!(
+            "Input {} has an unexpected witness script",
+            input_index
+        ));
+    }
+    if prev_out.script_pubkey != ScriptBuf::new_p2wsh(&script.wscript_hash()) {
+        return Err(format!(
+            "Input {} witness script does not match the spent output",
+            input_index
+        ));
+    }
+
+    // every key of the spent script has to sign, any other signature is rejected
+    let (public_keys, _) = get_script_public_keys(script);
+    let mut signed_public_keys = vec![];
+    for element in witness.iter().take(witness.len() - 1) {
+        let signature = bitcoin::ecdsa::Signature::from_slice(element);
+        if signature.is_err() {
+            continue; // not a signature
+        }
+        let signature = signature.unwrap();
+
+        let sighash = SighashCache::new(tx)
+            .p2wsh_signature_hash(input_index, script, prev_out.value, signature.sighash_type)
+            .map_err(|err| format!("Failed to construct sighash: {}", err))?;
+        let message = Message::from(sighash);
+
+        let public_key = public_keys.iter().find(|public_key| {
+            secp.verify_ecdsa(&message, &signature.signature, &public_key.inner)
+                .is_ok()
+        });
+        if public_key.is_none() {
+            return Err(format!("Input {} has an invalid signature", input_index));
+        }
+        signed_public_keys.push(public_key.unwrap());
+    }
+
+    verify_signed_by_all(input_index, &public_keys, &signed_public_keys)
+}
+
+pub fn verify_p2wpkh_witness<C: Verification>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    input_index: usize,
+    prev_out: &TxOut,
+) -> Result<(), String> {
+    let witness = &tx.input[input_index].witness;
+    if witness.len() != 2 {
+        return Err(format!("Input {} has an unexpected witness", input_index));
+    }
+
+    let signature = bitcoin::ecdsa::Signature::from_slice(&witness[0])
+        .map_err(|err| format!("Input {} has an invalid signature: {}", input_index, err))?;
+    let public_key = PublicKey::from_slice(&witness[1])
+        .map_err(|err| format!("Input {} has an invalid public key: {}", input_index, err))?;
+    let wpubkey_hash = public_key
+        .wpubkey_hash()
+        .map_err(|_| format!("Input {} has an uncompressed public key", input_index))?;
+    if prev_out.script_pubkey != ScriptBuf::new_p2wpkh(&wpubkey_hash) {
+        return Err(format!(
+            "Input {} public key does not match the spent output",
+            input_index
+        ));
+    }
+
+    let sighash = SighashCache::new(tx)
+        .p2wpkh_signature_hash(
+            input_index,
+            &prev_out.script_pubkey,
+            prev_out.value,
+            signature.sighash_type,
+        )
+        .map_err(|err| format!("Failed to construct sighash: {}", err))?;
+
+    secp.verify_ecdsa(
+        &Message::from(sighash),
+        &signature.signature,
+        &public_key.inner,
+    )
+    .map_err(|_| format!("Input {} has an invalid signature", input_index))
+}
+
+pub fn verify_taproot_input_witness<C: Verification>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    script: &Script,
+) -> Result<(), String> {
+    let witness = &tx.input[input_index].witness;
+    if witness.len() < 2 || witness[witness.len() - 2] != *script.as_bytes() {
+        return Err(format!(
+            "Input {} has an unexpected leaf script",
+            input_index
+        ));
+    }
+
+    if !prevouts[input_index].script_pubkey.is_p2tr() {
+        return Err(format!(
+            "Input {} does not spend a taproot output",
+            input_index
+        ));
+    }
+    let output_key =
+        XOnlyPublicKey::from_slice(&prevouts[input_index].script_pubkey.as_bytes()[2..])
+            .map_err(|_| format!("Input {} spends an invalid taproot output", input_index))?;
+    let control_block = ControlBlock::decode(witness.last().unwrap()).map_err(|err| {
+        format!(
+            "Input {} has an invalid control block: {}",
+            input_index, err
+        )
+    })?;
+    if !control_block.verify_taproot_commitment(secp, output_key, script) {
+        return Err(format!(
+            "Input {} leaf script is not committed to by the spent output",
+            input_index
+        ));
+    }
+
+    // every key of the spent script has to sign, any other signature is rejected
+    let (_, taproot_public_keys) = get_script_public_keys(script);
+    let elements: Vec<&[u8]> = witness.iter().take(witness.len() - 2).collect();
+    let signed_public_keys = get_taproot_leaf_signers(
+        secp,
+        tx,
+        prevouts,
+        input_index,
+        script,
+        &taproot_public_keys,
+        &elements,
+    )?;
+
+    verify_signed_by_all(input_index, &taproot_public_keys, &signed_public_keys)
+}
+
+// Checks the witness of a taproot input that the remaining signatures, the leaf script and the
+// control block are not pushed to yet, like an input waiting for the aggregated n-of-n signature.
+// Every element has to be a signature by a key of the spent script.
+pub fn verify_partial_taproot_input_witness<C: Verification>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    script: &Script,
+) -> Result<(), String> {
+    let (_, taproot_public_keys) = get_script_public_keys(script);
+    let elements: Vec<&[u8]> = tx.input[input_index].witness.iter().collect();
+    let signed_public_keys = get_taproot_leaf_signers(
+        secp,
+        tx,
+        prevouts,
+        input_index,
+        script,
+        &taproot_public_keys,
+        &elements,
+    )?;
+    if signed_public_keys.len() != elements.len() {
+        return Err(format!(
+            "Input {} has an unexpected witness element",
+            input_index
+        ));
+    }
+
+    Ok(())
+}
+
+// Keys of the leaf script the signatures among the witness elements are valid for. Elements that
+// are not signatures, like commitments, are skipped.
+fn get_taproot_leaf_signers<'a, C: Verification>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    script: &Script,
+    taproot_public_keys: &'a [XOnlyPublicKey],
+    elements: &[&[u8]],
+) -> Result<Vec<&'a XOnlyPublicKey>, String> {
+    let mut signed_public_keys = vec![];
+    let leaf_hash = TapLeafHash::from_script(script, LeafVersion::TapScript);
+    for element in elements {
+        let signature = bitcoin::taproot::Signature::from_slice(element);
+        if signature.is_err() {
+            continue; // not a signature
+        }
+        let signature = signature.unwrap();
+
+        let mut sighash_cache = SighashCache::new(tx);
+        let sighash = if signature.sighash_type == TapSighashType::AllPlusAnyoneCanPay
+            || signature.sighash_type == TapSighashType::SinglePlusAnyoneCanPay
+            || signature.sighash_type == TapSighashType::NonePlusAnyoneCanPay
+        {
+            sighash_cache.taproot_script_spend_signature_hash(
+                input_index,
+                &Prevouts::One(input_index, &prevouts[input_index]),
+                leaf_hash,
+                signature.sighash_type,
+            )
+        } else {
+            sighash_cache.taproot_script_spend_signature_hash(
+                input_index,
+                &Prevouts::All(&prevouts),
+                leaf_hash,
+                signature.sighash_type,
+            )
+        }
+        .map_err(|err| format!("Failed to construct sighash: {}", err))?;
+        let message = Message::from(sighash);
+
+        let public_key = taproot_public_keys.iter().find(|public_key| {
+            secp.verify_schnorr(&signature.signature, &message, public_key)
+                .is_ok()
+        });
+        if public_key.is_none() {
+            return Err(format!("Input {} has an invalid signature", input_index));
+        }
+        signed_public_keys.push(public_key.unwrap());
+    }
+
+    Ok(signed_public_keys)
+}
+
+fn verify_signed_by_all<K: PartialEq + Display>(
+    input_index: usize,
+    public_keys: &[K],
+    signed_public_keys: &[&K],
+) -> Result<(), String> {
+    match public_keys
+        .iter()
+        .find(|public_key| !signed_public_keys.contains(public_key))
+    {
+        Some(public_key) => Err(format!(
+            "Input {} is missing the signature of {}",
+            input_index, public_key
+        )),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,137 @@
+use bitcoin::{
+    hashes::Hash,
+    secp256k1::schnorr,
+    sighash::{Prevouts, SighashCache},
+    taproot::LeafVersion,
+    Script, TapLeafHash, TapSighash, TapSighashType, Transaction, TxOut,
+};
+use musig2::{
+    secp::{Point, Scalar},
+    AggNonce, LiftedSignature, PartialSignature, PubNonce, SecNonce,
+};
+
+use super::super::contexts::{base::generate_n_of_n_key_agg_context, verifier::VerifierContext};
+
+pub fn generate_taproot_leaf_sighash(
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    sighash_type: TapSighashType,
+    script: &Script,
+) -> TapSighash {
+    let leaf_hash = TapLeafHash::from_script(script, LeafVersion::TapScript);
+    let mut sighash_cache = SighashCache::new(tx);
+
+    if sighash_type == TapSighashType::AllPlusAnyoneCanPay
+        || sighash_type == TapSighashType::SinglePlusAnyoneCanPay
+        || sighash_type == TapSighashType::NonePlusAnyoneCanPay
+    {
+        sighash_cache.taproot_script_spend_signature_hash(
+            input_index,
+            &Prevouts::One(input_index, &prevouts[input_index]),
+            leaf_hash,
+            sighash_type,
+        )
+    } else {
+        sighash_cache.taproot_script_spend_signature_hash(
+            input_index,
+            &Prevouts::All(&prevouts),
+            leaf_hash,
+            sighash_type,
+        )
+    }
+    .expect("Failed to construct sighash")
+}
+
+fn get_secret_key(context: &VerifierContext) -> Scalar {
+    Scalar::from_slice(&context.verifier_keypair.secret_key().secret_bytes()).unwrap()
+}
+
+// Every nonce must only ever be used for a single signature, the secret nonce is therefore
+// generated from fresh randomness instead of being derived from the message.
+pub fn generate_nonce(context: &VerifierContext) -> SecNonce {
+    SecNonce::build(rand::random::<[u8; 32]>())
+        .with_seckey(get_secret_key(context))
+        .with_aggregated_pubkey(Point::from_slice(&context.n_of_n_public_key.to_bytes()).unwrap())
+        .build()
+}
+
+pub fn generate_taproot_leaf_partial_signature(
+    context: &VerifierContext,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    sighash_type: TapSighashType,
+    script: &Script,
+    secret_nonce: SecNonce,
+    public_nonces: &Vec<PubNonce>,
+) -> Result<PartialSignature, String> {
+    let sighash = generate_taproot_leaf_sighash(tx, prevouts, input_index, sighash_type, script);
+    let key_agg_context = generate_n_of_n_key_agg_context(&context.n_of_n_public_keys);
+    let aggregated_nonce = AggNonce::sum(public_nonces);
+
+    musig2::sign_partial(
+        &key_agg_context,
+        get_secret_key(context),
+        secret_nonce,
+        &aggregated_nonce,
+        sighash.to_byte_array(),
+    )
+    .map_err(|err| format!("Input {} could not be signed: {}", input_index, err))
+}
+
+pub fn aggregate_taproot_leaf_partial_signatures(
+    context: &VerifierContext,
+    tx: &Transaction,
+    prevouts: &Vec<TxOut>,
+    input_index: usize,
+    sighash_type: TapSighashType,
+    script: &Script,
+    public_nonces: &Vec<PubNonce>,
+    partial_signatures: &Vec<PartialSignature>,
+) -> Result<bitcoin::taproot::Signature, String> {
+    let sighash = generate_taproot_leaf_sighash(tx, prevouts, input_index, sighash_type, script);
+    let key_agg_context = generate_n_of_n_key_agg_context(&context.n_of_n_public_keys);
+    let aggregated_nonce = AggNonce::sum(public_nonces);
+
+    // Nonces and partial signatures are ordered like the n-of-n public keys of the context
+    for ((public_key, public_nonce), partial_signature) in context
+        .n_of_n_public_keys
+        .iter()
+        .zip(public_nonces.iter())
+        .zip(partial_signatures.iter())
+    {
+        musig2::verify_partial(
+            &key_agg_context,
+            *partial_signature,
+            &aggregated_nonce,
+            Point::from_slice(&public_key.to_bytes()).unwrap(),
+            public_nonce,
+            sighash.to_byte_array(),
+        )
+        .map_err(|_| {
+            format!(
+                "Input {} has an invalid partial signature of verifier {}",
+                input_index, public_key
+            )
+        })?;
+    }
+
+    let signature: LiftedSignature = musig2::aggregate_partial_signatures(
+        &key_agg_context,
+        &aggregated_nonce,
+        partial_signatures.iter().copied(),
+        sighash.to_byte_array(),
+    )
+    .map_err(|err| {
+        format!(
+            "Input {} partial signatures could not be aggregated: {}",
+            input_index, err
+        )
+    })?;
+
+    Ok(bitcoin::taproot::Signature {
+        signature: schnorr::Signature::from_slice(&signature.serialize()).unwrap(),
+        sighash_type,
+    })
+}
@@ -1,5 +1,10 @@
-use bitcoin::{absolute, Amount, EcdsaSighashType, ScriptBuf, TapSighashType, Transaction, TxOut};
+use bitcoin::{
+    absolute, taproot::TaprootSpendInfo, EcdsaSighashType, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
@@ -8,11 +13,13 @@ use super::{
             connector_a::ConnectorA, connector_b::ConnectorB,
         },
         contexts::{operator::OperatorContext, verifier::VerifierContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
 };
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
@@ -20,8 +27,11 @@ pub struct Take1Transaction {
     tx: Transaction,
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
+    connector_0: Connector0,
     connector_a: ConnectorA,
     connector_b: ConnectorB,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for Take1Transaction {
@@ -32,6 +42,35 @@ impl PreSignedTransaction for Take1Transaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_0.generate_taproot_spend_info()),
+            2 => Some(self.connector_a.generate_taproot_spend_info()),
+            3 => Some(self.connector_b.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for Take1Transaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0, 3] }
 }
 
 impl Take1Transaction {
@@ -42,25 +81,32 @@ impl Take1Transaction {
         input2: Input,
         input3: Input,
     ) -> Self {
-        let connector_0 = Connector0::new(context.network, &context.n_of_n_public_key);
-        let connector_1 = Connector1::new(context.network, &context.operator_public_key);
+        let connector_0 = Connector0::new(context.network, &context.n_of_n_taproot_public_key);
+        let connector_1 = Connector1::new(
+            context.network,
+            &context.operator_public_key,
+            context.parameters.take1_timelock,
+        );
         let connector_a = ConnectorA::new(
             context.network,
             &context.operator_taproot_public_key,
             &context.n_of_n_taproot_public_key,
         );
-        let connector_b = ConnectorB::new(context.network, &context.n_of_n_taproot_public_key);
+        let connector_b = ConnectorB::new(
+            context.network,
+            &context.n_of_n_taproot_public_key,
+            context.parameters.burn_timelock,
+        );
 
-        let _input0 = connector_0.generate_tx_in(&input0);
+        let _input0 = connector_0.generate_taproot_leaf_tx_in(0, &input0).unwrap();
 
         let _input1 = connector_1.generate_tx_in(&input1);
 
-        let _input2 = connector_a.generate_taproot_leaf_tx_in(0, &input2);
+        let _input2 = connector_a.generate_taproot_leaf_tx_in(0, &input2).unwrap();
 
-        let _input3 = connector_b.generate_taproot_leaf_tx_in(0, &input3);
+        let _input3 = connector_b.generate_taproot_leaf_tx_in(0, &input3).unwrap();
 
-        let total_output_amount = input0.amount + input1.amount + input2.amount + input3.amount
-            - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount + input1.amount + input2.amount + input3.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
@@ -81,7 +127,7 @@ impl Take1Transaction {
             prev_outs: vec![
                 TxOut {
                     value: input0.amount,
-                    script_pubkey: connector_0.generate_address().script_pubkey(),
+                    script_pubkey: connector_0.generate_taproot_address().script_pubkey(),
                 },
                 TxOut {
                     value: input1.amount,
@@ -97,29 +143,47 @@ impl Take1Transaction {
                 },
             ],
             prev_scripts: vec![
-                connector_0.generate_script(),
+                connector_0.generate_taproot_leaf_script(0).unwrap(),
                 connector_1.generate_script(),
-                connector_a.generate_taproot_leaf_script(0),
-                connector_b.generate_taproot_leaf_script(0),
+                connector_a.generate_taproot_leaf_script(0).unwrap(),
+                connector_b.generate_taproot_leaf_script(0).unwrap(),
             ],
+            connector_0,
             connector_a,
             connector_b,
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
         };
 
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.sign_input1(context);
         this.sign_input2(context);
 
         this
     }
 
-    fn sign_input0(&mut self, context: &VerifierContext) {
-        pre_sign_p2wsh_input(
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 0;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            0,
-            EcdsaSighashType::All,
-            &vec![&context.n_of_n_keypair],
-        );
+            input_index,
+            TapSighashType::All,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::All,
+            self.connector_0.generate_taproot_spend_info(),
+        )
     }
 
     fn sign_input1(&mut self, context: &OperatorContext) {
@@ -143,20 +207,55 @@ impl Take1Transaction {
         );
     }
 
-    fn sign_input3(&mut self, context: &VerifierContext) {
-        pre_sign_taproot_input(
+    fn sign_input3(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 3;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            3,
+            input_index,
+            TapSighashType::All,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
             TapSighashType::All,
             self.connector_b.generate_taproot_spend_info(),
-            &vec![&context.n_of_n_keypair],
-        );
+        )
+    }
+
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
+        let input_index = 0;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        let input_index = 3;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
+    }
+
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)?;
+        self.sign_input3(context, secret_nonces)
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) {
-        self.sign_input0(context);
-        self.sign_input3(context);
+    pub fn merge(&mut self, take1: &Take1Transaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &take1.tx)?;
+        merge_musig2_nonces_and_signatures(self, take1)
     }
 }
 
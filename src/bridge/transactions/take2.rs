@@ -1,5 +1,10 @@
-use bitcoin::{absolute, Amount, EcdsaSighashType, ScriptBuf, Transaction, TxOut};
+use bitcoin::{
+    absolute, taproot::TaprootSpendInfo, EcdsaSighashType, PublicKey, ScriptBuf, TapSighashType,
+    Transaction, TxOut,
+};
+use musig2::{PartialSignature, PubNonce, SecNonce};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{
     super::{
@@ -7,11 +12,13 @@ use super::{
             connector::*, connector_0::Connector0, connector_2::Connector2, connector_3::Connector3,
         },
         contexts::{operator::OperatorContext, verifier::VerifierContext},
-        graphs::base::FEE_AMOUNT,
+        error::BridgeError,
         scripts::*,
     },
     base::*,
+    fee::deduct_fee,
     pre_signed::*,
+    pre_signed_musig2::*,
 };
 
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
@@ -19,6 +26,10 @@ pub struct Take2Transaction {
     tx: Transaction,
     prev_outs: Vec<TxOut>,
     prev_scripts: Vec<ScriptBuf>,
+    connector_0: Connector0,
+    connector_3: Connector3,
+    musig2_nonces: HashMap<usize, HashMap<PublicKey, PubNonce>>,
+    musig2_signatures: HashMap<usize, HashMap<PublicKey, PartialSignature>>,
 }
 
 impl PreSignedTransaction for Take2Transaction {
@@ -29,22 +40,53 @@ impl PreSignedTransaction for Take2Transaction {
     fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
 
     fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+
+    fn taproot_spend_info(&self, input_index: usize) -> Option<TaprootSpendInfo> {
+        match input_index {
+            0 => Some(self.connector_0.generate_taproot_spend_info()),
+            2 => Some(self.connector_3.generate_taproot_spend_info()),
+            _ => None,
+        }
+    }
+}
+
+impl PreSignedMusig2Transaction for Take2Transaction {
+    fn musig2_nonces(&self) -> &HashMap<usize, HashMap<PublicKey, PubNonce>> { &self.musig2_nonces }
+
+    fn musig2_nonces_mut(&mut self) -> &mut HashMap<usize, HashMap<PublicKey, PubNonce>> {
+        &mut self.musig2_nonces
+    }
+
+    fn musig2_signatures(&self) -> &HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &self.musig2_signatures
+    }
+
+    fn musig2_signatures_mut(
+        &mut self,
+    ) -> &mut HashMap<usize, HashMap<PublicKey, PartialSignature>> {
+        &mut self.musig2_signatures
+    }
+
+    fn musig2_input_indices(&self) -> Vec<usize> { vec![0, 2] }
 }
 
 impl Take2Transaction {
     pub fn new(context: &OperatorContext, input0: Input, input1: Input, input2: Input) -> Self {
-        let connector_0 = Connector0::new(context.network, &context.n_of_n_public_key);
-        let connector_2 = Connector2::new(context.network, &context.operator_public_key);
-        let connector_3 = Connector3::new(context.network, &context.n_of_n_public_key);
+        let connector_0 = Connector0::new(context.network, &context.n_of_n_taproot_public_key);
+        let connector_2 = Connector2::new(
+            context.network,
+            &context.operator_public_key,
+            context.parameters.take2_timelock,
+        );
+        let connector_3 = Connector3::new(context.network, &context.n_of_n_taproot_public_key);
 
-        let _input0 = connector_0.generate_tx_in(&input0);
+        let _input0 = connector_0.generate_taproot_leaf_tx_in(0, &input0).unwrap();
 
         let _input1 = connector_2.generate_tx_in(&input1);
 
-        let _input2 = connector_3.generate_tx_in(&input2);
+        let _input2 = connector_3.generate_taproot_leaf_tx_in(0, &input2).unwrap();
 
-        let total_output_amount =
-            input0.amount + input1.amount + input2.amount - Amount::from_sat(FEE_AMOUNT);
+        let total_output_amount = input0.amount + input1.amount + input2.amount;
 
         let _output0 = TxOut {
             value: total_output_amount,
@@ -65,7 +107,7 @@ impl Take2Transaction {
             prev_outs: vec![
                 TxOut {
                     value: input0.amount,
-                    script_pubkey: connector_0.generate_address().script_pubkey(),
+                    script_pubkey: connector_0.generate_taproot_address().script_pubkey(),
                 },
                 TxOut {
                     value: input1.amount,
@@ -73,29 +115,48 @@ impl Take2Transaction {
                 },
                 TxOut {
                     value: input2.amount,
-                    script_pubkey: connector_3.generate_address().script_pubkey(),
+                    script_pubkey: connector_3.generate_taproot_address().script_pubkey(),
                 },
             ],
             prev_scripts: vec![
-                connector_0.generate_script(),
+                connector_0.generate_taproot_leaf_script(0).unwrap(),
                 connector_2.generate_script(),
-                connector_3.generate_script(),
+                connector_3.generate_taproot_leaf_script(0).unwrap(),
             ],
+            connector_0,
+            connector_3,
+            musig2_nonces: HashMap::new(),
+            musig2_signatures: HashMap::new(),
         };
 
+        deduct_fee(&mut this, 0, context.parameters.presigned_fee_rate());
+
         this.sign_input1(context);
 
         this
     }
 
-    fn sign_input0(&mut self, context: &VerifierContext) {
-        pre_sign_p2wsh_input(
+    fn sign_input0(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 0;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            0,
-            EcdsaSighashType::All,
-            &vec![&context.n_of_n_keypair],
-        );
+            input_index,
+            TapSighashType::All,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::All,
+            self.connector_0.generate_taproot_spend_info(),
+        )
     }
 
     fn sign_input1(&mut self, context: &OperatorContext) {
@@ -108,19 +169,55 @@ impl Take2Transaction {
         );
     }
 
-    fn sign_input2(&mut self, context: &VerifierContext) {
-        pre_sign_p2wsh_input(
+    fn sign_input2(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        let input_index = 2;
+        pre_sign_musig2_taproot_input(
             self,
             context,
-            2,
-            EcdsaSighashType::All,
-            &vec![&context.n_of_n_keypair],
-        );
+            input_index,
+            TapSighashType::All,
+            secret_nonces,
+        )?;
+
+        finalize_musig2_taproot_input(
+            self,
+            context,
+            input_index,
+            TapSighashType::All,
+            self.connector_3.generate_taproot_spend_info(),
+        )
+    }
+
+    pub fn push_nonces(&mut self, context: &VerifierContext) -> HashMap<usize, SecNonce> {
+        let mut secret_nonces = HashMap::new();
+
+        let input_index = 0;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        let input_index = 2;
+        let secret_nonce = push_nonce(self, context, input_index);
+        secret_nonces.insert(input_index, secret_nonce);
+
+        secret_nonces
+    }
+
+    pub fn pre_sign(
+        &mut self,
+        context: &VerifierContext,
+        secret_nonces: &mut HashMap<usize, SecNonce>,
+    ) -> Result<(), BridgeError> {
+        self.sign_input0(context, secret_nonces)?;
+        self.sign_input2(context, secret_nonces)
     }
 
-    pub fn pre_sign(&mut self, context: &VerifierContext) {
-        self.sign_input0(context);
-        self.sign_input2(context);
+    pub fn merge(&mut self, take2: &Take2Transaction) -> Result<(), BridgeError> {
+        merge_transactions(&mut self.tx, &take2.tx)?;
+        merge_musig2_nonces_and_signatures(self, take2)
     }
 }
 
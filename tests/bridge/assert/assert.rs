@@ -13,7 +13,7 @@ use super::super::{helper::generate_stub_outpoint, setup::setup_test};
 
 #[tokio::test]
 async fn test_assert_tx() {
-    let (client, _, operator_context, verifier_context, _, _, connector_b, _, _, _, _, _, _, _) =
+    let (client, _, operator_context, verifier_contexts, _, _, connector_b, _, _, _, _, _, _, _) =
         setup_test().await;
 
     let amount = Amount::from_sat(ONE_HUNDRED * 2 / 100);
@@ -22,10 +22,17 @@ async fn test_assert_tx() {
 
     let mut assert_tx = AssertTransaction::new(&operator_context, Input { outpoint, amount });
 
-    assert_tx.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| assert_tx.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        assert_tx.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let tx = assert_tx.finalize();
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));
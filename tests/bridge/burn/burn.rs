@@ -19,8 +19,22 @@ mod tests {
 
     #[tokio::test]
     async fn test_should_be_able_to_submit_burn_tx_successfully() {
-        let (client, _, operator_context, verifier_context, _, _, connector_b, _, _, _, _, _, _, _) =
-            setup_test().await;
+        let (
+            client,
+            _,
+            operator_context,
+            verifier_contexts,
+            _,
+            _,
+            connector_b,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = setup_test().await;
 
         let amount = Amount::from_sat(INITIAL_AMOUNT);
         let outpoint =
@@ -28,11 +42,19 @@ mod tests {
 
         let mut burn_tx = BurnTransaction::new(&operator_context, Input { outpoint, amount });
 
-        burn_tx.pre_sign(&verifier_context);
+        let mut secret_nonces: Vec<_> = verifier_contexts
+            .iter()
+            .map(|verifier_context| burn_tx.push_nonces(verifier_context))
+            .collect();
+        for (verifier_context, secret_nonces) in
+            verifier_contexts.iter().zip(secret_nonces.iter_mut())
+        {
+            burn_tx.pre_sign(verifier_context, secret_nonces).unwrap();
+        }
         let tx = burn_tx.finalize();
         println!("Script Path Spend Transaction: {:?}\n", tx);
 
-        let result = client.esplora.broadcast(&tx).await;
+        let result = client.chain.broadcast(&tx).await;
         println!("Txid: {:?}", tx.compute_txid());
         println!("Broadcast result: {:?}\n", result);
         println!("Transaction hex: \n{}", serialize_hex(&tx));
@@ -41,8 +63,22 @@ mod tests {
 
     #[tokio::test]
     async fn test_should_be_able_to_submit_burn_tx_with_verifier_added_to_output_successfully() {
-        let (client, _, operator_context, verifier_context, _, _, connector_b, _, _, _, _, _, _, _) =
-            setup_test().await;
+        let (
+            client,
+            _,
+            operator_context,
+            verifier_contexts,
+            _,
+            _,
+            connector_b,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = setup_test().await;
 
         let amount = Amount::from_sat(INITIAL_AMOUNT);
         let outpoint =
@@ -50,15 +86,23 @@ mod tests {
 
         let mut burn_tx = BurnTransaction::new(&operator_context, Input { outpoint, amount });
 
-        burn_tx.pre_sign(&verifier_context);
+        let mut secret_nonces: Vec<_> = verifier_contexts
+            .iter()
+            .map(|verifier_context| burn_tx.push_nonces(verifier_context))
+            .collect();
+        for (verifier_context, secret_nonces) in
+            verifier_contexts.iter().zip(secret_nonces.iter_mut())
+        {
+            burn_tx.pre_sign(verifier_context, secret_nonces).unwrap();
+        }
         let mut tx = burn_tx.finalize();
 
-        let secp = verifier_context.secp;
+        let secp = &verifier_contexts[0].secp;
         let verifier_secret: &str =
             "aaaaaaaaaabbbbbbbbbbccccccccccddddddddddeeeeeeeeeeffffffffff1234";
         let verifier_keypair = Keypair::from_seckey_str(&secp, verifier_secret).unwrap();
         let verifier_private_key =
-            PrivateKey::new(verifier_keypair.secret_key(), verifier_context.network);
+            PrivateKey::new(verifier_keypair.secret_key(), verifier_contexts[0].network);
         let verifier_pubkey = PublicKey::from_private_key(&secp, &verifier_private_key);
 
         let verifier_output = TxOut {
@@ -70,7 +114,7 @@ mod tests {
 
         println!("Script Path Spend Transaction: {:?}\n", tx);
 
-        let result = client.esplora.broadcast(&tx).await;
+        let result = client.chain.broadcast(&tx).await;
         println!("Txid: {:?}", tx.compute_txid());
         println!("Broadcast result: {:?}\n", result);
         println!("Transaction hex: \n{}", serialize_hex(&tx));
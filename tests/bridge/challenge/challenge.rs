@@ -36,6 +36,7 @@ async fn test_challenge_tx() {
     let crowdfunding_utxos = client
         .get_initial_utxos(address.clone(), amount_1)
         .await
+        .unwrap()
         .unwrap_or_else(|| {
             panic!(
                 "Fund {:?} with {} sats at https://faucet.mutinynet.com/",
@@ -64,33 +65,35 @@ async fn test_challenge_tx() {
         input_amount_crowdfunding_total,
     );
 
-    challenge_tx.add_inputs_and_output(
-        &depositor_context,
-        &vec![
-            InputWithScript {
-                outpoint: OutPoint {
-                    txid: crowdfunding_utxos[0].txid,
-                    vout: crowdfunding_utxos[0].vout,
+    challenge_tx
+        .add_inputs_and_output(
+            &depositor_context,
+            &vec![
+                InputWithScript {
+                    outpoint: OutPoint {
+                        txid: crowdfunding_utxos[0].txid,
+                        vout: crowdfunding_utxos[0].vout,
+                    },
+                    amount: amount_1,
+                    script: &generate_pay_to_pubkey_script(crowdfunding_public_key),
                 },
-                amount: amount_1,
-                script: &generate_pay_to_pubkey_script(crowdfunding_public_key),
-            },
-            InputWithScript {
-                outpoint: OutPoint {
-                    txid: crowdfunding_utxos[1].txid,
-                    vout: crowdfunding_utxos[1].vout,
+                InputWithScript {
+                    outpoint: OutPoint {
+                        txid: crowdfunding_utxos[1].txid,
+                        vout: crowdfunding_utxos[1].vout,
+                    },
+                    amount: amount_1,
+                    script: &generate_pay_to_pubkey_script(crowdfunding_public_key),
                 },
-                amount: amount_1,
-                script: &generate_pay_to_pubkey_script(crowdfunding_public_key),
-            },
-        ],
-        crowdfunding_keypair,
-        refund_address.script_pubkey(),
-    );
+            ],
+            crowdfunding_keypair,
+            refund_address.script_pubkey(),
+        )
+        .unwrap();
 
     let tx = challenge_tx.finalize();
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));
@@ -98,11 +101,7 @@ async fn test_challenge_tx() {
 
     // assert refund balance
     let challenge_tx_id = tx.compute_txid();
-    let refund_utxos = client
-        .esplora
-        .get_address_utxo(refund_address)
-        .await
-        .unwrap();
+    let refund_utxos = client.chain.get_address_utxo(refund_address).await.unwrap();
     let refund_utxo = refund_utxos
         .clone()
         .into_iter()
@@ -0,0 +1,27 @@
+use bitcoin::Network;
+
+use bitvm::bridge::client::chain::esplora::{
+    get_esplora_url, BITCOIN_ESPLORA_URL, MUTINYNET_ESPLORA_URL, REGTEST_ESPLORA_URL,
+    TESTNET_ESPLORA_URL,
+};
+
+#[test]
+fn test_esplora_url_of_every_network() {
+    assert_eq!(
+        get_esplora_url(Network::Bitcoin).unwrap(),
+        BITCOIN_ESPLORA_URL
+    );
+    assert_eq!(
+        get_esplora_url(Network::Testnet).unwrap(),
+        TESTNET_ESPLORA_URL
+    );
+    assert_eq!(
+        get_esplora_url(Network::Signet).unwrap(),
+        MUTINYNET_ESPLORA_URL
+    );
+    assert_eq!(
+        get_esplora_url(Network::Regtest).unwrap(),
+        REGTEST_ESPLORA_URL
+    );
+    assert_ne!(TESTNET_ESPLORA_URL, MUTINYNET_ESPLORA_URL);
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bitcoin::Network;
+use tokio::time::sleep;
+
+use bitvm::bridge::{
+    client::data_store::{data_store::DataStore, local_file::LocalFile},
+    contexts::base::generate_keys_from_keypair,
+};
+
+use super::super::setup::{depositor_keypair, operator_keypair};
+
+fn create_local_data_store() -> DataStore {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let directory = std::env::temp_dir().join(format!("bridge-data-store-test-{}", time));
+
+    DataStore::with_backend(Box::new(LocalFile::new(directory.to_str().unwrap())))
+}
+
+#[tokio::test]
+async fn test_local_file_data_store() {
+    let (secp, keypair, _, taproot_public_key) =
+        generate_keys_from_keypair(Network::Testnet, &operator_keypair());
+    let allowed_public_keys = vec![taproot_public_key];
+    let data_store = create_local_data_store();
+
+    let latest_data = data_store.fetch_latest_data(&allowed_public_keys).await;
+    assert!(latest_data.is_ok_and(|data| data.is_none()));
+
+    let json = String::from("{\"version\":1}");
+    let key = data_store.write_data(json.clone(), &secp, &keypair).await;
+    assert!(key.is_ok());
+
+    let latest_data = data_store.fetch_latest_data(&allowed_public_keys).await;
+    assert!(latest_data.is_ok_and(|data| data.is_some_and(|data| data.eq(&json))));
+}
+
+#[tokio::test]
+async fn test_data_store_skips_data_from_unknown_writer() {
+    let (_, _, _, operator_taproot_public_key) =
+        generate_keys_from_keypair(Network::Testnet, &operator_keypair());
+    let (secp, depositor_keypair, _, _) =
+        generate_keys_from_keypair(Network::Testnet, &depositor_keypair());
+    let data_store = create_local_data_store();
+
+    let json = String::from("{\"version\":1}");
+    let key = data_store
+        .write_data(json.clone(), &secp, &depositor_keypair)
+        .await;
+    assert!(key.is_ok());
+
+    let latest_data = data_store
+        .fetch_latest_data(&vec![operator_taproot_public_key])
+        .await;
+    assert!(latest_data.is_ok_and(|data| data.is_none()));
+}
+
+#[tokio::test]
+async fn test_data_store_fetches_newer_files_of_other_writers() {
+    let (secp, operator_keypair, _, operator_taproot_public_key) =
+        generate_keys_from_keypair(Network::Testnet, &operator_keypair());
+    let (_, depositor_keypair, _, depositor_taproot_public_key) =
+        generate_keys_from_keypair(Network::Testnet, &depositor_keypair());
+    let data_store = create_local_data_store();
+
+    // data files are named by their timestamp in milliseconds
+    let jsons: Vec<String> = (1..4)
+        .map(|version| format!("{{\"version\":{}}}", version))
+        .collect();
+    for (json, keypair) in
+        jsons
+            .iter()
+            .zip([&depositor_keypair, &operator_keypair, &depositor_keypair])
+    {
+        data_store
+            .write_data(json.clone(), &secp, keypair)
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(2)).await;
+    }
+
+    // the depositor file older than the latest operator file is superseded by it
+    let data_files = data_store
+        .fetch_latest_data_files(&vec![operator_taproot_public_key])
+        .await
+        .unwrap();
+    assert_eq!(
+        data_files,
+        vec![
+            (operator_taproot_public_key, jsons[1].clone()),
+            (depositor_taproot_public_key, jsons[2].clone())
+        ]
+    );
+}
@@ -1 +1,7 @@
+pub mod chain;
+pub mod data_store;
+pub mod process;
+pub mod registry;
+pub mod status;
 pub mod sync;
+pub mod verify;
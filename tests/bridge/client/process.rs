@@ -0,0 +1,484 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bitcoin::{Amount, Network};
+
+use bitvm::bridge::{
+    client::{
+        chain::simulator::Simulator, client::BitVMClient, data_store::local_file::LocalFile,
+        policy::ProcessingPolicy,
+    },
+    commitments::{sign_commit_y, CommitmentValue, COMMITMENT_VALUE_LENGTH},
+    contexts::{
+        base::generate_keys_from_keypair, operator::OperatorContext, verifier::VerifierContext,
+    },
+    graphs::{
+        base::{BaseGraph, PreSigningState, FEE_AMOUNT, INITIAL_AMOUNT},
+        peg_in::PegInDepositorStatus,
+        peg_out::{PegOutGraph, PegOutState},
+    },
+    registry::ParticipantRegistry,
+    scripts::generate_pay_to_pubkey_script_address,
+    transactions::base::Input,
+};
+
+use super::super::{
+    helper::generate_stub_outpoint,
+    setup::{
+        depositor_key_chain, depositor_keypair, operator_keypair, setup_test, verifier_0_keypair,
+        verifier_1_keypair, withdrawer_destination_address, withdrawer_key_chain, EVM_ADDRESS,
+    },
+};
+
+#[tokio::test]
+async fn test_process_waits_for_peg_in_deposit() {
+    let (mut client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    )
+    .await;
+
+    client
+        .create_peg_in_graph(Input { outpoint, amount }, &evm_address)
+        .await
+        .unwrap();
+
+    // the peg-in deposit has not been broadcast, so there is nothing to presign or execute yet
+    let data_changed = client.process(&ProcessingPolicy::default()).await.unwrap();
+    assert!(!data_changed);
+}
+
+// The kick-off of these tests does not commit to a peg-out of the graph.
+const INVALID_CLAIM: CommitmentValue = [0xc1; COMMITMENT_VALUE_LENGTH];
+
+const EVM_BURN_TX_HASH: &str = "0x5f1c6b4f0a2e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c";
+
+fn no_actions() -> ProcessingPolicy {
+    ProcessingPolicy {
+        pre_sign: false,
+        challenge: false,
+        burn: false,
+        peg_in_confirm: false,
+        kick_off: false,
+        assert: false,
+        take1: false,
+        take2: false,
+        ..ProcessingPolicy::default()
+    }
+}
+
+fn create_data_store_directory() -> String {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    std::env::temp_dir()
+        .join(format!("bridge-client-process-test-{}", time))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+// Creates a peg-in and a peg-out graph and mines the peg-in deposit. The first client runs every
+// role except the second verifier, which runs in the second client.
+async fn setup_graphs(
+    simulator: &Simulator,
+) -> (BitVMClient, BitVMClient, OperatorContext, VerifierContext) {
+    let network = Network::Testnet;
+    let operator_public_key = generate_keys_from_keypair(network, &operator_keypair()).2;
+    let verifier_public_keys = [verifier_0_keypair(), verifier_1_keypair()]
+        .iter()
+        .map(|keypair| generate_keys_from_keypair(network, keypair).2)
+        .collect();
+    let registry =
+        ParticipantRegistry::new(network, &vec![operator_public_key], &verifier_public_keys)
+            .unwrap();
+    let (n_of_n_public_key, n_of_n_taproot_public_key) = registry.n_of_n_public_key();
+    let operator_context = OperatorContext::from_keypair(
+        network,
+        &operator_keypair(),
+        &n_of_n_public_key,
+        &n_of_n_taproot_public_key,
+    );
+    let verifier_context =
+        VerifierContext::from_keypair(network, &verifier_0_keypair(), &verifier_public_keys)
+            .unwrap();
+
+    let directory = create_data_store_directory();
+    let mut client_0 = BitVMClient::new(
+        &registry,
+        Some(&depositor_key_chain()),
+        Some(&operator_keypair()),
+        Some(&verifier_0_keypair()),
+        Some(&withdrawer_key_chain()),
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+    let mut client_1 = BitVMClient::new(
+        &registry,
+        None,
+        None,
+        Some(&verifier_1_keypair()),
+        None,
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+
+    let depositor_public_key = generate_keys_from_keypair(network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(network, &depositor_public_key),
+        amount,
+    );
+    client_0
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+    let peg_in_graph_id = client_0.data().peg_in_graphs[0].id().clone();
+
+    // the operator funds the kick-off from the Commit Y address of its peg-out graph
+    let operator_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let operator_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(network, &operator_context.operator_public_key),
+        operator_amount,
+    );
+    let kick_off_input = client_0
+        .broadcast_commit_y_funding(
+            &peg_in_graph_id,
+            Input {
+                outpoint: operator_outpoint,
+                amount: operator_amount,
+            },
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+    client_0
+        .create_peg_out_graph(&peg_in_graph_id, kick_off_input)
+        .await
+        .unwrap();
+    client_0.flush().await.unwrap();
+    client_1.sync().await;
+
+    client_0
+        .broadcast_peg_in_deposit(&peg_in_graph_id)
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    (client_0, client_1, operator_context, verifier_context)
+}
+
+// Runs pre-signing rounds of both verifiers until the graphs of both clients are presigned.
+async fn pre_sign_graphs(client_0: &mut BitVMClient, client_1: &mut BitVMClient) {
+    let policy = ProcessingPolicy {
+        pre_sign: true,
+        ..no_actions()
+    };
+    for _ in 0..3 {
+        for client in [&mut *client_1, &mut *client_0] {
+            client.sync().await;
+            client.process(&policy).await.unwrap();
+            client.flush().await.unwrap();
+        }
+    }
+
+    client_0.sync().await;
+    for client in [client_0, client_1] {
+        assert_eq!(
+            client.data().peg_in_graphs[0].pre_signing_state(),
+            PreSigningState::Presigned
+        );
+        assert_eq!(
+            client.data().peg_out_graphs[0].pre_signing_state(),
+            PreSigningState::Presigned
+        );
+    }
+}
+
+async fn confirm_peg_in(simulator: &Simulator, client: &mut BitVMClient) {
+    let policy = ProcessingPolicy {
+        peg_in_confirm: true,
+        ..no_actions()
+    };
+    client.process(&policy).await.unwrap();
+    simulator.mine_blocks(1);
+}
+
+// Processes one round with the policy and mines the transactions it broadcast.
+async fn process_and_mine(
+    simulator: &Simulator,
+    client: &mut BitVMClient,
+    policy: &ProcessingPolicy,
+) -> PegOutState {
+    client.process(policy).await.unwrap();
+    simulator.mine_blocks(1);
+
+    client.data().peg_out_graphs[0]
+        .state(client.chain.as_ref())
+        .await
+        .unwrap()
+        .state
+}
+
+async fn kick_off_with_invalid_claim(
+    simulator: &Simulator,
+    client: &BitVMClient,
+    operator_context: &OperatorContext,
+) {
+    let mut peg_out_graph: PegOutGraph =
+        serde_json::from_str(&serde_json::to_string(&client.data().peg_out_graphs[0]).unwrap())
+            .unwrap();
+    let commit_y = sign_commit_y(operator_context, peg_out_graph.id(), &INVALID_CLAIM);
+    peg_out_graph
+        .kick_off(client.chain.as_ref(), &commit_y)
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+}
+
+#[tokio::test]
+async fn test_process_pre_signs_and_confirms_peg_in_only_when_enabled() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, _, verifier_context) = setup_graphs(&simulator).await;
+
+    // pre-sign disabled
+    assert!(!client_0.process(&no_actions()).await.unwrap());
+    assert!(!client_0.data().peg_in_graphs[0].has_nonces_of(&verifier_context));
+    assert!(!client_0.data().peg_out_graphs[0].has_nonces_of(&verifier_context));
+
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+
+    // peg-in confirm disabled
+    assert!(!client_0.process(&no_actions()).await.unwrap());
+    simulator.mine_blocks(1);
+    assert_eq!(
+        client_0.data().peg_in_graphs[0]
+            .depositor_status(client_0.chain.as_ref())
+            .await
+            .unwrap(),
+        PegInDepositorStatus::PegInConfirmWait
+    );
+
+    confirm_peg_in(&simulator, &mut client_0).await;
+    assert_eq!(
+        client_0.data().peg_in_graphs[0]
+            .depositor_status(client_0.chain.as_ref())
+            .await
+            .unwrap(),
+        PegInDepositorStatus::PegInConfirmComplete
+    );
+}
+
+#[tokio::test]
+async fn test_process_kicks_off_and_takes_1_only_when_enabled() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, operator_context, _) = setup_graphs(&simulator).await;
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+    confirm_peg_in(&simulator, &mut client_0).await;
+
+    let peg_in_graph_id = client_0.data().peg_in_graphs[0].id().clone();
+    let peg_out_graph_id = client_0.data().peg_out_graphs[0].id().clone();
+    client_0
+        .request_peg_out(
+            &peg_in_graph_id,
+            EVM_ADDRESS,
+            EVM_BURN_TX_HASH,
+            &withdrawer_destination_address(operator_context.network),
+        )
+        .await
+        .unwrap();
+    let peg_in_amount = client_0.data().peg_in_graphs[0]
+        .peg_in_confirm_transaction_ref()
+        .tx()
+        .output[0]
+        .value;
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            operator_context.network,
+            &operator_context.operator_public_key,
+        ),
+        peg_in_amount,
+    );
+    client_0
+        .peg_out(
+            &peg_out_graph_id,
+            Input {
+                outpoint,
+                amount: peg_in_amount,
+            },
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::KickOffPending);
+    let policy = ProcessingPolicy {
+        kick_off: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::KickedOff);
+
+    simulator.mine_blocks(operator_context.parameters.take1_timelock);
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::KickedOff);
+    let policy = ProcessingPolicy {
+        take1: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::CompletedByTake1);
+}
+
+#[tokio::test]
+async fn test_process_challenges_and_asserts_only_when_enabled() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, operator_context, verifier_context) =
+        setup_graphs(&simulator).await;
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+    confirm_peg_in(&simulator, &mut client_0).await;
+    kick_off_with_invalid_claim(&simulator, &client_0, &operator_context).await;
+
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::KickedOff);
+    simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            verifier_context.network,
+            &verifier_context.verifier_public_key,
+        ),
+        client_0.data().peg_out_graphs[0].challenge_crowdfunding_amount(),
+    );
+    let policy = ProcessingPolicy {
+        challenge: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::Challenged);
+
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::Challenged);
+    let policy = ProcessingPolicy {
+        assert: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::Asserted);
+
+    // disprove is never taken automatically, whatever the verifier actions enabled
+    let policy = ProcessingPolicy {
+        pre_sign: true,
+        challenge: true,
+        burn: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::Asserted);
+}
+
+#[tokio::test]
+async fn test_process_takes_2_only_when_enabled() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, operator_context, verifier_context) =
+        setup_graphs(&simulator).await;
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+    confirm_peg_in(&simulator, &mut client_0).await;
+    kick_off_with_invalid_claim(&simulator, &client_0, &operator_context).await;
+
+    simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            verifier_context.network,
+            &verifier_context.verifier_public_key,
+        ),
+        client_0.data().peg_out_graphs[0].challenge_crowdfunding_amount(),
+    );
+    let policy = ProcessingPolicy {
+        challenge: true,
+        assert: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::Challenged);
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::Asserted);
+
+    simulator.mine_blocks(operator_context.parameters.take2_timelock);
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::Asserted);
+    let policy = ProcessingPolicy {
+        take2: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::CompletedByTake2);
+}
+
+#[tokio::test]
+async fn test_process_burns_only_when_enabled() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, operator_context, _) = setup_graphs(&simulator).await;
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+    confirm_peg_in(&simulator, &mut client_0).await;
+    kick_off_with_invalid_claim(&simulator, &client_0, &operator_context).await;
+
+    simulator.mine_blocks(operator_context.parameters.burn_timelock);
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::KickedOff);
+    let policy = ProcessingPolicy {
+        burn: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_0, &policy).await;
+    assert_eq!(state, PegOutState::FailedByBurn);
+}
+
+#[tokio::test]
+async fn test_process_operator_sees_challenge_and_burn_of_another_client() {
+    let simulator = Simulator::new();
+    let (mut client_0, mut client_1, operator_context, _) = setup_graphs(&simulator).await;
+    pre_sign_graphs(&mut client_0, &mut client_1).await;
+    confirm_peg_in(&simulator, &mut client_0).await;
+    kick_off_with_invalid_claim(&simulator, &client_0, &operator_context).await;
+
+    // the second verifier challenges on its own copy of the graph
+    let network = operator_context.network;
+    simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            network,
+            &generate_keys_from_keypair(network, &verifier_1_keypair()).2,
+        ),
+        client_1.data().peg_out_graphs[0].challenge_crowdfunding_amount(),
+    );
+    let policy = ProcessingPolicy {
+        challenge: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_1, &policy).await;
+    assert_eq!(state, PegOutState::Challenged);
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::Challenged);
+
+    // and burns once the operator did not assert in time
+    simulator.mine_blocks(operator_context.parameters.burn_timelock);
+    let policy = ProcessingPolicy {
+        burn: true,
+        ..no_actions()
+    };
+    let state = process_and_mine(&simulator, &mut client_1, &policy).await;
+    assert_eq!(state, PegOutState::FailedByBurn);
+    let state = process_and_mine(&simulator, &mut client_0, &no_actions()).await;
+    assert_eq!(state, PegOutState::FailedByBurn);
+}
@@ -0,0 +1,266 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bitcoin::{key::Keypair, Amount, Network};
+
+use bitvm::bridge::{
+    client::{chain::simulator::Simulator, client::BitVMClient, data_store::local_file::LocalFile},
+    contexts::base::generate_keys_from_keypair,
+    error::BridgeError,
+    graphs::{
+        base::{FEE_AMOUNT, INITIAL_AMOUNT},
+        status::{GraphRole, GraphStatusReport},
+    },
+    registry::ParticipantRegistry,
+    scripts::generate_pay_to_pubkey_script_address,
+    transactions::base::Input,
+};
+
+use super::super::setup::{
+    depositor_key_chain, depositor_keypair, operator_1_keypair, operator_keypair,
+    verifier_0_keypair, verifier_1_keypair, EVM_ADDRESS,
+};
+
+fn setup_registry(operator_keypairs: &[Keypair]) -> ParticipantRegistry {
+    let network = Network::Testnet;
+    let operator_public_keys = operator_keypairs
+        .iter()
+        .map(|keypair| generate_keys_from_keypair(network, keypair).2)
+        .collect();
+    let verifier_public_keys = [verifier_0_keypair(), verifier_1_keypair()]
+        .iter()
+        .map(|keypair| generate_keys_from_keypair(network, keypair).2)
+        .collect();
+
+    ParticipantRegistry::new(network, &operator_public_keys, &verifier_public_keys).unwrap()
+}
+
+fn create_data_store_directory() -> String {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    std::env::temp_dir()
+        .join(format!("bridge-client-registry-test-{}", time))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn create_peg_out_graph(
+    simulator: &Simulator,
+    client: &mut BitVMClient,
+    peg_in_graph_id: &str,
+    operator_keypair: &Keypair,
+) -> Vec<GraphStatusReport> {
+    let operator_public_key = generate_keys_from_keypair(Network::Testnet, operator_keypair).2;
+    let kick_off_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let kick_off_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(Network::Testnet, &operator_public_key),
+        kick_off_amount,
+    );
+
+    client
+        .create_peg_out_graph(
+            peg_in_graph_id,
+            Input {
+                outpoint: kick_off_outpoint,
+                amount: kick_off_amount,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[test]
+fn test_participant_registry_from_json() {
+    let registry = setup_registry(&[operator_keypair(), operator_1_keypair()]);
+
+    let json = serde_json::to_string(&registry).unwrap();
+    assert_eq!(ParticipantRegistry::from_json(&json).unwrap(), registry);
+
+    let operator_public_key = registry.operator_public_keys[1];
+    assert!(registry.is_operator(&operator_public_key));
+    assert!(!registry.is_verifier(&operator_public_key));
+    let public_context = registry.public_context(&operator_public_key).unwrap();
+    assert_eq!(public_context.operator_public_key, operator_public_key);
+    assert_eq!(
+        public_context.n_of_n_taproot_public_key,
+        registry.n_of_n_public_key().1
+    );
+    assert!(registry
+        .public_context(&registry.verifier_public_keys[0])
+        .is_none());
+
+    // every participant must be registered once
+    let mut duplicate_registry = registry.clone();
+    duplicate_registry
+        .verifier_public_keys
+        .push(registry.verifier_public_keys[0]);
+    let result =
+        ParticipantRegistry::from_json(&serde_json::to_string(&duplicate_registry).unwrap());
+    assert!(matches!(result, Err(BridgeError::Registry(_))));
+
+    let result =
+        ParticipantRegistry::new(registry.network, &vec![], &registry.verifier_public_keys);
+    assert!(matches!(result, Err(BridgeError::Registry(_))));
+}
+
+#[tokio::test]
+async fn test_verifier_status_reports_peg_out_graph_of_every_operator() {
+    let simulator = Simulator::new();
+    let registry = setup_registry(&[operator_keypair(), operator_1_keypair()]);
+    let mut client = BitVMClient::new(
+        &registry,
+        Some(&depositor_key_chain()),
+        Some(&operator_keypair()),
+        Some(&verifier_0_keypair()),
+        None,
+        None,
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+
+    let depositor_public_key = generate_keys_from_keypair(registry.network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+        amount,
+    );
+    client
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+
+    let peg_in_graph_id = client.status().await.unwrap()[0].graph_id.clone();
+    create_peg_out_graph(
+        &simulator,
+        &mut client,
+        &peg_in_graph_id,
+        &operator_keypair(),
+    )
+    .await;
+
+    // the local operator only reports its own peg-out graph
+    let operator_reports = client.operator_status().await.unwrap();
+    assert_eq!(operator_reports.len(), 1);
+    assert!(operator_reports[0].error.is_none());
+
+    // the verifier waits for the peg-out graph of the other operator
+    let verifier_reports = client.verifier_status().await.unwrap();
+    assert_eq!(verifier_reports.len(), 2);
+    assert!(verifier_reports
+        .iter()
+        .all(|report| report.role == GraphRole::Verifier));
+    assert_eq!(verifier_reports[0].graph_id, operator_reports[0].graph_id);
+    assert!(verifier_reports[0].error.is_none());
+    assert_ne!(verifier_reports[1].graph_id, verifier_reports[0].graph_id);
+    assert!(verifier_reports[1]
+        .error
+        .as_ref()
+        .unwrap()
+        .contains(&registry.operator_public_keys[1].to_string()));
+}
+
+#[tokio::test]
+async fn test_client_rejects_unregistered_operator() {
+    let registry = setup_registry(&[operator_1_keypair()]);
+    let result = BitVMClient::new(
+        &registry,
+        None,
+        Some(&operator_keypair()),
+        None,
+        None,
+        None,
+        Some(Box::new(Simulator::new())),
+    )
+    .await;
+    assert!(matches!(result, Err(BridgeError::Registry(_))));
+}
+
+#[tokio::test]
+async fn test_every_registered_operator_creates_its_peg_out_graph() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry(&[operator_keypair(), operator_1_keypair()]);
+    let mut operator_0_client = BitVMClient::new(
+        &registry,
+        Some(&depositor_key_chain()),
+        Some(&operator_keypair()),
+        None,
+        None,
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+    let mut operator_1_client = BitVMClient::new(
+        &registry,
+        None,
+        Some(&operator_1_keypair()),
+        None,
+        None,
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+
+    let depositor_public_key = generate_keys_from_keypair(registry.network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+        amount,
+    );
+    operator_0_client
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+    let peg_in_graph_id = operator_0_client.data().peg_in_graphs[0].id().clone();
+
+    // the graph of the second operator is still missing
+    let reports = create_peg_out_graph(
+        &simulator,
+        &mut operator_0_client,
+        &peg_in_graph_id,
+        &operator_keypair(),
+    )
+    .await;
+    assert_eq!(reports.len(), 2);
+    assert!(reports
+        .iter()
+        .all(|report| report.role == GraphRole::Operator));
+    assert!(reports[0].error.is_none());
+    assert!(reports[1]
+        .error
+        .as_ref()
+        .unwrap()
+        .contains(&registry.operator_public_keys[1].to_string()));
+    operator_0_client.flush().await.unwrap();
+
+    // the second operator creates its graph for the same peg-in with its own client
+    operator_1_client.sync().await;
+    let reports = create_peg_out_graph(
+        &simulator,
+        &mut operator_1_client,
+        &peg_in_graph_id,
+        &operator_1_keypair(),
+    )
+    .await;
+    assert_eq!(reports.len(), 2);
+    assert!(reports.iter().all(|report| report.error.is_none()));
+    operator_1_client.flush().await.unwrap();
+
+    // both graphs are verified against the keys the registry has for their operators
+    operator_0_client.sync().await;
+    assert_eq!(operator_0_client.data().peg_out_graphs.len(), 2);
+    for (peg_out_graph, operator_public_key) in operator_0_client
+        .data()
+        .peg_out_graphs
+        .iter()
+        .zip(registry.operator_public_keys.iter())
+    {
+        assert_eq!(peg_out_graph.operator_public_key(), operator_public_key);
+    }
+}
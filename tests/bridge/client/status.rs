@@ -0,0 +1,141 @@
+use bitcoin::Amount;
+
+use bitvm::bridge::{
+    client::chain::simulator::Simulator,
+    graphs::{
+        base::{FEE_AMOUNT, INITIAL_AMOUNT},
+        peg_in::{PegInDepositorStatus, PegInGraph},
+        status::{GraphRole, GraphStatus, GraphStatusReport},
+    },
+    scripts::generate_pay_to_pubkey_script_address,
+    transactions::base::{BaseTransaction, Input},
+};
+
+use super::super::setup::setup_test_with_chain;
+
+#[tokio::test]
+async fn test_peg_in_depositor_status_report() {
+    let simulator = Simulator::new();
+    let (client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    );
+    let peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+
+    let report = peg_in_graph
+        .status_report(client.chain.as_ref(), GraphRole::Depositor)
+        .await;
+    assert_eq!(
+        report.status,
+        Some(GraphStatus::PegInDepositor(
+            PegInDepositorStatus::PegInDepositWait
+        ))
+    );
+    assert_eq!(report.next_action, None);
+    assert!(!report.transaction("Peg-in deposit").unwrap().confirmed);
+    assert_eq!(
+        report.timelock("Peg-in refund").unwrap().blocks_remaining,
+        None
+    );
+
+    let deposit_tx = peg_in_graph.peg_in_deposit_transaction_ref().finalize();
+    assert!(client.chain.broadcast(&deposit_tx).await.is_ok());
+    simulator.mine_blocks(1);
+    let deposit_height = client.chain.get_height().await.unwrap();
+
+    let report = peg_in_graph
+        .status_report(client.chain.as_ref(), GraphRole::Depositor)
+        .await;
+    assert_eq!(
+        report.status,
+        Some(GraphStatus::PegInDepositor(
+            PegInDepositorStatus::PegInConfirmWait
+        ))
+    );
+    let deposit_report = report.transaction("Peg-in deposit").unwrap();
+    assert_eq!(deposit_report.txid, deposit_tx.compute_txid());
+    assert!(deposit_report.confirmed);
+    assert_eq!(deposit_report.block_height, Some(deposit_height));
+    let refund_timelock = report.timelock("Peg-in refund").unwrap();
+    assert_eq!(
+        refund_timelock.expiry_height,
+        Some(deposit_height + peg_in_graph.parameters().peg_in_refund_timelock)
+    );
+    assert_eq!(
+        refund_timelock.blocks_remaining,
+        Some(peg_in_graph.parameters().peg_in_refund_timelock)
+    );
+
+    simulator.mine_blocks(peg_in_graph.parameters().peg_in_refund_timelock);
+
+    let report = peg_in_graph
+        .status_report(client.chain.as_ref(), GraphRole::Depositor)
+        .await;
+    assert_eq!(
+        report.status,
+        Some(GraphStatus::PegInDepositor(
+            PegInDepositorStatus::PegInRefundAvailable
+        ))
+    );
+    assert_eq!(report.next_action, Some(String::from("peg-in refund")));
+    assert_eq!(
+        report.timelock("Peg-in refund").unwrap().blocks_remaining,
+        Some(0)
+    );
+
+    let json = serde_json::to_string(&report).unwrap();
+    let deserialized_report: GraphStatusReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized_report, report);
+}
+
+#[tokio::test]
+async fn test_client_status_reports_every_role() {
+    let simulator = Simulator::new();
+    let (mut client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    );
+    client
+        .create_peg_in_graph(Input { outpoint, amount }, &evm_address)
+        .await
+        .unwrap();
+
+    let reports = client.status().await.unwrap();
+
+    // no peg-out graph has been created yet, so the operator and the verifier report it missing
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].role, GraphRole::Depositor);
+    assert_eq!(
+        reports[0].status,
+        Some(GraphStatus::PegInDepositor(
+            PegInDepositorStatus::PegInDepositWait
+        ))
+    );
+    assert_eq!(reports[1].role, GraphRole::Operator);
+    assert_eq!(reports[1].graph_id, reports[0].graph_id);
+    assert!(reports[1].status.is_none());
+    assert!(reports[1].error.is_some());
+    assert_eq!(
+        reports[1].next_action,
+        Some(String::from("create peg-out graph"))
+    );
+    assert_eq!(reports[2].role, GraphRole::Verifier);
+    assert!(reports[2].status.is_none());
+    assert!(reports[2].error.is_some());
+    assert_eq!(reports[2].next_action, None);
+}
@@ -1,12 +1,36 @@
-use bitcoin::Amount;
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bitcoin::{key::Keypair, Amount, Network};
 
 use bitvm::bridge::{
-    graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
+    client::{
+        chain::simulator::Simulator, client::BitVMClient, data_store::local_file::LocalFile,
+        policy::ProcessingPolicy,
+    },
+    contexts::{base::generate_keys_from_keypair, verifier::VerifierContext},
+    error::BridgeError,
+    graphs::{
+        base::{BaseGraph, PreSigningState, FEE_AMOUNT, INITIAL_AMOUNT},
+        peg_out::{PegOutRequest, PegOutWithdrawerStatus},
+        status::{GraphRole, GraphStatus},
+    },
+    keys::{key_chain::KeyChain, secret_nonce_store::SecretNonceStore},
+    registry::ParticipantRegistry,
     scripts::generate_pay_to_pubkey_script_address,
     transactions::base::Input,
 };
 
-use super::super::{helper::generate_stub_outpoint, setup::setup_test};
+use super::super::{
+    helper::generate_stub_outpoint,
+    setup::{
+        depositor_key_chain, depositor_keypair, operator_keypair, setup_test, verifier_0_keypair,
+        verifier_1_keypair, withdrawer_destination_address, withdrawer_key_chain,
+        withdrawer_keypair, EVM_ADDRESS,
+    },
+};
 
 #[tokio::test]
 async fn test_sync() {
@@ -27,11 +51,506 @@ async fn test_sync() {
 
     client
         .create_peg_in_graph(Input { outpoint, amount }, &evm_address)
-        .await;
+        .await
+        .unwrap();
 
     println!("Save to remote");
-    client.flush().await;
+    let result = client.flush().await;
+    assert!(result.is_ok());
 
     println!("Read from remote");
     client.sync().await;
 }
+
+const EVM_BURN_TX_HASH: &str = "0x5f1c6b4f0a2e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c";
+
+fn setup_registry() -> ParticipantRegistry {
+    let network = Network::Testnet;
+    let operator_public_key = generate_keys_from_keypair(network, &operator_keypair()).2;
+    let verifier_public_keys = [verifier_0_keypair(), verifier_1_keypair()]
+        .iter()
+        .map(|keypair| generate_keys_from_keypair(network, keypair).2)
+        .collect();
+
+    ParticipantRegistry::new(network, &vec![operator_public_key], &verifier_public_keys).unwrap()
+}
+
+fn create_data_store_directory() -> String {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    std::env::temp_dir()
+        .join(format!("bridge-client-sync-test-{}", time))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn create_client(
+    registry: &ParticipantRegistry,
+    directory: &str,
+    simulator: &Simulator,
+    depositor_key_chain: Option<&KeyChain>,
+    verifier_keypair: Option<&Keypair>,
+) -> BitVMClient {
+    BitVMClient::new(
+        registry,
+        depositor_key_chain,
+        None,
+        verifier_keypair,
+        None,
+        Some(Box::new(LocalFile::new(directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap()
+}
+
+// Creates a peg-in graph with the first client, shares it with the second one and confirms the
+// deposit, so the verifiers of both clients can presign it.
+async fn setup_shared_peg_in_graph(
+    registry: &ParticipantRegistry,
+    simulator: &Simulator,
+    client_0: &mut BitVMClient,
+    client_1: &mut BitVMClient,
+) {
+    let depositor_public_key = generate_keys_from_keypair(registry.network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+        amount,
+    );
+    client_0
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+    client_0.flush().await.unwrap();
+
+    client_1.sync().await;
+    assert_eq!(client_1.data().peg_in_graphs.len(), 1);
+
+    let peg_in_graph_id = client_0.data().peg_in_graphs[0].id().clone();
+    client_0
+        .broadcast_peg_in_deposit(&peg_in_graph_id)
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+}
+
+#[tokio::test]
+async fn test_concurrent_changes_to_the_same_graph_are_merged() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry();
+    let mut client_0 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        Some(&verifier_0_keypair()),
+    )
+    .await;
+    let mut client_1 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        None,
+        Some(&verifier_1_keypair()),
+    )
+    .await;
+    setup_shared_peg_in_graph(&registry, &simulator, &mut client_0, &mut client_1).await;
+
+    // both verifiers push their nonces to the same version of the graph
+    let policy = ProcessingPolicy::default();
+    assert!(client_0.process(&policy).await.unwrap());
+    assert!(client_1.process(&policy).await.unwrap());
+    client_0.flush().await.unwrap();
+    client_1.flush().await.unwrap();
+
+    // the second save merged the nonces of the first one instead of overwriting them
+    client_0.sync().await;
+    assert_eq!(client_0.data().version, client_1.data().version);
+    let verifier_context = VerifierContext::from_keypair(
+        registry.network,
+        &verifier_0_keypair(),
+        &registry.verifier_public_keys,
+    )
+    .unwrap();
+    assert!(client_0.data().peg_in_graphs[0].has_all_nonces(&verifier_context));
+    assert!(client_1.data().peg_in_graphs[0].has_all_nonces(&verifier_context));
+}
+
+#[tokio::test]
+async fn test_conflicting_changes_to_the_same_graph_are_rejected() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry();
+    let mut client_0 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        Some(&verifier_0_keypair()),
+    )
+    .await;
+    // a second instance of the same verifier generates different nonces
+    let mut client_1 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        None,
+        Some(&verifier_0_keypair()),
+    )
+    .await;
+    setup_shared_peg_in_graph(&registry, &simulator, &mut client_0, &mut client_1).await;
+
+    let policy = ProcessingPolicy::default();
+    assert!(client_0.process(&policy).await.unwrap());
+    assert!(client_1.process(&policy).await.unwrap());
+    client_0.flush().await.unwrap();
+
+    let result = client_1.flush().await;
+    assert!(matches!(result, Err(BridgeError::GraphMerge(_))));
+
+    // the conflicting nonces were not saved
+    client_0.sync().await;
+    let verifier_context = VerifierContext::from_keypair(
+        registry.network,
+        &verifier_0_keypair(),
+        &registry.verifier_public_keys,
+    )
+    .unwrap();
+    assert!(client_0.data().peg_in_graphs[0].has_nonces_of(&verifier_context));
+    assert!(!client_0.data().peg_in_graphs[0].has_all_nonces(&verifier_context));
+}
+
+#[tokio::test]
+async fn test_verifier_restarting_mid_pre_signing_round_can_pre_sign() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let secret_nonce_store_path = PathBuf::from(format!("{}-secret-nonces.json", directory));
+    let registry = setup_registry();
+    let mut client_0 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        Some(&verifier_0_keypair()),
+    )
+    .await;
+    client_0
+        .store_verifier_secret_nonces(&secret_nonce_store_path)
+        .unwrap();
+    let mut client_1 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        None,
+        Some(&verifier_1_keypair()),
+    )
+    .await;
+    setup_shared_peg_in_graph(&registry, &simulator, &mut client_0, &mut client_1).await;
+
+    let policy = ProcessingPolicy::default();
+    assert!(client_0.process(&policy).await.unwrap());
+    client_0.flush().await.unwrap();
+    assert!(secret_nonce_store_path.exists());
+
+    // the verifier restarts after publishing its nonces and before pre-signing
+    drop(client_0);
+    let mut client_0 = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        Some(&verifier_0_keypair()),
+    )
+    .await;
+    client_0
+        .store_verifier_secret_nonces(&secret_nonce_store_path)
+        .unwrap();
+
+    for _ in 0..3 {
+        for client in [&mut client_1, &mut client_0] {
+            client.sync().await;
+            client.process(&policy).await.unwrap();
+            client.flush().await.unwrap();
+        }
+    }
+
+    client_0.sync().await;
+    assert_eq!(
+        client_0.data().peg_in_graphs[0].pre_signing_state(),
+        PreSigningState::Presigned
+    );
+
+    // the secret nonces of a presigned graph are not kept
+    let verifier_context = VerifierContext::from_keypair(
+        registry.network,
+        &verifier_0_keypair(),
+        &registry.verifier_public_keys,
+    )
+    .unwrap();
+    let secret_nonce_store =
+        SecretNonceStore::new(&secret_nonce_store_path, &verifier_context.verifier_keypair);
+    assert!(secret_nonce_store.load().unwrap().is_empty());
+
+    // the secret nonces can only be read back with the key of the verifier
+    let verifier_context = VerifierContext::from_keypair(
+        registry.network,
+        &verifier_1_keypair(),
+        &registry.verifier_public_keys,
+    )
+    .unwrap();
+    let secret_nonce_store =
+        SecretNonceStore::new(&secret_nonce_store_path, &verifier_context.verifier_keypair);
+    assert!(matches!(
+        secret_nonce_store.load(),
+        Err(BridgeError::Keystore(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_graphs_of_depositor_are_fetched_by_other_clients() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry();
+    // the depositor is not a registered participant
+    let mut depositor_client = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        None,
+    )
+    .await;
+    let mut verifier_client = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        None,
+        Some(&verifier_1_keypair()),
+    )
+    .await;
+
+    let depositor_public_key = generate_keys_from_keypair(registry.network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+        amount,
+    );
+    depositor_client
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+    depositor_client.flush().await.unwrap();
+
+    verifier_client.sync().await;
+    assert_eq!(verifier_client.data().peg_in_graphs.len(), 1);
+    assert_eq!(
+        verifier_client.data().peg_in_graphs[0].id(),
+        depositor_client.data().peg_in_graphs[0].id()
+    );
+
+    // the verifier takes the graph over into the data it saves
+    verifier_client.flush().await.unwrap();
+    depositor_client.sync().await;
+    assert_eq!(depositor_client.data().peg_in_graphs.len(), 1);
+    assert_eq!(
+        depositor_client.data().version,
+        verifier_client.data().version
+    );
+}
+
+#[tokio::test]
+async fn test_depositor_client_uses_a_fresh_key_for_every_peg_in_graph() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry();
+    let mut depositor_client = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        None,
+    )
+    .await;
+    let mut verifier_client = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        None,
+        Some(&verifier_1_keypair()),
+    )
+    .await;
+
+    let mut depositor_public_keys = vec![];
+    for _ in 0..2 {
+        let depositor_public_key = depositor_client.next_depositor_public_key().unwrap();
+        let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+        let outpoint = simulator.fund_address(
+            &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+            amount,
+        );
+        depositor_client
+            .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+            .await
+            .unwrap();
+        depositor_client.flush().await.unwrap();
+        depositor_public_keys.push(depositor_public_key);
+    }
+    assert_eq!(
+        depositor_public_keys[0],
+        generate_keys_from_keypair(registry.network, &depositor_keypair()).2
+    );
+    assert_ne!(depositor_public_keys[0], depositor_public_keys[1]);
+    assert_eq!(depositor_client.depositor_status().await.unwrap().len(), 2);
+
+    // each graph was saved with its own key, the verifier trusts the depositor with both
+    verifier_client.sync().await;
+    assert_eq!(verifier_client.data().peg_in_graphs.len(), 2);
+
+    // a restarted depositor client continues with the next key
+    let restarted_depositor_client = create_client(
+        &registry,
+        &directory,
+        &simulator,
+        Some(&depositor_key_chain()),
+        None,
+    )
+    .await;
+    assert_eq!(
+        restarted_depositor_client
+            .depositor_status()
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    let next_depositor_public_key = restarted_depositor_client
+        .next_depositor_public_key()
+        .unwrap();
+    assert!(!depositor_public_keys.contains(&next_depositor_public_key));
+}
+
+#[tokio::test]
+async fn test_peg_out_request_is_picked_up_by_operator_client() {
+    let simulator = Simulator::new();
+    let directory = create_data_store_directory();
+    let registry = setup_registry();
+    let mut operator_client = BitVMClient::new(
+        &registry,
+        Some(&depositor_key_chain()),
+        Some(&operator_keypair()),
+        None,
+        None,
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+    // the withdrawer is not a registered participant
+    let mut withdrawer_client = BitVMClient::new(
+        &registry,
+        None,
+        None,
+        None,
+        Some(&withdrawer_key_chain()),
+        Some(Box::new(LocalFile::new(&directory))),
+        Some(Box::new(simulator.clone())),
+    )
+    .await
+    .unwrap();
+
+    let depositor_public_key = generate_keys_from_keypair(registry.network, &depositor_keypair()).2;
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &depositor_public_key),
+        amount,
+    );
+    operator_client
+        .create_peg_in_graph(Input { outpoint, amount }, EVM_ADDRESS)
+        .await
+        .unwrap();
+    let peg_in_graph_id = operator_client.data().peg_in_graphs[0].id().clone();
+    let kick_off_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let kick_off_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &registry.operator_public_keys[0]),
+        kick_off_amount,
+    );
+    operator_client
+        .create_peg_out_graph(
+            &peg_in_graph_id,
+            Input {
+                outpoint: kick_off_outpoint,
+                amount: kick_off_amount,
+            },
+        )
+        .await
+        .unwrap();
+    operator_client.flush().await.unwrap();
+
+    withdrawer_client.sync().await;
+    withdrawer_client
+        .request_peg_out(
+            &peg_in_graph_id,
+            EVM_ADDRESS,
+            EVM_BURN_TX_HASH,
+            &withdrawer_destination_address(registry.network),
+        )
+        .await
+        .unwrap();
+    withdrawer_client.flush().await.unwrap();
+    let reports = withdrawer_client.withdrawer_status().await.unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].role, GraphRole::Withdrawer);
+    assert_eq!(
+        reports[0].status,
+        Some(GraphStatus::PegOutWithdrawer(
+            PegOutWithdrawerStatus::PegOutRequested
+        ))
+    );
+
+    // the operator client picks up the request, the peg-out only waits for the pre-signing now
+    operator_client.sync().await;
+    let withdrawer_public_key =
+        generate_keys_from_keypair(registry.network, &withdrawer_keypair()).2;
+    let peg_out_graph = &operator_client.data().peg_out_graphs[0];
+    assert_eq!(
+        peg_out_graph.peg_out_request(),
+        Some(PegOutRequest {
+            peg_in_graph_id: peg_in_graph_id.clone(),
+            withdrawer_public_key,
+            withdrawer_evm_address: String::from(EVM_ADDRESS),
+            evm_burn_tx_hash: String::from(EVM_BURN_TX_HASH),
+            destination_script_pubkey: withdrawer_destination_address(registry.network)
+                .script_pubkey(),
+        })
+    );
+    let peg_out_graph_id = peg_out_graph.id().clone();
+    let peg_in_amount = Amount::from_sat(INITIAL_AMOUNT);
+    let peg_out_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(registry.network, &registry.operator_public_keys[0]),
+        peg_in_amount,
+    );
+    let result = operator_client
+        .peg_out(
+            &peg_out_graph_id,
+            Input {
+                outpoint: peg_out_outpoint,
+                amount: peg_in_amount,
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(BridgeError::PreSigning(_))));
+
+    // the request is kept once the operator saves
+    operator_client.flush().await.unwrap();
+    withdrawer_client.sync().await;
+    assert_eq!(
+        withdrawer_client.withdrawer_status().await.unwrap().len(),
+        1
+    );
+}
@@ -0,0 +1,338 @@
+use bitcoin::{Amount, XOnlyPublicKey};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use bitvm::bridge::{
+    client::chain::simulator::Simulator,
+    commitments::generate_commit_y_public_key,
+    connectors::{connector::TaprootConnector, connector_y::ConnectorY},
+    graphs::{
+        base::{PreSigningState, FEE_AMOUNT, INITIAL_AMOUNT},
+        peg_in::PegInGraph,
+        peg_out::{generate_id, PegOutGraph},
+    },
+    scripts::generate_pay_to_pubkey_script_address,
+    transactions::base::Input,
+};
+
+use super::super::{
+    helper::generate_stub_outpoint,
+    setup::{setup_test, setup_test_with_chain},
+};
+
+#[tokio::test]
+async fn test_graph_verification() {
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    )
+    .await;
+
+    let mut peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    let kick_off_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let kick_off_outpoint = generate_stub_outpoint(
+        &client,
+        &ConnectorY::new(
+            operator_context.network,
+            &operator_context.operator_taproot_public_key,
+            &generate_commit_y_public_key(
+                &operator_context,
+                &generate_id(&peg_in_graph, &operator_context.operator_public_key),
+            ),
+        )
+        .generate_taproot_address(),
+        kick_off_amount,
+    )
+    .await;
+
+    let mut peg_out_graph = PegOutGraph::new(
+        &operator_context,
+        &peg_in_graph,
+        Input {
+            outpoint: kick_off_outpoint,
+            amount: kick_off_amount,
+        },
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    let peg_in_result = peg_in_graph.verify(&verifier_contexts[0].n_of_n_taproot_public_key);
+    assert!(peg_in_result.is_ok());
+
+    let peg_out_result = peg_out_graph.verify(
+        &peg_in_graph,
+        &verifier_contexts[0].n_of_n_taproot_public_key,
+    );
+    assert!(peg_out_result.is_ok());
+
+    // graphs generated for a different n-of-n key must be rejected
+    let peg_in_result = peg_in_graph.verify(&operator_context.operator_taproot_public_key);
+    assert!(peg_in_result.is_err());
+
+    let peg_out_result =
+        peg_out_graph.verify(&peg_in_graph, &operator_context.operator_taproot_public_key);
+    assert!(peg_out_result.is_err());
+}
+
+async fn create_presigned_graphs(
+    simulator: &Simulator,
+) -> (PegInGraph, PegOutGraph, XOnlyPublicKey) {
+    let (
+        _,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    );
+    let mut peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+
+    let kick_off_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let kick_off_outpoint = simulator.fund_address(
+        &ConnectorY::new(
+            operator_context.network,
+            &operator_context.operator_taproot_public_key,
+            &generate_commit_y_public_key(
+                &operator_context,
+                &generate_id(&peg_in_graph, &operator_context.operator_public_key),
+            ),
+        )
+        .generate_taproot_address(),
+        kick_off_amount,
+    );
+    let mut peg_out_graph = PegOutGraph::new(
+        &operator_context,
+        &peg_in_graph,
+        Input {
+            outpoint: kick_off_outpoint,
+            amount: kick_off_amount,
+        },
+    );
+
+    let mut peg_in_secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    let mut peg_out_secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (i, verifier_context) in verifier_contexts.iter().enumerate() {
+        peg_in_graph
+            .pre_sign(verifier_context, &mut peg_in_secret_nonces[i])
+            .unwrap();
+        peg_out_graph
+            .pre_sign(verifier_context, &mut peg_out_secret_nonces[i])
+            .unwrap();
+    }
+    assert_eq!(peg_in_graph.pre_signing_state(), PreSigningState::Presigned);
+    assert_eq!(
+        peg_out_graph.pre_signing_state(),
+        PreSigningState::Presigned
+    );
+
+    (
+        peg_in_graph,
+        peg_out_graph,
+        operator_context.n_of_n_taproot_public_key,
+    )
+}
+
+// Applies the change to the json of the graph, as a malicious writer of the data store could.
+fn tamper<T: Serialize + DeserializeOwned>(graph: &T, change: impl FnOnce(&mut Value)) -> T {
+    let mut json = serde_json::to_value(graph).unwrap();
+    change(&mut json);
+    serde_json::from_value(json).unwrap()
+}
+
+// Flips the first bit of the hex encoded bytes.
+fn flip_bit(hex: &mut Value) {
+    let mut bytes = hex::decode(hex.as_str().unwrap()).unwrap();
+    bytes[0] ^= 1;
+    *hex = Value::String(hex::encode(bytes));
+}
+
+#[tokio::test]
+async fn test_graph_verification_rejects_tampered_witnesses() {
+    let simulator = Simulator::new();
+    let (peg_in_graph, peg_out_graph, n_of_n_taproot_public_key) =
+        create_presigned_graphs(&simulator).await;
+
+    // invalid depositor signature of the peg-in confirm
+    let tampered_peg_in_graph = tamper(&peg_in_graph, |json| {
+        flip_bit(&mut json["peg_in_confirm_transaction"]["tx"]["input"][0]["witness"][0])
+    });
+    assert!(tampered_peg_in_graph
+        .verify(&n_of_n_taproot_public_key)
+        .is_err());
+
+    // invalid aggregated n-of-n signature of the take 1
+    let tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        flip_bit(&mut json["take1_transaction"]["tx"]["input"][0]["witness"][0])
+    });
+    assert!(tampered_peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_err());
+
+    // the n-of-n signature of the take 1 is missing, the leaf script expects it
+    let tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        json["take1_transaction"]["tx"]["input"][0]["witness"]
+            .as_array_mut()
+            .unwrap()
+            .remove(0);
+    });
+    assert!(tampered_peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_err());
+
+    // the take 1 spends a leaf other than the expected one
+    let tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        let witness = json["take1_transaction"]["tx"]["input"][0]["witness"].clone();
+        json["take1_transaction"]["tx"]["input"][0]["witness"] =
+            json["take1_transaction"]["tx"]["input"][3]["witness"].clone();
+        json["take1_transaction"]["tx"]["input"][3]["witness"] = witness;
+    });
+    assert!(tampered_peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_graph_verification_rejects_tampered_transactions() {
+    let simulator = Simulator::new();
+    let (peg_in_graph, peg_out_graph, n_of_n_taproot_public_key) =
+        create_presigned_graphs(&simulator).await;
+
+    // the peg-in confirm pays a different amount than the verifiers signed
+    let tampered_peg_in_graph = tamper(&peg_in_graph, |json| {
+        let value = json["peg_in_confirm_transaction"]["tx"]["output"][0]["value"]
+            .as_u64()
+            .unwrap();
+        json["peg_in_confirm_transaction"]["tx"]["output"][0]["value"] = Value::from(value - 1);
+    });
+    assert!(tampered_peg_in_graph
+        .verify(&n_of_n_taproot_public_key)
+        .is_err());
+
+    // the take 1 pays a different amount than the verifiers signed
+    let tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        let value = json["take1_transaction"]["tx"]["output"][0]["value"]
+            .as_u64()
+            .unwrap();
+        json["take1_transaction"]["tx"]["output"][0]["value"] = Value::from(value - 1);
+    });
+    assert!(tampered_peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_err());
+
+    // the take 1 pays to a different script than the verifiers signed
+    let tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        flip_bit(&mut json["take1_transaction"]["tx"]["output"][0]["script_pubkey"])
+    });
+    assert!(tampered_peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_err());
+
+    // the untampered graphs still verify
+    assert!(peg_in_graph.verify(&n_of_n_taproot_public_key).is_ok());
+    assert!(peg_out_graph
+        .verify(&peg_in_graph, &n_of_n_taproot_public_key)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_pre_signing_state_is_recomputed_from_the_witnesses() {
+    let simulator = Simulator::new();
+    let (peg_in_graph, peg_out_graph, _) = create_presigned_graphs(&simulator).await;
+
+    // the stored state still claims the graph is presigned
+    let mut tampered_peg_out_graph = tamper(&peg_out_graph, |json| {
+        flip_bit(&mut json["take1_transaction"]["tx"]["input"][0]["witness"][0])
+    });
+    assert_eq!(
+        tampered_peg_out_graph.pre_signing_state(),
+        PreSigningState::Presigned
+    );
+    tampered_peg_out_graph.recompute_pre_signing_state();
+    assert_eq!(
+        tampered_peg_out_graph.pre_signing_state(),
+        PreSigningState::AwaitingSignatures
+    );
+
+    let mut tampered_peg_in_graph = tamper(&peg_in_graph, |json| {
+        json["peg_in_confirm_transaction"]["musig2_nonces"] = Value::Object(Default::default());
+    });
+    tampered_peg_in_graph.recompute_pre_signing_state();
+    assert_eq!(
+        tampered_peg_in_graph.pre_signing_state(),
+        PreSigningState::AwaitingNonces
+    );
+
+    // untampered graphs stay presigned
+    let mut peg_out_graph = peg_out_graph;
+    peg_out_graph.recompute_pre_signing_state();
+    assert_eq!(
+        peg_out_graph.pre_signing_state(),
+        PreSigningState::Presigned
+    );
+}
@@ -0,0 +1,60 @@
+use bitvm::{
+    bridge::commitments::{
+        generate_commit_y_public_key, generate_commit_y_verify_script, sign_commit_y,
+        verify_commit_y, CommitmentValue,
+    },
+    treepp::*,
+};
+
+use super::super::{helper::PEG_OUT_GRAPH_ID, setup::setup_test};
+
+const VALUE: CommitmentValue = [
+    0x00, 0x01, 0x02, 0x10, 0x7f, 0x80, 0xff, 0x21, 0x43, 0x65, 0x87, 0xa9, 0xcb, 0xed, 0x0f, 0xf0,
+    0x55, 0xaa, 0x33, 0xcc,
+];
+
+#[tokio::test]
+async fn test_commit_y_roundtrip() {
+    let (_, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
+    let public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let signature = sign_commit_y(&operator_context, PEG_OUT_GRAPH_ID, &VALUE);
+
+    assert_eq!(verify_commit_y(&public_key, &signature).unwrap(), VALUE);
+
+    // signed with the key of another graph
+    let other_public_key =
+        generate_commit_y_public_key(&operator_context, "OTHER_PEG_OUT_GRAPH_ID");
+    assert!(verify_commit_y(&other_public_key, &signature).is_err());
+
+    // tampered digit
+    let mut tampered_signature = signature.clone();
+    tampered_signature[1] = vec![15];
+    assert!(verify_commit_y(&public_key, &tampered_signature).is_err());
+}
+
+#[tokio::test]
+async fn test_commit_y_verify_script() {
+    let (_, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
+    let public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let signature = sign_commit_y(&operator_context, PEG_OUT_GRAPH_ID, &VALUE);
+
+    let script = script! {
+        for element in signature.iter() {
+            { element.clone() }
+        }
+        { generate_commit_y_verify_script(&public_key) }
+        OP_TRUE
+    };
+    assert!(execute_script(script).success);
+
+    // signed with the key of another graph
+    let other_signature = sign_commit_y(&operator_context, "OTHER_PEG_OUT_GRAPH_ID", &VALUE);
+    let script = script! {
+        for element in other_signature.iter() {
+            { element.clone() }
+        }
+        { generate_commit_y_verify_script(&public_key) }
+        OP_TRUE
+    };
+    assert!(!execute_script(script).success);
+}
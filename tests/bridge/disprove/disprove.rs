@@ -20,8 +20,22 @@ mod tests {
 
     #[tokio::test]
     async fn test_should_be_able_to_submit_disprove_tx_successfully() {
-        let (client, _, operator_context, verifier_context, _, _, _, connector_c, _, _, _, _, _, _) =
-            setup_test().await;
+        let (
+            client,
+            _,
+            operator_context,
+            verifier_contexts,
+            _,
+            _,
+            _,
+            connector_c,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = setup_test().await;
 
         let amount_0 = Amount::from_sat(DUST_AMOUNT);
         let outpoint_0 =
@@ -46,10 +60,20 @@ mod tests {
             1,
         );
 
-        disprove_tx.pre_sign(&verifier_context);
+        let mut secret_nonces: Vec<_> = verifier_contexts
+            .iter()
+            .map(|verifier_context| disprove_tx.push_nonces(verifier_context))
+            .collect();
+        for (verifier_context, secret_nonces) in
+            verifier_contexts.iter().zip(secret_nonces.iter_mut())
+        {
+            disprove_tx
+                .pre_sign(verifier_context, secret_nonces)
+                .unwrap();
+        }
         let tx = disprove_tx.finalize();
         println!("Script Path Spend Transaction: {:?}\n", tx);
-        let result = client.esplora.broadcast(&tx).await;
+        let result = client.chain.broadcast(&tx).await;
         println!("Txid: {:?}", tx.compute_txid());
         println!("Broadcast result: {:?}\n", result);
         println!("Transaction hex: \n{}", serialize_hex(&tx));
@@ -59,8 +83,22 @@ mod tests {
     #[tokio::test]
     async fn test_should_be_able_to_submit_disprove_tx_with_verifier_added_to_output_successfully()
     {
-        let (client, _, operator_context, verifier_context, _, _, _, connector_c, _, _, _, _, _, _) =
-            setup_test().await;
+        let (
+            client,
+            _,
+            operator_context,
+            verifier_contexts,
+            _,
+            _,
+            _,
+            connector_c,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = setup_test().await;
 
         let amount_0 = Amount::from_sat(DUST_AMOUNT);
         let outpoint_0 =
@@ -85,10 +123,20 @@ mod tests {
             1,
         );
 
-        disprove_tx.pre_sign(&verifier_context);
+        let mut secret_nonces: Vec<_> = verifier_contexts
+            .iter()
+            .map(|verifier_context| disprove_tx.push_nonces(verifier_context))
+            .collect();
+        for (verifier_context, secret_nonces) in
+            verifier_contexts.iter().zip(secret_nonces.iter_mut())
+        {
+            disprove_tx
+                .pre_sign(verifier_context, secret_nonces)
+                .unwrap();
+        }
         let mut tx = disprove_tx.finalize();
 
-        let secp = verifier_context.secp;
+        let secp = &verifier_contexts[0].secp;
         let verifier_secret: &str =
             "aaaaaaaaaabbbbbbbbbbccccccccccddddddddddeeeeeeeeeeffffffffff1234";
         let verifier_keypair = Keypair::from_seckey_str(&secp, verifier_secret).unwrap();
@@ -103,7 +151,7 @@ mod tests {
         tx.output.push(verifier_output);
 
         println!("Script Path Spend Transaction: {:?}\n", tx);
-        let result = client.esplora.broadcast(&tx).await;
+        let result = client.chain.broadcast(&tx).await;
         println!("Txid: {:?}", tx.compute_txid());
         println!("Broadcast result: {:?}\n", result);
         println!("Transaction hex: \n{}", serialize_hex(&tx));
@@ -0,0 +1,136 @@
+use bitcoin::{Amount, FeeRate, Weight};
+
+use bitvm::bridge::{
+    commitments::{generate_commit_y_public_key, sign_commit_y, COMMITMENT_VALUE_LENGTH},
+    connectors::{connector::TaprootConnector, connector_y::ConnectorY},
+    error::BridgeError,
+    graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
+    scripts::generate_pay_to_pubkey_script_address,
+    transactions::{
+        base::{BaseTransaction, Input},
+        cpfp::CpfpTransaction,
+        fee::{calculate_fee, estimate_weight, get_fee},
+        kick_off::KickOffTransaction,
+    },
+};
+
+use super::super::{
+    helper::{generate_stub_outpoint, PEG_OUT_GRAPH_ID},
+    setup::setup_test,
+};
+
+#[tokio::test]
+async fn test_cpfp_tx_pays_for_parent() {
+    let (client, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let commit_y_public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let connector_y = ConnectorY::new(
+        operator_context.network,
+        &operator_context.operator_taproot_public_key,
+        &commit_y_public_key,
+    );
+    let outpoint =
+        generate_stub_outpoint(&client, &connector_y.generate_taproot_address(), amount).await;
+    let mut kick_off = KickOffTransaction::new(
+        &operator_context,
+        Input { outpoint, amount },
+        &commit_y_public_key,
+    );
+    kick_off
+        .add_commit_y(&sign_commit_y(
+            &operator_context,
+            PEG_OUT_GRAPH_ID,
+            &[0u8; COMMITMENT_VALUE_LENGTH],
+        ))
+        .unwrap();
+    let kick_off_tx = kick_off.finalize();
+    assert_eq!(estimate_weight(&kick_off), kick_off_tx.weight());
+    // the presigned fee covers the kick-off once the commitment to y is added
+    assert!(
+        get_fee(&kick_off)
+            >= calculate_fee(
+                kick_off_tx.weight(),
+                operator_context.parameters.presigned_fee_rate()
+            )
+    );
+
+    let funding_amount = Amount::from_sat(INITIAL_AMOUNT);
+    let funding_outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            operator_context.network,
+            &operator_context.operator_public_key,
+        ),
+        funding_amount,
+    )
+    .await;
+    let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+    let cpfp = CpfpTransaction::new(
+        &operator_context,
+        &kick_off_tx,
+        get_fee(&kick_off),
+        kick_off.anchor_vout() as u32,
+        &vec![Input {
+            outpoint: funding_outpoint,
+            amount: funding_amount,
+        }],
+        &operator_context.operator_keypair,
+        fee_rate,
+    )
+    .unwrap();
+    let cpfp_tx = cpfp.finalize();
+    assert_eq!(cpfp.parent_txid(), kick_off_tx.compute_txid());
+
+    // parent and child together pay the fee rate, without overpaying by more than the signature
+    // length estimate
+    let package_weight = kick_off_tx.weight() + cpfp_tx.weight();
+    let package_fee = get_fee(&kick_off) + get_fee(&cpfp);
+    assert!(package_fee >= calculate_fee(package_weight, fee_rate));
+    assert!(package_fee <= calculate_fee(package_weight + Weight::from_wu(8), fee_rate));
+}
+
+#[tokio::test]
+async fn test_cpfp_tx_rejects_unspendable_or_insufficient_inputs() {
+    let (client, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let commit_y_public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let connector_y = ConnectorY::new(
+        operator_context.network,
+        &operator_context.operator_taproot_public_key,
+        &commit_y_public_key,
+    );
+    let outpoint =
+        generate_stub_outpoint(&client, &connector_y.generate_taproot_address(), amount).await;
+    let kick_off = KickOffTransaction::new(
+        &operator_context,
+        Input { outpoint, amount },
+        &commit_y_public_key,
+    );
+    let kick_off_tx = kick_off.finalize();
+
+    // output 0 is not the operator's anchor
+    let result = CpfpTransaction::new(
+        &operator_context,
+        &kick_off_tx,
+        get_fee(&kick_off),
+        0,
+        &vec![],
+        &operator_context.operator_keypair,
+        FeeRate::from_sat_per_vb(1).unwrap(),
+    );
+    assert!(matches!(result, Err(BridgeError::FeeBumping(_))));
+
+    // the anchor alone cannot pay for the package
+    let result = CpfpTransaction::new(
+        &operator_context,
+        &kick_off_tx,
+        get_fee(&kick_off),
+        kick_off.anchor_vout() as u32,
+        &vec![],
+        &operator_context.operator_keypair,
+        FeeRate::from_sat_per_vb(50).unwrap(),
+    );
+    assert!(matches!(result, Err(BridgeError::FeeBumping(_))));
+}
@@ -10,6 +10,7 @@ pub async fn generate_stub_outpoint(
     let funding_utxo = client
         .get_initial_utxo(funding_utxo_address.clone(), input_value)
         .await
+        .unwrap()
         .unwrap_or_else(|| {
             panic!(
                 "Fund {:?} with {} sats at https://faucet.mutinynet.com/",
@@ -22,3 +23,7 @@ pub async fn generate_stub_outpoint(
         vout: funding_utxo.vout,
     }
 }
+
+// Stands in for the id of the peg-out graph of kick-offs created without a graph, the operator
+// derives the key committing to y from it.
+pub const PEG_OUT_GRAPH_ID: &str = "PEG_OUT_GRAPH_ID";
@@ -0,0 +1,1284 @@
+use bitcoin::{Address, Amount, FeeRate, ScriptBuf};
+
+use bitvm::bridge::{
+    client::{
+        chain::{base::ChainBackend, simulator::Simulator},
+        client::BitVMClient,
+    },
+    commitments::{
+        generate_commit_y_public_key, sign_commit_y, CommitmentValue, COMMITMENT_VALUE_LENGTH,
+    },
+    connectors::{connector::TaprootConnector, connector_y::ConnectorY},
+    contexts::{depositor::DepositorContext, operator::OperatorContext},
+    error::BridgeError,
+    graphs::{
+        base::{BaseGraph, PreSigningState, FEE_AMOUNT, INITIAL_AMOUNT},
+        peg_in::{PegInDepositorStatus, PegInGraph},
+        peg_out::{
+            generate_id, PegOutAction, PegOutGraph, PegOutRequest, PegOutState,
+            PegOutVerifierStatus, PegOutWithdrawerStatus,
+        },
+        status::GraphRole,
+    },
+    scripts::{generate_pay_to_pubkey_script, generate_pay_to_pubkey_script_address},
+    serialization::{deserialize, serialize},
+    transactions::{
+        base::{BaseTransaction, Input, InputWithScript},
+        fee::{calculate_fee, get_fee},
+        pre_signed::PreSignedTransaction,
+    },
+};
+
+use crate::bridge::setup::setup_test_with_chain;
+
+// The peg-out graphs of these tests have no peg-out transaction, the operator claims a stand-in y.
+const CLAIM: CommitmentValue = [0xc1; COMMITMENT_VALUE_LENGTH];
+
+const EVM_BURN_TX_HASH: &str = "0x5f1c6b4f0a2e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c";
+
+async fn create_and_mine_peg_in(
+    client: &BitVMClient,
+    simulator: &Simulator,
+    peg_in_graph: &PegInGraph,
+) {
+    let deposit_tx = peg_in_graph.peg_in_deposit_transaction_ref().finalize();
+    let deposit_result = client.chain.broadcast(&deposit_tx).await;
+    assert!(deposit_result.is_ok());
+    simulator.mine_blocks(1);
+
+    let confirm_tx = peg_in_graph.peg_in_confirm_transaction_ref().finalize();
+    let confirm_result = client.chain.broadcast(&confirm_tx).await;
+    assert!(confirm_result.is_ok());
+    simulator.mine_blocks(1);
+}
+
+fn create_graphs(
+    simulator: &Simulator,
+    depositor_context: &DepositorContext,
+    operator_context: &OperatorContext,
+    evm_address: &str,
+) -> (PegInGraph, PegOutGraph) {
+    let deposit_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let deposit_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        deposit_amount,
+    );
+    let peg_in_graph = PegInGraph::new(
+        depositor_context,
+        Input {
+            outpoint: deposit_outpoint,
+            amount: deposit_amount,
+        },
+        evm_address,
+    );
+
+    let kick_off_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let kick_off_outpoint = simulator.fund_address(
+        &ConnectorY::new(
+            operator_context.network,
+            &operator_context.operator_taproot_public_key,
+            &generate_commit_y_public_key(
+                operator_context,
+                &generate_id(&peg_in_graph, &operator_context.operator_public_key),
+            ),
+        )
+        .generate_taproot_address(),
+        kick_off_amount,
+    );
+    let peg_out_graph = PegOutGraph::new(
+        operator_context,
+        &peg_in_graph,
+        Input {
+            outpoint: kick_off_outpoint,
+            amount: kick_off_amount,
+        },
+    );
+
+    (peg_in_graph, peg_out_graph)
+}
+
+#[tokio::test]
+async fn test_flow_kick_off_challenge_assert_disprove() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        withdrawer_context,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // peg-in
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // kick-off
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    // the claim does not commit to a peg-out of this graph
+    assert_eq!(
+        peg_out_graph
+            .kick_off_claim(client.chain.as_ref())
+            .await
+            .unwrap(),
+        CLAIM
+    );
+    assert!(!peg_out_graph
+        .is_kick_off_claim_valid(client.chain.as_ref())
+        .await
+        .unwrap());
+
+    // challenge, crowdfunded by the depositor on their own copy of the graph
+    let mut challenger_graph: PegOutGraph = deserialize(&serialize(&peg_out_graph));
+    let crowdfunding_amount = peg_out_graph.challenge_crowdfunding_amount();
+    let crowdfunding_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        crowdfunding_amount,
+    );
+    let crowdfunding_script =
+        generate_pay_to_pubkey_script(&depositor_context.depositor_public_key);
+    challenger_graph
+        .challenge(
+            client.chain.as_ref(),
+            &depositor_context,
+            &vec![InputWithScript {
+                outpoint: crowdfunding_outpoint,
+                amount: crowdfunding_amount,
+                script: &crowdfunding_script,
+            }],
+            &depositor_context.depositor_keypair,
+            crowdfunding_script.clone(),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    // the completed challenge has another txid than the operator's template
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::Challenged);
+
+    // assert
+    peg_out_graph.assert(client.chain.as_ref()).await.unwrap();
+    simulator.mine_blocks(1);
+
+    // disprove, with a reward output the operator's template doesn't have
+    let reward_address = generate_pay_to_pubkey_script_address(
+        withdrawer_context.network,
+        &withdrawer_context.withdrawer_public_key,
+    );
+    let script_index = 1;
+    challenger_graph
+        .disprove(
+            client.chain.as_ref(),
+            script_index,
+            reward_address.script_pubkey(),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    let reward_utxos = client.chain.get_address_utxo(reward_address).await.unwrap();
+    assert_eq!(reward_utxos.len(), 1);
+    assert!(reward_utxos[0].status.confirmed);
+
+    for graph in [&peg_out_graph, &challenger_graph] {
+        let graph_state = graph.state(client.chain.as_ref()).await.unwrap();
+        assert_eq!(graph_state.state, PegOutState::FailedByDisprove);
+        assert!(graph_state.available_actions.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_flow_kick_off_assert_take2() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // peg-in
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // kick-off
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    // assert
+    peg_out_graph.assert(client.chain.as_ref()).await.unwrap();
+    simulator.mine_blocks(1);
+
+    // take2, once the assert timelock has elapsed
+    simulator.mine_blocks(peg_out_graph.parameters().take2_timelock);
+    peg_out_graph.take2(client.chain.as_ref()).await.unwrap();
+    simulator.mine_blocks(1);
+
+    let operator_utxos = client
+        .chain
+        .get_address_utxo(generate_pay_to_pubkey_script_address(
+            operator_context.network,
+            &operator_context.operator_public_key,
+        ))
+        .await
+        .unwrap();
+    // the take2 payout and the unspent anchors of the kick-off and the assert
+    assert_eq!(operator_utxos.len(), 3);
+    assert!(operator_utxos.iter().all(|utxo| utxo.status.confirmed));
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::CompletedByTake2);
+    assert!(graph_state.available_actions.is_empty());
+}
+
+#[tokio::test]
+async fn test_flow_returns_errors_instead_of_panicking() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // assert before kick-off
+    let assert_result = peg_out_graph.assert(client.chain.as_ref()).await;
+    assert!(matches!(
+        assert_result,
+        Err(BridgeError::TransactionNotConfirmed(_))
+    ));
+
+    // kick-off twice
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+    let kick_off_result = peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await;
+    assert!(matches!(
+        kick_off_result,
+        Err(BridgeError::TransactionAlreadyMined(_))
+    ));
+
+    // take1 before the kick-off timelock has elapsed
+    let take1_result = peg_out_graph.take1(client.chain.as_ref()).await;
+    assert!(matches!(
+        take1_result,
+        Err(BridgeError::TimelockNotElapsed(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_flow_musig2_presign_across_verifiers_take1() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut verifier_0_peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // every verifier works on its own copy of the peg-out graph
+    let mut verifier_1_peg_out_graph: PegOutGraph =
+        serde_json::from_str(&serde_json::to_string(&verifier_0_peg_out_graph).unwrap()).unwrap();
+
+    // nonce exchange
+    let mut verifier_0_secret_nonces = verifier_0_peg_out_graph
+        .push_nonces(&verifier_contexts[0])
+        .unwrap();
+    let mut verifier_1_secret_nonces = verifier_1_peg_out_graph
+        .push_nonces(&verifier_contexts[1])
+        .unwrap();
+    assert!(!verifier_0_peg_out_graph.has_all_nonces(&verifier_contexts[0]));
+    assert_eq!(
+        verifier_0_peg_out_graph.pre_signing_state(),
+        PreSigningState::AwaitingNonces
+    );
+    assert!(verifier_0_peg_out_graph
+        .pre_signing_status()
+        .iter()
+        .all(|status| status.missing_nonces == vec![verifier_contexts[1].verifier_public_key]));
+    verifier_0_peg_out_graph
+        .merge(&verifier_1_peg_out_graph)
+        .unwrap();
+    verifier_1_peg_out_graph
+        .merge(&verifier_0_peg_out_graph)
+        .unwrap();
+    assert!(verifier_0_peg_out_graph.has_all_nonces(&verifier_contexts[0]));
+    assert!(verifier_1_peg_out_graph.has_all_nonces(&verifier_contexts[1]));
+    assert_eq!(
+        verifier_0_peg_out_graph.pre_signing_state(),
+        PreSigningState::AwaitingSignatures
+    );
+
+    // partial signatures
+    verifier_0_peg_out_graph
+        .pre_sign(&verifier_contexts[0], &mut verifier_0_secret_nonces)
+        .unwrap();
+    verifier_1_peg_out_graph
+        .pre_sign(&verifier_contexts[1], &mut verifier_1_secret_nonces)
+        .unwrap();
+    // every secret nonce is deleted once its partial signature is made
+    assert!(verifier_0_secret_nonces.is_empty());
+    assert!(verifier_1_secret_nonces.is_empty());
+    assert!(verifier_0_peg_out_graph.has_signatures_of(&verifier_contexts[0]));
+    assert!(!verifier_0_peg_out_graph.has_all_signatures(&verifier_contexts[0]));
+    assert!(verifier_0_peg_out_graph
+        .pre_signing_status()
+        .iter()
+        .all(
+            |status| status.missing_signatures == vec![verifier_contexts[1].verifier_public_key]
+                && !status.finalized
+        ));
+
+    // aggregation, once the partial signatures of all verifiers are known
+    verifier_0_peg_out_graph
+        .merge(&verifier_1_peg_out_graph)
+        .unwrap();
+    assert!(verifier_0_peg_out_graph.has_all_signatures(&verifier_contexts[0]));
+    assert_eq!(
+        verifier_0_peg_out_graph.pre_signing_state(),
+        PreSigningState::AwaitingSignatures
+    );
+    verifier_0_peg_out_graph
+        .pre_sign(&verifier_contexts[0], &mut verifier_0_secret_nonces)
+        .unwrap();
+    assert_eq!(
+        verifier_0_peg_out_graph.pre_signing_state(),
+        PreSigningState::Presigned
+    );
+
+    // the aggregated signatures reach the other verifier through the graph
+    verifier_1_peg_out_graph
+        .merge(&verifier_0_peg_out_graph)
+        .unwrap();
+    assert_eq!(
+        verifier_1_peg_out_graph.pre_signing_state(),
+        PreSigningState::Presigned
+    );
+
+    // peg-in
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // kick-off
+    verifier_0_peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, verifier_0_peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    // take1, spending the n-of-n outputs with the aggregated signatures
+    simulator.mine_blocks(verifier_0_peg_out_graph.parameters().take1_timelock);
+    verifier_0_peg_out_graph
+        .take1(client.chain.as_ref())
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+}
+
+#[tokio::test]
+async fn test_flow_kick_off_bump_fee() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // peg-in
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // kick-off, stuck in the mempool once the fee rate rises
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.set_fee_rate(FeeRate::from_sat_per_vb(20).unwrap());
+    let fee_rate = client.chain.get_fee_rate(1).await.unwrap();
+
+    // the operator pays for the kick-off with a child spending its anchor
+    let funding_amount = Amount::from_sat(INITIAL_AMOUNT);
+    let funding_outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            operator_context.network,
+            &operator_context.operator_public_key,
+        ),
+        funding_amount,
+    );
+    let kick_off_txid = peg_out_graph.kick_off_transaction_ref().tx().compute_txid();
+    let cpfp_txid = peg_out_graph
+        .bump_fee(
+            client.chain.as_ref(),
+            &operator_context,
+            &kick_off_txid,
+            &vec![Input {
+                outpoint: funding_outpoint,
+                amount: funding_amount,
+            }],
+            fee_rate,
+        )
+        .await
+        .unwrap();
+
+    let kick_off_tx = client.chain.get_tx(&kick_off_txid).await.unwrap().unwrap();
+    let cpfp_tx = client.chain.get_tx(&cpfp_txid).await.unwrap().unwrap();
+    let cpfp_input_amount =
+        funding_amount + kick_off_tx.output[cpfp_tx.input[0].previous_output.vout as usize].value;
+    let package_fee = get_fee(peg_out_graph.kick_off_transaction_ref()) + cpfp_input_amount
+        - cpfp_tx.output[0].value;
+    assert!(package_fee >= calculate_fee(kick_off_tx.weight() + cpfp_tx.weight(), fee_rate));
+
+    simulator.mine_blocks(1);
+    assert!(
+        client
+            .chain
+            .get_tx_status(&kick_off_txid)
+            .await
+            .unwrap()
+            .confirmed
+    );
+
+    // nothing left to bump once the kick-off is mined
+    let bump_result = peg_out_graph
+        .bump_fee(
+            client.chain.as_ref(),
+            &operator_context,
+            &kick_off_txid,
+            &vec![],
+            fee_rate,
+        )
+        .await;
+    assert!(matches!(
+        bump_result,
+        Err(BridgeError::TransactionAlreadyMined(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_flow_kick_off_challenge_and_burn_available() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        withdrawer_context,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::PreSigning);
+    assert_eq!(graph_state.available_actions, vec![PegOutAction::PreSign]);
+
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // peg-in
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    // no peg-out has been requested
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::PegOutPending);
+    assert!(graph_state.available_actions.is_empty());
+
+    // the withdrawer has not been paid
+    peg_out_graph
+        .request_peg_out(&PegOutRequest {
+            peg_in_graph_id: peg_in_graph.id().clone(),
+            withdrawer_public_key: withdrawer_context.withdrawer_public_key,
+            withdrawer_evm_address: evm_address.clone(),
+            evm_burn_tx_hash: String::from(EVM_BURN_TX_HASH),
+            destination_script_pubkey: generate_pay_to_pubkey_script_address(
+                withdrawer_context.network,
+                &withdrawer_context.withdrawer_public_key,
+            )
+            .script_pubkey(),
+        })
+        .unwrap();
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::PegOutPending);
+    assert_eq!(graph_state.available_actions, vec![PegOutAction::PegOut]);
+
+    // kick-off
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::KickedOff);
+    assert_eq!(graph_state.available_actions, vec![PegOutAction::Challenge]);
+    assert_eq!(
+        peg_out_graph
+            .verifier_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegOutVerifierStatus::PegOutChallengeAvailabe
+    );
+
+    // the operator neither takes 1 nor asserts until the kick-off can be burnt
+    simulator.mine_blocks(peg_out_graph.parameters().burn_timelock);
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::KickedOff);
+    assert_eq!(
+        graph_state.actions_of(GraphRole::Verifier),
+        vec![PegOutAction::Challenge, PegOutAction::Burn]
+    );
+    assert_eq!(
+        graph_state.actions_of(GraphRole::Operator),
+        vec![PegOutAction::Take1]
+    );
+    assert_eq!(
+        peg_out_graph
+            .verifier_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegOutVerifierStatus::PegOutBurnAvailable
+    );
+
+    // burn
+    let reward_address = generate_pay_to_pubkey_script_address(
+        withdrawer_context.network,
+        &withdrawer_context.withdrawer_public_key,
+    );
+    peg_out_graph
+        .burn(client.chain.as_ref(), reward_address.script_pubkey())
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::FailedByBurn);
+    assert!(graph_state.available_actions.is_empty());
+    assert_eq!(
+        peg_out_graph
+            .verifier_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegOutVerifierStatus::PegOutFailedByBurn
+    );
+}
+
+#[tokio::test]
+async fn test_flow_burn_bump_reward_fee() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        withdrawer_context,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+
+    // peg-in and kick-off
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &CLAIM),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    // burn, stuck in the mempool once the fee rate rises
+    simulator.mine_blocks(peg_out_graph.parameters().burn_timelock);
+    let reward_address = generate_pay_to_pubkey_script_address(
+        withdrawer_context.network,
+        &withdrawer_context.withdrawer_public_key,
+    );
+    peg_out_graph
+        .burn(client.chain.as_ref(), reward_address.script_pubkey())
+        .await
+        .unwrap();
+    simulator.set_fee_rate(FeeRate::from_sat_per_vb(20).unwrap());
+    let fee_rate = client.chain.get_fee_rate(1).await.unwrap();
+
+    // the burner pays for the burn with a child spending the reward output
+    let funding_amount = Amount::from_sat(INITIAL_AMOUNT);
+    let funding_outpoint = simulator.fund_address(&reward_address, funding_amount);
+    let burn_txid = peg_out_graph.burn_transaction_ref().tx().compute_txid();
+    let cpfp_txid = peg_out_graph
+        .bump_reward_fee(
+            client.chain.as_ref(),
+            &withdrawer_context,
+            &burn_txid,
+            &vec![Input {
+                outpoint: funding_outpoint,
+                amount: funding_amount,
+            }],
+            &withdrawer_context.withdrawer_keypair,
+            fee_rate,
+        )
+        .await
+        .unwrap();
+
+    let burn_tx = client.chain.get_tx(&burn_txid).await.unwrap().unwrap();
+    let cpfp_tx = client.chain.get_tx(&cpfp_txid).await.unwrap().unwrap();
+    let reward_vout = peg_out_graph.burn_transaction_ref().reward_vout();
+    assert_eq!(cpfp_tx.input[0].previous_output.vout as usize, reward_vout);
+    let cpfp_input_amount = funding_amount + burn_tx.output[reward_vout].value;
+    let package_fee =
+        get_fee(peg_out_graph.burn_transaction_ref()) + cpfp_input_amount - cpfp_tx.output[0].value;
+    assert!(package_fee >= calculate_fee(burn_tx.weight() + cpfp_tx.weight(), fee_rate));
+
+    // only the holder of the reward output can bump the burn
+    let bump_result = peg_out_graph
+        .bump_reward_fee(
+            client.chain.as_ref(),
+            &operator_context,
+            &burn_txid,
+            &vec![],
+            &operator_context.operator_keypair,
+            fee_rate,
+        )
+        .await;
+    assert!(matches!(bump_result, Err(BridgeError::FeeBumping(_))));
+
+    simulator.mine_blocks(1);
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::FailedByBurn);
+}
+
+#[tokio::test]
+async fn test_flow_peg_in_refund() {
+    let simulator = Simulator::new();
+    let (mut client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    );
+    client
+        .create_peg_in_graph(Input { outpoint, amount }, &evm_address)
+        .await
+        .unwrap();
+    let report = client.depositor_status().await.unwrap().remove(0);
+    let peg_in_graph_id = report.graph_id.clone();
+    let peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+    assert_eq!(peg_in_graph.id(), &peg_in_graph_id);
+
+    // refund before the deposit
+    let refund_result = client.broadcast_peg_in_refund(&peg_in_graph_id).await;
+    assert!(matches!(
+        refund_result,
+        Err(BridgeError::TransactionNotConfirmed(_))
+    ));
+
+    let deposit_tx = peg_in_graph.peg_in_deposit_transaction_ref().finalize();
+    assert!(client.chain.broadcast(&deposit_tx).await.is_ok());
+    simulator.mine_blocks(1);
+
+    // refund before the peg-in refund timelock has elapsed
+    let refund_result = client.broadcast_peg_in_refund(&peg_in_graph_id).await;
+    assert!(matches!(
+        refund_result,
+        Err(BridgeError::TimelockNotElapsed(_))
+    ));
+
+    simulator.mine_blocks(peg_in_graph.parameters().peg_in_refund_timelock);
+    client
+        .broadcast_peg_in_refund(&peg_in_graph_id)
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+
+    let refund_txid = peg_in_graph
+        .peg_in_refund_transaction_ref()
+        .tx()
+        .compute_txid();
+    assert!(
+        client
+            .chain
+            .get_tx_status(&refund_txid)
+            .await
+            .unwrap()
+            .confirmed
+    );
+    assert_eq!(
+        peg_in_graph
+            .depositor_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegInDepositorStatus::PegInRefundComplete
+    );
+
+    // refund twice
+    let refund_result = client.broadcast_peg_in_refund(&peg_in_graph_id).await;
+    assert!(matches!(
+        refund_result,
+        Err(BridgeError::TransactionAlreadyMined(txid)) if txid == refund_txid
+    ));
+
+    let result = client.broadcast_peg_in_refund("unknown").await;
+    assert!(matches!(result, Err(BridgeError::GraphNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_flow_peg_in_deposit_and_confirm() {
+    let simulator = Simulator::new();
+    let (client, depositor_context, _, verifier_contexts, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT * 2);
+    let outpoint = simulator.fund_address(
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    );
+    let mut peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+
+    // confirm before the verifiers pre-signed
+    let confirm_result = peg_in_graph.confirm(client.chain.as_ref()).await;
+    assert!(matches!(confirm_result, Err(BridgeError::PreSigning(_))));
+
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    assert!(peg_in_graph
+        .peg_in_confirm_transaction_ref()
+        .has_depositor_signature());
+
+    // confirm before the deposit
+    let confirm_result = peg_in_graph.confirm(client.chain.as_ref()).await;
+    assert!(matches!(
+        confirm_result,
+        Err(BridgeError::TransactionNotConfirmed(_))
+    ));
+
+    peg_in_graph.deposit(client.chain.as_ref()).await.unwrap();
+    simulator.mine_blocks(1);
+    let deposit_result = peg_in_graph.deposit(client.chain.as_ref()).await;
+    assert!(matches!(
+        deposit_result,
+        Err(BridgeError::TransactionAlreadyMined(_))
+    ));
+    assert_eq!(
+        peg_in_graph
+            .depositor_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegInDepositorStatus::PegInConfirmWait
+    );
+
+    peg_in_graph.confirm(client.chain.as_ref()).await.unwrap();
+    simulator.mine_blocks(1);
+    assert_eq!(
+        peg_in_graph
+            .depositor_status(client.chain.as_ref())
+            .await
+            .unwrap(),
+        PegInDepositorStatus::PegInConfirmComplete
+    );
+
+    // the deposit can no longer be refunded
+    let refund_result = peg_in_graph.refund(client.chain.as_ref()).await;
+    assert!(matches!(
+        refund_result,
+        Err(BridgeError::TransactionAlreadyMined(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_flow_withdrawer_peg_out_request() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        verifier_contexts,
+        withdrawer_context,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    let (mut peg_in_graph, mut peg_out_graph) = create_graphs(
+        &simulator,
+        &depositor_context,
+        &operator_context,
+        &evm_address,
+    );
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_out_graph.push_nonces(verifier_context).unwrap())
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_out_graph
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
+    create_and_mine_peg_in(&client, &simulator, &peg_in_graph).await;
+
+    let peg_in_amount = peg_in_graph.peg_in_confirm_transaction_ref().tx().output[0].value;
+    let operator_address = generate_pay_to_pubkey_script_address(
+        operator_context.network,
+        &operator_context.operator_public_key,
+    );
+    let outpoint = simulator.fund_address(&operator_address, peg_in_amount);
+    let input = || Input {
+        outpoint,
+        amount: peg_in_amount,
+    };
+
+    // peg-out before the withdrawer requested it
+    assert_eq!(
+        peg_out_graph.withdrawer_status(client.chain.as_ref()).await,
+        PegOutWithdrawerStatus::PegOutNotRequested
+    );
+    let peg_out_result = peg_out_graph
+        .peg_out(client.chain.as_ref(), &operator_context, input())
+        .await;
+    assert!(matches!(peg_out_result, Err(BridgeError::PegOut(_))));
+
+    // the withdrawer is paid to an address of its choice, not necessarily one of its key
+    let destination_script_pubkey = Address::p2tr(
+        &withdrawer_context.secp,
+        withdrawer_context.withdrawer_taproot_public_key,
+        None,
+        withdrawer_context.network,
+    )
+    .script_pubkey();
+    let request = PegOutRequest {
+        peg_in_graph_id: peg_in_graph.id().clone(),
+        withdrawer_public_key: withdrawer_context.withdrawer_public_key,
+        withdrawer_evm_address: evm_address.clone(),
+        evm_burn_tx_hash: String::from(EVM_BURN_TX_HASH),
+        destination_script_pubkey: destination_script_pubkey.clone(),
+    };
+    let invalid_request = PegOutRequest {
+        destination_script_pubkey: ScriptBuf::new(),
+        ..request.clone()
+    };
+    assert!(matches!(
+        peg_out_graph.request_peg_out(&invalid_request),
+        Err(BridgeError::PegOut(_))
+    ));
+    assert_eq!(peg_out_graph.peg_out_request(), None);
+    peg_out_graph.request_peg_out(&request).unwrap();
+    assert_eq!(peg_out_graph.peg_out_request(), Some(request.clone()));
+    assert_eq!(
+        peg_out_graph.withdrawer_status(client.chain.as_ref()).await,
+        PegOutWithdrawerStatus::PegOutRequested
+    );
+
+    // the peg-in is paid to a single withdrawer
+    let other_request = PegOutRequest {
+        withdrawer_public_key: depositor_context.depositor_public_key,
+        ..request.clone()
+    };
+    assert!(matches!(
+        peg_out_graph.request_peg_out(&other_request),
+        Err(BridgeError::PegOut(_))
+    ));
+
+    // the operator has to pay at least the peg-in amount
+    let short_amount = peg_in_amount - Amount::from_sat(FEE_AMOUNT);
+    let short_outpoint = simulator.fund_address(&operator_address, short_amount);
+    let peg_out_result = peg_out_graph
+        .peg_out(
+            client.chain.as_ref(),
+            &operator_context,
+            Input {
+                outpoint: short_outpoint,
+                amount: short_amount,
+            },
+        )
+        .await;
+    assert!(matches!(peg_out_result, Err(BridgeError::PegOut(_))));
+    assert!(peg_out_graph.peg_out_transaction_ref().is_none());
+
+    peg_out_graph
+        .peg_out(client.chain.as_ref(), &operator_context, input())
+        .await
+        .unwrap();
+    assert_eq!(
+        peg_out_graph.withdrawer_status(client.chain.as_ref()).await,
+        PegOutWithdrawerStatus::PegOutWait
+    );
+    simulator.mine_blocks(1);
+
+    assert_eq!(
+        peg_out_graph.withdrawer_status(client.chain.as_ref()).await,
+        PegOutWithdrawerStatus::PegOutComplete
+    );
+    assert_eq!(
+        peg_out_graph.peg_out_transaction_ref().unwrap().tx().output[0].script_pubkey,
+        destination_script_pubkey
+    );
+    assert!(peg_out_graph
+        .verify(&peg_in_graph, &operator_context.n_of_n_taproot_public_key)
+        .is_ok());
+
+    // a peg-out paying another destination than the requested one is rejected
+    let other_destination_script_pubkey = generate_pay_to_pubkey_script_address(
+        withdrawer_context.network,
+        &withdrawer_context.withdrawer_public_key,
+    )
+    .script_pubkey();
+    let json = serialize(&peg_out_graph).replace(
+        &format!(
+            "\"withdrawer_destination_script_pubkey\":\"{}\"",
+            destination_script_pubkey.to_hex_string()
+        ),
+        &format!(
+            "\"withdrawer_destination_script_pubkey\":\"{}\"",
+            other_destination_script_pubkey.to_hex_string()
+        ),
+    );
+    let redirected_peg_out_graph: PegOutGraph = deserialize(&json);
+    assert_eq!(
+        redirected_peg_out_graph
+            .peg_out_request()
+            .unwrap()
+            .destination_script_pubkey,
+        other_destination_script_pubkey
+    );
+    assert!(matches!(
+        redirected_peg_out_graph.verify(&peg_in_graph, &operator_context.n_of_n_taproot_public_key),
+        Err(BridgeError::InvalidGraph(_))
+    ));
+
+    let graph_state = peg_out_graph.state(client.chain.as_ref()).await.unwrap();
+    assert_eq!(graph_state.state, PegOutState::KickOffPending);
+    assert_eq!(graph_state.available_actions, vec![PegOutAction::KickOff]);
+
+    // peg-out twice
+    let peg_out_result = peg_out_graph
+        .peg_out(client.chain.as_ref(), &operator_context, input())
+        .await;
+    assert!(matches!(
+        peg_out_result,
+        Err(BridgeError::TransactionAlreadyMined(_))
+    ));
+
+    // the kick-off commits to the peg-out paying the withdrawer
+    let claim = peg_out_graph.peg_out_claim().unwrap();
+    peg_out_graph
+        .kick_off(
+            client.chain.as_ref(),
+            &sign_commit_y(&operator_context, peg_out_graph.id(), &claim),
+        )
+        .await
+        .unwrap();
+    simulator.mine_blocks(1);
+    assert!(peg_out_graph
+        .is_kick_off_claim_valid(client.chain.as_ref())
+        .await
+        .unwrap());
+}
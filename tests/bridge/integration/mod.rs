@@ -1,2 +1,3 @@
+pub mod flow;
 pub mod peg_in;
 pub mod peg_out;
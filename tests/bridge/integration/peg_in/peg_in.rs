@@ -1,9 +1,8 @@
-use std::time::Duration;
-
 use bitcoin::{Amount, OutPoint};
 
 use bitvm::bridge::{
-    connectors::{connector::P2wshConnector, connector_0::Connector0},
+    client::chain::simulator::Simulator,
+    connectors::{connector::TaprootConnector, connector_0::Connector0},
     graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
@@ -13,15 +12,14 @@ use bitvm::bridge::{
         peg_in_refund::PegInRefundTransaction,
     },
 };
-use esplora_client::Error;
-use tokio::time::sleep;
 
-use crate::bridge::{helper::generate_stub_outpoint, setup::setup_test};
+use crate::bridge::setup::setup_test_with_chain;
 
 #[tokio::test]
 async fn test_peg_in_success() {
-    let (client, depositor_context, _, verifier_context, _, _, _, _, _, _, _, _, _, evm_address) =
-        setup_test().await;
+    let simulator = Simulator::new();
+    let (client, depositor_context, _, verifier_contexts, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT * 2;
     let deposit_input_amount = Amount::from_sat(input_amount_raw);
@@ -32,7 +30,7 @@ async fn test_peg_in_success() {
         &depositor_context.depositor_public_key,
     );
     let deposit_funding_outpoint =
-        generate_stub_outpoint(&client, &deposit_funding_utxo_address, deposit_input_amount).await;
+        simulator.fund_address(&deposit_funding_utxo_address, deposit_input_amount);
     let deposit_input = Input {
         outpoint: deposit_funding_outpoint,
         amount: deposit_input_amount,
@@ -45,8 +43,9 @@ async fn test_peg_in_success() {
     let deposit_tx_id = peg_in_deposit_tx.compute_txid();
 
     // mine peg-in deposit
-    let deposit_result = client.esplora.broadcast(&peg_in_deposit_tx).await;
+    let deposit_result = client.chain.broadcast(&peg_in_deposit_tx).await;
     assert!(deposit_result.is_ok());
+    simulator.mine_blocks(1);
 
     // peg-in confirm
     let output_index = 0;
@@ -60,22 +59,32 @@ async fn test_peg_in_success() {
     };
     let mut peg_in_confirm =
         PegInConfirmTransaction::new(&depositor_context, &evm_address, confirm_input);
-    peg_in_confirm.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_confirm.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_confirm
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
     let peg_in_confirm_tx = peg_in_confirm.finalize();
     let confirm_tx_id = peg_in_confirm_tx.compute_txid();
 
     // mine peg-in confirm
-    let confirm_result = client.esplora.broadcast(&peg_in_confirm_tx).await;
+    let confirm_result = client.chain.broadcast(&peg_in_confirm_tx).await;
     assert!(confirm_result.is_ok());
+    simulator.mine_blocks(1);
 
     // multi-sig balance
     let connector_0 = Connector0::new(
         depositor_context.network,
-        &depositor_context.n_of_n_public_key,
+        &depositor_context.n_of_n_taproot_public_key,
     );
-    let multi_sig_address = connector_0.generate_address();
+    let multi_sig_address = connector_0.generate_taproot_address();
     let multi_sig_utxos = client
-        .esplora
+        .chain
         .get_address_utxo(multi_sig_address.clone())
         .await
         .unwrap();
@@ -98,8 +107,9 @@ async fn test_peg_in_success() {
 
 #[tokio::test]
 async fn test_peg_in_time_lock_not_surpassed() {
+    let simulator = Simulator::new();
     let (client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
-        setup_test().await;
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT * 2;
     let deposit_input_amount = Amount::from_sat(input_amount_raw);
@@ -110,7 +120,7 @@ async fn test_peg_in_time_lock_not_surpassed() {
         &depositor_context.depositor_public_key,
     );
     let deposit_funding_outpoint =
-        generate_stub_outpoint(&client, &deposit_funding_utxo_address, deposit_input_amount).await;
+        simulator.fund_address(&deposit_funding_utxo_address, deposit_input_amount);
     let deposit_input = Input {
         outpoint: deposit_funding_outpoint,
         amount: deposit_input_amount,
@@ -122,8 +132,9 @@ async fn test_peg_in_time_lock_not_surpassed() {
     let deposit_tx_id = peg_in_deposit_tx.compute_txid();
 
     // mine peg-in deposit
-    let deposit_result = client.esplora.broadcast(&peg_in_deposit_tx).await;
+    let deposit_result = client.chain.broadcast(&peg_in_deposit_tx).await;
     assert!(deposit_result.is_ok());
+    simulator.mine_blocks(1);
 
     // peg-in refund
     let output_index = 0;
@@ -139,22 +150,18 @@ async fn test_peg_in_time_lock_not_surpassed() {
     let peg_in_refund_tx = peg_in_refund.finalize();
 
     // mine peg-in refund
-    let refund_result = client.esplora.broadcast(&peg_in_refund_tx).await;
+    let refund_result = client.chain.broadcast(&peg_in_refund_tx).await;
     assert!(refund_result.is_err());
     let error = refund_result.unwrap_err();
-    let expected_error = Error::HttpResponse {
-        status: 400,
-        message: String::from(
-            "sendrawtransaction RPC error: {\"code\":-26,\"message\":\"non-BIP68-final\"}",
-        ), // indicates that relative timelock based on sequence numbers has not elapsed
-    };
-    assert_eq!(error.to_string(), expected_error.to_string());
+    // indicates that relative timelock based on sequence numbers has not elapsed
+    assert!(error.to_string().contains("non-BIP68-final"));
 }
 
 #[tokio::test]
 async fn test_peg_in_time_lock_surpassed() {
+    let simulator = Simulator::new();
     let (client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
-        setup_test().await;
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT * 2;
     let deposit_input_amount = Amount::from_sat(input_amount_raw);
@@ -165,7 +172,7 @@ async fn test_peg_in_time_lock_surpassed() {
         &depositor_context.depositor_public_key,
     );
     let deposit_funding_outpoint =
-        generate_stub_outpoint(&client, &deposit_funding_utxo_address, deposit_input_amount).await;
+        simulator.fund_address(&deposit_funding_utxo_address, deposit_input_amount);
     let deposit_input = Input {
         outpoint: deposit_funding_outpoint,
         amount: deposit_input_amount,
@@ -177,8 +184,9 @@ async fn test_peg_in_time_lock_surpassed() {
     let deposit_tx_id = peg_in_deposit_tx.compute_txid();
 
     // mine peg-in deposit
-    let deposit_result = client.esplora.broadcast(&peg_in_deposit_tx).await;
+    let deposit_result = client.chain.broadcast(&peg_in_deposit_tx).await;
     assert!(deposit_result.is_ok());
+    simulator.mine_blocks(1);
 
     // peg-in refund
     let output_index = 0;
@@ -195,9 +203,10 @@ async fn test_peg_in_time_lock_surpassed() {
     let refund_tx_id = peg_in_refund_tx.compute_txid();
 
     // mine peg-in refund
-    sleep(Duration::from_secs(60)).await; // TODO: check if this can be refactored to drop waiting
-    let refund_result = client.esplora.broadcast(&peg_in_refund_tx).await;
+    simulator.mine_blocks(depositor_context.parameters.peg_in_refund_timelock);
+    let refund_result = client.chain.broadcast(&peg_in_refund_tx).await;
     assert!(refund_result.is_ok());
+    simulator.mine_blocks(1);
 
     // depositor balance
     let depositor_address = generate_pay_to_pubkey_script_address(
@@ -205,7 +214,7 @@ async fn test_peg_in_time_lock_surpassed() {
         &depositor_context.depositor_public_key,
     );
     let depositor_utxos = client
-        .esplora
+        .chain
         .get_address_utxo(depositor_address.clone())
         .await
         .unwrap();
@@ -1,26 +1,25 @@
-use std::time::Duration;
-
 use bitcoin::OutPoint;
 use bitvm::bridge::{
+    client::chain::simulator::Simulator,
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         base::{BaseTransaction, Input},
         burn::BurnTransaction,
     },
 };
-use tokio::time::sleep;
 
-use crate::bridge::setup::setup_test;
+use crate::bridge::setup::setup_test_with_chain;
 
 use super::utils::create_and_mine_kick_off_tx;
 
 #[tokio::test]
 async fn test_burn_success() {
+    let simulator = Simulator::new();
     let (
         client,
         _,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         withdrawer_context,
         _,
         _,
@@ -31,11 +30,11 @@ async fn test_burn_success() {
         _,
         _,
         _,
-    ) = setup_test().await;
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     // kick-off
     let (kick_off_tx, kick_off_tx_id) =
-        create_and_mine_kick_off_tx(&client, &operator_context).await;
+        create_and_mine_kick_off_tx(&client, &simulator, &operator_context).await;
 
     // burn
     let burn_kick_off_outpoint = OutPoint {
@@ -48,7 +47,14 @@ async fn test_burn_success() {
     };
 
     let mut burn = BurnTransaction::new(&operator_context, burn_kick_off_input);
-    burn.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| burn.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        burn.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
 
     let reward_address = generate_pay_to_pubkey_script_address(
         withdrawer_context.network,
@@ -60,17 +66,13 @@ async fn test_burn_success() {
     let burn_tx_id = burn_tx.compute_txid();
 
     // mine burn
-    sleep(Duration::from_secs(60)).await;
-    let burn_result = client.esplora.broadcast(&burn_tx).await;
+    simulator.mine_blocks(operator_context.parameters.burn_timelock);
+    let burn_result = client.chain.broadcast(&burn_tx).await;
     println!("Broadcast burn result: {:?}\n", burn_result);
     assert!(burn_result.is_ok());
 
     // reward balance
-    let reward_utxos = client
-        .esplora
-        .get_address_utxo(reward_address)
-        .await
-        .unwrap();
+    let reward_utxos = client.chain.get_address_utxo(reward_address).await.unwrap();
     let reward_utxo = reward_utxos
         .clone()
         .into_iter()
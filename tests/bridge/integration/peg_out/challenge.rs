@@ -1,27 +1,29 @@
 use bitcoin::{Amount, OutPoint};
 
 use bitvm::bridge::{
+    client::chain::simulator::Simulator,
+    error::BridgeError,
     graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
     scripts::{generate_pay_to_pubkey_script, generate_pay_to_pubkey_script_address},
     transactions::{
         base::{BaseTransaction, Input, InputWithScript},
-        challenge::ChallengeTransaction,
+        challenge::{combine_crowdfunding_psbts, get_crowdfunded_amount, ChallengeTransaction},
     },
 };
 
 use crate::bridge::{
-    helper::generate_stub_outpoint, integration::peg_out::utils::create_and_mine_kick_off_tx,
-    setup::setup_test,
+    integration::peg_out::utils::create_and_mine_kick_off_tx, setup::setup_test_with_chain,
 };
 
 #[tokio::test]
 async fn test_challenge_success() {
+    let simulator = Simulator::new();
     let (client, depositor_context, operator_context, _, _, _, _, _, _, _, _, _, _, _) =
-        setup_test().await;
+        setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     // kick-off
     let (kick_off_tx, kick_off_tx_id) =
-        create_and_mine_kick_off_tx(&client, &operator_context).await;
+        create_and_mine_kick_off_tx(&client, &simulator, &operator_context).await;
 
     // challenge
     let challenge_input_amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
@@ -29,12 +31,8 @@ async fn test_challenge_success() {
         depositor_context.network,
         &depositor_context.depositor_public_key,
     );
-    let challenge_funding_outpoint = generate_stub_outpoint(
-        &client,
-        &challenge_funding_utxo_address,
-        challenge_input_amount,
-    )
-    .await;
+    let challenge_funding_outpoint =
+        simulator.fund_address(&challenge_funding_utxo_address, challenge_input_amount);
     let challenge_crowdfunding_input = InputWithScript {
         outpoint: challenge_funding_outpoint,
         amount: challenge_input_amount,
@@ -56,17 +54,19 @@ async fn test_challenge_success() {
         challenge_kick_off_input,
         challenge_input_amount,
     );
-    challenge.add_inputs_and_output(
-        &depositor_context,
-        &vec![challenge_crowdfunding_input],
-        &depositor_context.depositor_keypair,
-        generate_pay_to_pubkey_script(&depositor_context.depositor_public_key),
-    ); // add crowdfunding input
+    challenge
+        .add_inputs_and_output(
+            &depositor_context,
+            &vec![challenge_crowdfunding_input],
+            &depositor_context.depositor_keypair,
+            generate_pay_to_pubkey_script(&depositor_context.depositor_public_key),
+        )
+        .unwrap(); // add crowdfunding input
     let challenge_tx = challenge.finalize();
     let challenge_tx_id = challenge_tx.compute_txid();
 
     // mine challenge tx
-    let challenge_result = client.esplora.broadcast(&challenge_tx).await;
+    let challenge_result = client.chain.broadcast(&challenge_tx).await;
     assert!(challenge_result.is_ok());
 
     // operator balance
@@ -75,7 +75,7 @@ async fn test_challenge_success() {
         &operator_context.operator_public_key,
     );
     let operator_utxos = client
-        .esplora
+        .chain
         .get_address_utxo(operator_address)
         .await
         .unwrap();
@@ -88,3 +88,140 @@ async fn test_challenge_success() {
     assert!(operator_utxo.is_some());
     assert_eq!(operator_utxo.unwrap().value, challenge_tx.output[0].value);
 }
+
+#[tokio::test]
+async fn test_challenge_crowdfunded_by_two_challengers() {
+    let simulator = Simulator::new();
+    let (
+        client,
+        depositor_context,
+        operator_context,
+        _,
+        withdrawer_context,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
+
+    // kick-off
+    let (kick_off_tx, kick_off_tx_id) =
+        create_and_mine_kick_off_tx(&client, &simulator, &operator_context).await;
+
+    let kick_off_output_index = 1; // connectorA
+    let challenge_crowdfunding_amount = Amount::from_sat(INITIAL_AMOUNT);
+    let mut challenge = ChallengeTransaction::new(
+        &operator_context,
+        Input {
+            outpoint: OutPoint {
+                txid: kick_off_tx_id,
+                vout: kick_off_output_index,
+            },
+            amount: kick_off_tx.output[kick_off_output_index as usize].value,
+        },
+        challenge_crowdfunding_amount,
+    );
+
+    // We re-use the depositor and withdrawer keys to imitate two third-party challengers
+    let depositor_script = generate_pay_to_pubkey_script(&depositor_context.depositor_public_key);
+    let depositor_amount = Amount::from_sat(INITIAL_AMOUNT * 2 / 3);
+    let depositor_address = generate_pay_to_pubkey_script_address(
+        depositor_context.network,
+        &depositor_context.depositor_public_key,
+    );
+    let depositor_outpoint = simulator.fund_address(&depositor_address, depositor_amount);
+    let withdrawer_script =
+        generate_pay_to_pubkey_script(&withdrawer_context.withdrawer_public_key);
+    let withdrawer_amount = Amount::from_sat(INITIAL_AMOUNT * 2 / 3);
+    let withdrawer_address = generate_pay_to_pubkey_script_address(
+        withdrawer_context.network,
+        &withdrawer_context.withdrawer_public_key,
+    );
+    let withdrawer_outpoint = simulator.fund_address(&withdrawer_address, withdrawer_amount);
+
+    // every challenger adds its inputs, the second one is refunded the excess
+    let mut psbt = challenge.crowdfunding_psbt();
+    challenge
+        .add_crowdfunding_inputs(
+            &mut psbt,
+            &vec![InputWithScript {
+                outpoint: depositor_outpoint,
+                amount: depositor_amount,
+                script: &depositor_script,
+            }],
+            depositor_address.script_pubkey(),
+        )
+        .unwrap();
+    assert_eq!(get_crowdfunded_amount(&psbt), depositor_amount);
+    let sign_result = challenge.sign_crowdfunding_inputs(
+        &depositor_context,
+        &mut psbt,
+        &depositor_context.depositor_keypair,
+    );
+    assert!(matches!(sign_result, Err(BridgeError::Crowdfunding(_))));
+
+    challenge
+        .add_crowdfunding_inputs(
+            &mut psbt,
+            &vec![InputWithScript {
+                outpoint: withdrawer_outpoint,
+                amount: withdrawer_amount,
+                script: &withdrawer_script,
+            }],
+            withdrawer_address.script_pubkey(),
+        )
+        .unwrap();
+    assert_eq!(get_crowdfunded_amount(&psbt), challenge_crowdfunding_amount);
+
+    // every challenger signs its own copy, the copies are combined
+    let mut depositor_psbt = psbt.clone();
+    challenge
+        .sign_crowdfunding_inputs(
+            &depositor_context,
+            &mut depositor_psbt,
+            &depositor_context.depositor_keypair,
+        )
+        .unwrap();
+    let mut withdrawer_psbt = psbt.clone();
+    challenge
+        .sign_crowdfunding_inputs(
+            &withdrawer_context,
+            &mut withdrawer_psbt,
+            &withdrawer_context.withdrawer_keypair,
+        )
+        .unwrap();
+    let finalize_result = challenge.finalize_crowdfunding_psbt(&depositor_psbt);
+    assert!(matches!(finalize_result, Err(BridgeError::Crowdfunding(_))));
+
+    let combined_psbt = combine_crowdfunding_psbts(&[depositor_psbt, withdrawer_psbt]).unwrap();
+    challenge
+        .finalize_crowdfunding_psbt(&combined_psbt)
+        .unwrap();
+    let challenge_tx = challenge.finalize();
+    let challenge_tx_id = challenge_tx.compute_txid();
+
+    // mine challenge tx
+    let challenge_result = client.chain.broadcast(&challenge_tx).await;
+    assert!(challenge_result.is_ok());
+    simulator.mine_blocks(1);
+
+    // refund of the second challenger
+    let withdrawer_utxos = client
+        .chain
+        .get_address_utxo(withdrawer_address)
+        .await
+        .unwrap();
+    let refund_utxo = withdrawer_utxos
+        .into_iter()
+        .find(|x| x.txid == challenge_tx_id);
+    assert!(refund_utxo.is_some());
+    assert_eq!(
+        refund_utxo.unwrap().value,
+        depositor_amount + withdrawer_amount - challenge_crowdfunding_amount
+    );
+}
@@ -1,5 +1,6 @@
 use bitcoin::OutPoint;
 use bitvm::bridge::{
+    client::chain::simulator::Simulator,
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         assert::AssertTransaction,
@@ -8,17 +9,18 @@ use bitvm::bridge::{
     },
 };
 
-use crate::bridge::setup::setup_test;
+use crate::bridge::setup::setup_test_with_chain;
 
 use super::utils::create_and_mine_kick_off_tx;
 
 #[tokio::test]
 async fn test_disprove_success() {
+    let simulator = Simulator::new();
     let (
         client,
         _,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         withdrawer_context,
         _,
         _,
@@ -29,11 +31,11 @@ async fn test_disprove_success() {
         _,
         _,
         _,
-    ) = setup_test().await;
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     // kick-off
     let (kick_off_tx, kick_off_tx_id) =
-        create_and_mine_kick_off_tx(&client, &operator_context).await;
+        create_and_mine_kick_off_tx(&client, &simulator, &operator_context).await;
 
     // assert
     let assert_kick_off_outpoint = OutPoint {
@@ -45,10 +47,17 @@ async fn test_disprove_success() {
         amount: kick_off_tx.output[2].value,
     };
     let mut assert = AssertTransaction::new(&operator_context, assert_kick_off_input);
-    assert.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| assert.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        assert.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let assert_tx = assert.finalize();
     let assert_tx_id = assert_tx.compute_txid();
-    let assert_result = client.esplora.broadcast(&assert_tx).await;
+    let assert_result = client.chain.broadcast(&assert_tx).await;
     assert!(assert_result.is_ok());
 
     // disprove
@@ -76,28 +85,33 @@ async fn test_disprove_success() {
         disprove_assert_input_1,
         script_index,
     );
-    disprove.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| disprove.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        disprove.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
 
     let reward_address = generate_pay_to_pubkey_script_address(
         withdrawer_context.network,
         &withdrawer_context.withdrawer_public_key,
     );
     let verifier_reward_script = reward_address.script_pubkey(); // send reward to withdrawer address
-    disprove.add_input_output(script_index, verifier_reward_script);
+    disprove
+        .add_input_output(script_index, verifier_reward_script)
+        .unwrap();
 
     let disprove_tx = disprove.finalize();
     let disprove_tx_id = disprove_tx.compute_txid();
 
     // mine disprove
-    let disprove_result = client.esplora.broadcast(&disprove_tx).await;
+    let disprove_result = client.chain.broadcast(&disprove_tx).await;
     assert!(disprove_result.is_ok());
 
     // reward balance
-    let reward_utxos = client
-        .esplora
-        .get_address_utxo(reward_address)
-        .await
-        .unwrap();
+    let reward_utxos = client.chain.get_address_utxo(reward_address).await.unwrap();
     let reward_utxo = reward_utxos
         .clone()
         .into_iter()
@@ -1,26 +1,25 @@
-use std::time::Duration;
-
 use bitcoin::OutPoint;
 use bitvm::bridge::{
+    client::chain::simulator::Simulator,
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         base::{BaseTransaction, Input},
         take1::Take1Transaction,
     },
 };
-use tokio::time::sleep;
 
-use crate::bridge::setup::setup_test;
+use crate::bridge::setup::setup_test_with_chain;
 
 use super::utils::{create_and_mine_kick_off_tx, create_and_mine_peg_in_confirm_tx};
 
 #[tokio::test]
 async fn test_take1_success() {
+    let simulator = Simulator::new();
     let (
         client,
         depositor_context,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         _,
         _,
         _,
@@ -31,13 +30,14 @@ async fn test_take1_success() {
         _,
         _,
         evm_address,
-    ) = setup_test().await;
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     // peg-in confirm
     let (peg_in_confirm_tx, peg_in_confirm_tx_id) = create_and_mine_peg_in_confirm_tx(
         &client,
+        &simulator,
         &depositor_context,
-        &verifier_context,
+        &verifier_contexts,
         &connector_z,
         &evm_address,
     )
@@ -45,7 +45,7 @@ async fn test_take1_success() {
 
     // kick-off
     let (kick_off_tx, kick_off_tx_id) =
-        create_and_mine_kick_off_tx(&client, &operator_context).await;
+        create_and_mine_kick_off_tx(&client, &simulator, &operator_context).await;
 
     // take1
     let connector_0_input = Input {
@@ -85,13 +85,20 @@ async fn test_take1_success() {
         connector_b_input,
     );
 
-    take1.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| take1.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        take1.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let take1_tx = take1.finalize();
     let take1_tx_id = take1_tx.compute_txid();
 
     // mine take1
-    sleep(Duration::from_secs(60)).await;
-    let take1_result = client.esplora.broadcast(&take1_tx).await;
+    simulator.mine_blocks(operator_context.parameters.take1_timelock);
+    let take1_result = client.chain.broadcast(&take1_tx).await;
     assert!(take1_result.is_ok());
 
     // operator balance
@@ -100,7 +107,7 @@ async fn test_take1_success() {
         &operator_context.operator_public_key,
     );
     let operator_utxos = client
-        .esplora
+        .chain
         .get_address_utxo(operator_address.clone())
         .await
         .unwrap();
@@ -1,26 +1,27 @@
-use std::time::Duration;
-
 use bitcoin::OutPoint;
 use bitvm::bridge::{
+    client::chain::simulator::Simulator,
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         base::{BaseTransaction, Input},
         take2::Take2Transaction,
     },
 };
-use tokio::time::sleep;
 
-use crate::bridge::{integration::peg_out::utils::create_and_mine_assert_tx, setup::setup_test};
+use crate::bridge::{
+    integration::peg_out::utils::create_and_mine_assert_tx, setup::setup_test_with_chain,
+};
 
 use super::utils::create_and_mine_peg_in_confirm_tx;
 
 #[tokio::test]
 async fn test_take2_success() {
+    let simulator = Simulator::new();
     let (
         client,
         depositor_context,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         _,
         _,
         connector_b,
@@ -31,22 +32,28 @@ async fn test_take2_success() {
         _,
         _,
         evm_address,
-    ) = setup_test().await;
+    ) = setup_test_with_chain(Some(Box::new(simulator.clone()))).await;
 
     // peg-in confirm
     let (peg_in_confirm_tx, peg_in_confirm_tx_id) = create_and_mine_peg_in_confirm_tx(
         &client,
+        &simulator,
         &depositor_context,
-        &verifier_context,
+        &verifier_contexts,
         &connector_z,
         &evm_address,
     )
     .await;
 
     // assert
-    let (assert_tx, assert_tx_id) =
-        create_and_mine_assert_tx(&client, &operator_context, &verifier_context, &connector_b)
-            .await;
+    let (assert_tx, assert_tx_id) = create_and_mine_assert_tx(
+        &client,
+        &simulator,
+        &operator_context,
+        &verifier_contexts,
+        &connector_b,
+    )
+    .await;
 
     // take2
     let connector_0_input = Input {
@@ -78,13 +85,20 @@ async fn test_take2_success() {
         connector_3_input,
     );
 
-    take2.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| take2.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        take2.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let take2_tx = take2.finalize();
     let take2_tx_id = take2_tx.compute_txid();
 
     // mine take2
-    sleep(Duration::from_secs(60)).await;
-    let take2_result = client.esplora.broadcast(&take2_tx).await;
+    simulator.mine_blocks(operator_context.parameters.take2_timelock);
+    let take2_result = client.chain.broadcast(&take2_tx).await;
     assert!(take2_result.is_ok());
 
     // operator balance
@@ -93,7 +107,7 @@ async fn test_take2_success() {
         &operator_context.operator_public_key,
     );
     let operator_utxos = client
-        .esplora
+        .chain
         .get_address_utxo(operator_address.clone())
         .await
         .unwrap();
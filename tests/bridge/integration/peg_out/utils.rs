@@ -1,10 +1,13 @@
 use bitcoin::{Amount, Transaction, Txid};
 use bitvm::bridge::{
-    client::client::BitVMClient,
-    connectors::{connector::TaprootConnector, connector_b::ConnectorB, connector_z::ConnectorZ},
+    client::{chain::simulator::Simulator, client::BitVMClient},
+    commitments::{generate_commit_y_public_key, sign_commit_y, COMMITMENT_VALUE_LENGTH},
+    connectors::{
+        connector::TaprootConnector, connector_b::ConnectorB, connector_y::ConnectorY,
+        connector_z::ConnectorZ,
+    },
     contexts::{depositor::DepositorContext, operator::OperatorContext, verifier::VerifierContext},
     graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
-    scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         assert::AssertTransaction,
         base::{BaseTransaction, Input},
@@ -13,73 +16,92 @@ use bitvm::bridge::{
     },
 };
 
-use crate::bridge::helper::generate_stub_outpoint;
+use crate::bridge::helper::PEG_OUT_GRAPH_ID;
 
 pub async fn create_and_mine_kick_off_tx(
     client: &BitVMClient,
+    simulator: &Simulator,
     operator_context: &OperatorContext,
 ) -> (Transaction, Txid) {
     let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT;
     let input_amount = Amount::from_sat(input_amount_raw);
 
     // create kick-off tx
-    let kick_off_funding_utxo_address = generate_pay_to_pubkey_script_address(
+    let commit_y_public_key = generate_commit_y_public_key(operator_context, PEG_OUT_GRAPH_ID);
+    let kick_off_funding_utxo_address = ConnectorY::new(
         operator_context.network,
-        &operator_context.operator_public_key,
-    );
+        &operator_context.operator_taproot_public_key,
+        &commit_y_public_key,
+    )
+    .generate_taproot_address();
     let kick_off_funding_outpoint =
-        generate_stub_outpoint(&client, &kick_off_funding_utxo_address, input_amount).await;
+        simulator.fund_address(&kick_off_funding_utxo_address, input_amount);
     let kick_off_input = Input {
         outpoint: kick_off_funding_outpoint,
         amount: input_amount,
     };
-    let kick_off = KickOffTransaction::new(&operator_context, kick_off_input);
+    let mut kick_off =
+        KickOffTransaction::new(&operator_context, kick_off_input, &commit_y_public_key);
+    kick_off
+        .add_commit_y(&sign_commit_y(
+            operator_context,
+            PEG_OUT_GRAPH_ID,
+            &[0u8; COMMITMENT_VALUE_LENGTH],
+        ))
+        .unwrap();
     let kick_off_tx = kick_off.finalize();
     let kick_off_tx_id = kick_off_tx.compute_txid();
 
     // mine kick-off tx
-    let kick_off_result = client.esplora.broadcast(&kick_off_tx).await;
+    let kick_off_result = client.chain.broadcast(&kick_off_tx).await;
     assert!(kick_off_result.is_ok());
+    simulator.mine_blocks(1);
 
     return (kick_off_tx, kick_off_tx_id);
 }
 
 pub async fn create_and_mine_assert_tx(
     client: &BitVMClient,
+    simulator: &Simulator,
     operator_context: &OperatorContext,
-    verifier_context: &VerifierContext,
+    verifier_contexts: &Vec<VerifierContext>,
     connector_b: &ConnectorB,
 ) -> (Transaction, Txid) {
     let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT;
     let input_amount = Amount::from_sat(input_amount_raw);
 
     // create assert tx
-    let assert_funding_outpoint = generate_stub_outpoint(
-        &client,
-        &connector_b.generate_taproot_address(),
-        input_amount,
-    )
-    .await;
+    let assert_funding_outpoint =
+        simulator.fund_address(&connector_b.generate_taproot_address(), input_amount);
     let assert_input = Input {
         outpoint: assert_funding_outpoint,
         amount: input_amount,
     };
     let mut assert = AssertTransaction::new(&operator_context, assert_input);
-    assert.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| assert.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        assert.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let assert_tx = assert.finalize();
     let assert_tx_id = assert_tx.compute_txid();
 
     // mine assert tx
-    let assert_result = client.esplora.broadcast(&assert_tx).await;
+    let assert_result = client.chain.broadcast(&assert_tx).await;
     assert!(assert_result.is_ok());
+    simulator.mine_blocks(1);
 
     return (assert_tx, assert_tx_id);
 }
 
 pub async fn create_and_mine_peg_in_confirm_tx(
     client: &BitVMClient,
+    simulator: &Simulator,
     depositor_context: &DepositorContext,
-    verifier_context: &VerifierContext,
+    verifier_contexts: &Vec<VerifierContext>,
     connector_z: &ConnectorZ,
     evm_address: &str,
 ) -> (Transaction, Txid) {
@@ -89,7 +111,7 @@ pub async fn create_and_mine_peg_in_confirm_tx(
     // create peg-in confirm tx
     let funding_address = connector_z.generate_taproot_address();
     let peg_in_confirm_funding_outpoint =
-        generate_stub_outpoint(client, &funding_address, deposit_input_amount).await;
+        simulator.fund_address(&funding_address, deposit_input_amount);
 
     let confirm_input = Input {
         outpoint: peg_in_confirm_funding_outpoint,
@@ -97,13 +119,23 @@ pub async fn create_and_mine_peg_in_confirm_tx(
     };
     let mut peg_in_confirm =
         PegInConfirmTransaction::new(depositor_context, evm_address, confirm_input);
-    peg_in_confirm.pre_sign(verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_confirm.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_confirm
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
     let peg_in_confirm_tx = peg_in_confirm.finalize();
     let peg_in_confirm_tx_id = peg_in_confirm_tx.compute_txid();
 
     // mine peg-in confirm
-    let confirm_result = client.esplora.broadcast(&peg_in_confirm_tx).await;
+    let confirm_result = client.chain.broadcast(&peg_in_confirm_tx).await;
     assert!(confirm_result.is_ok());
+    simulator.mine_blocks(1);
 
     return (peg_in_confirm_tx, peg_in_confirm_tx_id);
 }
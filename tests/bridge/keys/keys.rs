@@ -0,0 +1,149 @@
+use bitcoin::Network;
+
+use bitvm::bridge::{
+    contexts::{
+        base::generate_n_of_n_public_key, depositor::DepositorContext, operator::OperatorContext,
+        public::PublicContext, verifier::VerifierContext,
+    },
+    error::BridgeError,
+    keys::{
+        key_chain::{KeyChain, KeyRole},
+        keystore::Keystore,
+    },
+};
+
+const SEED: [u8; 32] = [0x5e; 32];
+
+#[test]
+fn test_key_chain_derives_fresh_keys_per_role_and_graph() {
+    let key_chain = KeyChain::from_seed(Network::Testnet, &SEED).unwrap();
+
+    let depositor_key_0 = key_chain.derive_public_key(KeyRole::Depositor, 0).unwrap();
+    let depositor_key_1 = key_chain.derive_public_key(KeyRole::Depositor, 1).unwrap();
+    let withdrawer_key_0 = key_chain.derive_public_key(KeyRole::Withdrawer, 0).unwrap();
+    assert_ne!(depositor_key_0, depositor_key_1);
+    assert_ne!(depositor_key_0, withdrawer_key_0);
+
+    // the same seed always derives the same keys
+    let restored_key_chain = KeyChain::from_seed(Network::Testnet, &SEED).unwrap();
+    assert_eq!(
+        restored_key_chain
+            .derive_public_key(KeyRole::Depositor, 1)
+            .unwrap(),
+        depositor_key_1
+    );
+
+    // mainnet keys are never reused on a test network
+    let mainnet_key_chain = KeyChain::from_seed(Network::Bitcoin, &SEED).unwrap();
+    assert_ne!(
+        mainnet_key_chain
+            .derive_public_key(KeyRole::Depositor, 0)
+            .unwrap(),
+        depositor_key_0
+    );
+
+    assert!(matches!(
+        key_chain.derive_keypair(KeyRole::Depositor, 1 << 31),
+        Err(BridgeError::KeyDerivation(_))
+    ));
+}
+
+#[test]
+fn test_contexts_from_derived_keys() {
+    let network = Network::Testnet;
+    let operator_key_chain = KeyChain::from_seed(network, &[0x01; 32]).unwrap();
+    let verifier_key_chains: Vec<KeyChain> = [[0x02; 32], [0x03; 32]]
+        .iter()
+        .map(|seed| KeyChain::from_seed(network, seed).unwrap())
+        .collect();
+    let depositor_key_chain = KeyChain::from_seed(network, &[0x04; 32]).unwrap();
+
+    // the counterparties are only known by their public keys
+    let n_of_n_public_keys: Vec<_> = verifier_key_chains
+        .iter()
+        .map(|key_chain| key_chain.derive_public_key(KeyRole::Verifier, 0).unwrap())
+        .collect();
+    let public_context = PublicContext::new(
+        network,
+        &operator_key_chain
+            .derive_public_key(KeyRole::Operator, 0)
+            .unwrap(),
+        &n_of_n_public_keys,
+    );
+    assert_eq!(
+        (
+            public_context.n_of_n_public_key,
+            public_context.n_of_n_taproot_public_key
+        ),
+        generate_n_of_n_public_key(&n_of_n_public_keys)
+    );
+
+    let verifier_context =
+        VerifierContext::from_seed(network, &[0x03; 32], &public_context.n_of_n_public_keys)
+            .unwrap();
+    assert_eq!(verifier_context.verifier_public_key, n_of_n_public_keys[1]);
+    let operator_context = OperatorContext::from_seed(
+        network,
+        &[0x01; 32],
+        &public_context.n_of_n_public_key,
+        &public_context.n_of_n_taproot_public_key,
+    )
+    .unwrap();
+    assert_eq!(
+        operator_context.operator_public_key,
+        public_context.operator_public_key
+    );
+
+    // a depositor uses fresh keys for every peg-in graph
+    let depositor_contexts: Vec<DepositorContext> = (0..2)
+        .map(|graph_index| {
+            DepositorContext::from_seed(
+                network,
+                &[0x04; 32],
+                graph_index,
+                &public_context.n_of_n_public_key,
+                &public_context.n_of_n_taproot_public_key,
+            )
+            .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        depositor_contexts[1].depositor_public_key,
+        depositor_key_chain
+            .derive_public_key(KeyRole::Depositor, 1)
+            .unwrap()
+    );
+    assert_ne!(
+        depositor_contexts[0].depositor_public_key,
+        depositor_contexts[1].depositor_public_key
+    );
+}
+
+#[test]
+fn test_keystore_encrypts_the_seed() {
+    let keystore = Keystore::new(Network::Testnet, &SEED, "correct horse");
+
+    let json = serde_json::to_string(&keystore).unwrap();
+    assert!(!json.contains(&hex::encode(SEED)));
+
+    assert_eq!(keystore.seed("correct horse").unwrap(), SEED.to_vec());
+    assert!(matches!(
+        keystore.seed("battery staple"),
+        Err(BridgeError::Keystore(_))
+    ));
+
+    let path = std::env::temp_dir().join(format!("bitvm-keystore-{}.json", std::process::id()));
+    keystore.save(&path).unwrap();
+    let loaded_keystore = Keystore::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded_keystore, keystore);
+
+    let key_chain = loaded_keystore.key_chain("correct horse").unwrap();
+    assert_eq!(
+        key_chain.derive_public_key(KeyRole::Operator, 0).unwrap(),
+        KeyChain::from_seed(Network::Testnet, &SEED)
+            .unwrap()
+            .derive_public_key(KeyRole::Operator, 0)
+            .unwrap()
+    );
+}
@@ -1,15 +1,17 @@
 use bitcoin::{consensus::encode::serialize_hex, Amount};
 
 use bitvm::bridge::{
+    commitments::{generate_commit_y_public_key, sign_commit_y, COMMITMENT_VALUE_LENGTH},
     graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
     scripts::generate_pay_to_pubkey_script_address,
     transactions::{
         base::{BaseTransaction, Input},
-        kick_off::KickOffTransaction,
+        commit_y_funding::CommitYFundingTransaction,
+        kick_off::{extract_commit_y, KickOffTransaction},
     },
 };
 
-use crate::bridge::helper::generate_stub_outpoint;
+use crate::bridge::helper::{generate_stub_outpoint, PEG_OUT_GRAPH_ID};
 
 use super::super::setup::setup_test;
 
@@ -17,7 +19,7 @@ use super::super::setup::setup_test;
 async fn test_kick_off_tx() {
     let (client, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
 
-    let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT;
+    let input_amount_raw = INITIAL_AMOUNT + FEE_AMOUNT * 2;
     let input_amount = Amount::from_sat(input_amount_raw);
     let funding_address = generate_pay_to_pubkey_script_address(
         operator_context.network,
@@ -30,11 +32,37 @@ async fn test_kick_off_tx() {
         amount: input_amount,
     };
 
-    let kick_off_tx = KickOffTransaction::new(&operator_context, input);
+    // the operator moves its funds to the Commit Y address of the graph first
+    let commit_y_public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let commit_y_funding_tx =
+        CommitYFundingTransaction::new(&operator_context, input, &commit_y_public_key);
+    let result = client
+        .chain
+        .broadcast(&commit_y_funding_tx.finalize())
+        .await;
+    assert!(result.is_ok());
+
+    let mut kick_off_tx = KickOffTransaction::new(
+        &operator_context,
+        commit_y_funding_tx.kick_off_input(),
+        &commit_y_public_key,
+    );
+    let y = [0x5a; COMMITMENT_VALUE_LENGTH];
+    kick_off_tx
+        .add_commit_y(&sign_commit_y(&operator_context, PEG_OUT_GRAPH_ID, &y))
+        .unwrap();
 
+    // the commitment only verifies with the key of the graph
     let tx = kick_off_tx.finalize();
+    assert_eq!(extract_commit_y(&tx, &commit_y_public_key).unwrap(), y);
+    assert!(extract_commit_y(
+        &tx,
+        &generate_commit_y_public_key(&operator_context, "OTHER_PEG_OUT_GRAPH_ID")
+    )
+    .is_err());
+
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));
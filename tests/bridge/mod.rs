@@ -2,11 +2,15 @@ pub mod assert;
 pub mod burn;
 pub mod challenge;
 pub mod client;
+pub mod commitments;
 pub mod disprove;
+pub mod fee;
 pub mod helper;
 pub mod integration;
+pub mod keys;
 pub mod kick_off;
 pub mod peg_in;
+pub mod psbt;
 pub mod serialization;
 pub mod setup;
 pub mod take1;
@@ -6,6 +6,7 @@ use bitvm::bridge::{
     transactions::{
         base::{BaseTransaction, Input},
         peg_in_confirm::PegInConfirmTransaction,
+        pre_signed_musig2::has_signatures_of,
     },
 };
 
@@ -17,7 +18,7 @@ async fn test_peg_in_confirm_tx() {
         client,
         depositor_context,
         _,
-        verifier_context,
+        verifier_contexts,
         _,
         _,
         _,
@@ -37,12 +38,68 @@ async fn test_peg_in_confirm_tx() {
     let mut peg_in_confirm_tx =
         PegInConfirmTransaction::new(&depositor_context, &evm_address, Input { outpoint, amount });
 
-    peg_in_confirm_tx.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_confirm_tx.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        peg_in_confirm_tx
+            .pre_sign(verifier_context, secret_nonces)
+            .unwrap();
+    }
     let tx = peg_in_confirm_tx.finalize();
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn test_peg_in_confirm_pre_sign_consumes_the_secret_nonce() {
+    let (
+        client,
+        depositor_context,
+        _,
+        verifier_contexts,
+        _,
+        _,
+        _,
+        _,
+        connector_z,
+        _,
+        _,
+        _,
+        _,
+        evm_address,
+    ) = setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let outpoint =
+        generate_stub_outpoint(&client, &connector_z.generate_taproot_address(), amount).await;
+
+    let mut peg_in_confirm_tx =
+        PegInConfirmTransaction::new(&depositor_context, &evm_address, Input { outpoint, amount });
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| peg_in_confirm_tx.push_nonces(verifier_context))
+        .collect();
+    let mut unsigned_peg_in_confirm_tx: PegInConfirmTransaction =
+        serde_json::from_str(&serde_json::to_string(&peg_in_confirm_tx).unwrap()).unwrap();
+
+    peg_in_confirm_tx
+        .pre_sign(&verifier_contexts[0], &mut secret_nonces[0])
+        .unwrap();
+    assert!(secret_nonces[0].is_empty());
+
+    // the copy lost the partial signature, the nonce must not be used for a second one
+    assert!(unsigned_peg_in_confirm_tx
+        .pre_sign(&verifier_contexts[0], &mut secret_nonces[0])
+        .is_err());
+    assert!(!has_signatures_of(
+        &unsigned_peg_in_confirm_tx,
+        &verifier_contexts[0]
+    ));
+}
@@ -37,7 +37,7 @@ async fn test_peg_in_deposit_tx() {
 
     let tx = peg_in_deposit_tx.finalize();
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));
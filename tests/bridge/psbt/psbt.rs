@@ -0,0 +1,268 @@
+use bitcoin::{
+    absolute,
+    key::Secp256k1,
+    secp256k1::Message,
+    sighash::{Prevouts, SighashCache},
+    taproot::LeafVersion,
+    transaction, Amount, EcdsaSighashType, OutPoint, PublicKey, ScriptBuf, TapLeafHash,
+    TapSighashType, Transaction, TxIn, TxOut, Witness,
+};
+
+use bitvm::bridge::{
+    commitments::{generate_commit_y_public_key, sign_commit_y, COMMITMENT_VALUE_LENGTH},
+    connectors::{connector::TaprootConnector, connector_y::ConnectorY},
+    graphs::base::{FEE_AMOUNT, INITIAL_AMOUNT},
+    scripts::{
+        generate_p2wpkh_address, generate_pay_to_pubkey_script_address,
+        UNSPENDABLE_TAPROOT_PUBLIC_KEY,
+    },
+    transactions::{
+        base::Input,
+        kick_off::KickOffTransaction,
+        peg_in_deposit::PegInDepositTransaction,
+        pre_signed::PreSignedTransaction,
+        psbt::{export_psbt, import_psbt},
+    },
+};
+
+use super::super::{
+    helper::{generate_stub_outpoint, PEG_OUT_GRAPH_ID},
+    setup::setup_test,
+};
+
+#[tokio::test]
+async fn test_psbt_taproot_input_signed_externally() {
+    let (client, _, operator_context, _, _, _, _, _, _, _, _, _, _, _) = setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let commit_y_public_key = generate_commit_y_public_key(&operator_context, PEG_OUT_GRAPH_ID);
+    let connector_y = ConnectorY::new(
+        operator_context.network,
+        &operator_context.operator_taproot_public_key,
+        &commit_y_public_key,
+    );
+    let outpoint =
+        generate_stub_outpoint(&client, &connector_y.generate_taproot_address(), amount).await;
+    let mut kick_off = KickOffTransaction::new(
+        &operator_context,
+        Input { outpoint, amount },
+        &commit_y_public_key,
+    );
+    let presigned_witness = kick_off.tx().input[0].witness.clone();
+
+    // presigned inputs are exported finalized
+    let psbt = export_psbt(&kick_off);
+    assert_eq!(
+        psbt.inputs[0].final_script_witness,
+        Some(presigned_witness.clone())
+    );
+    assert_eq!(
+        psbt.inputs[0].witness_utxo,
+        Some(kick_off.prev_outs()[0].clone())
+    );
+
+    // an external signer signs the leaf script
+    kick_off.tx_mut().input[0].witness = Witness::default();
+    let mut psbt = export_psbt(&kick_off);
+    assert!(psbt.inputs[0].final_script_witness.is_none());
+    assert_eq!(
+        psbt.inputs[0].tap_internal_key,
+        Some(*UNSPENDABLE_TAPROOT_PUBLIC_KEY)
+    );
+    let script = kick_off.prev_scripts()[0].clone();
+    assert_eq!(
+        psbt.inputs[0].tap_scripts.values().next(),
+        Some(&(script.clone(), LeafVersion::TapScript))
+    );
+
+    let leaf_hash = TapLeafHash::from_script(&script, LeafVersion::TapScript);
+    let sighash = SighashCache::new(&psbt.unsigned_tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&[psbt.inputs[0].witness_utxo.clone().unwrap()]),
+            leaf_hash,
+            TapSighashType::All,
+        )
+        .unwrap();
+    let signature = Secp256k1::new()
+        .sign_schnorr_no_aux_rand(&Message::from(sighash), &operator_context.operator_keypair);
+    psbt.inputs[0].tap_script_sigs.insert(
+        (operator_context.operator_taproot_public_key, leaf_hash),
+        bitcoin::taproot::Signature {
+            signature,
+            sighash_type: TapSighashType::All,
+        },
+    );
+
+    import_psbt(&mut kick_off, &psbt).unwrap();
+    assert_eq!(kick_off.tx().input[0].witness, presigned_witness);
+    kick_off
+        .add_commit_y(&sign_commit_y(
+            &operator_context,
+            PEG_OUT_GRAPH_ID,
+            &[0u8; COMMITMENT_VALUE_LENGTH],
+        ))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_psbt_p2wsh_input_signed_externally() {
+    let (client, depositor_context, operator_context, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    )
+    .await;
+    let mut peg_in_deposit =
+        PegInDepositTransaction::new(&depositor_context, &evm_address, Input { outpoint, amount });
+    let presigned_witness = peg_in_deposit.tx().input[0].witness.clone();
+
+    // an external signer signs the witness script
+    peg_in_deposit.tx_mut().input[0].witness = Witness::default();
+    let mut psbt = export_psbt(&peg_in_deposit);
+    let script = psbt.inputs[0].witness_script.clone().unwrap();
+    assert_eq!(script, peg_in_deposit.prev_scripts()[0]);
+
+    let sighash = SighashCache::new(&psbt.unsigned_tx)
+        .p2wsh_signature_hash(0, &script, amount, EcdsaSighashType::All)
+        .unwrap();
+    let signature = Secp256k1::new().sign_ecdsa(
+        &Message::from(sighash),
+        &depositor_context.depositor_keypair.secret_key(),
+    );
+    psbt.inputs[0].partial_sigs.insert(
+        depositor_context.depositor_public_key,
+        bitcoin::ecdsa::Signature {
+            signature,
+            sighash_type: EcdsaSighashType::All,
+        },
+    );
+
+    import_psbt(&mut peg_in_deposit, &psbt).unwrap();
+    assert_eq!(peg_in_deposit.tx().input[0].witness, presigned_witness);
+
+    // signatures of another key do not complete the input
+    peg_in_deposit.tx_mut().input[0].witness = Witness::default();
+    let mut psbt = export_psbt(&peg_in_deposit);
+    psbt.inputs[0].partial_sigs.insert(
+        PublicKey::new(operator_context.operator_keypair.public_key()),
+        bitcoin::ecdsa::Signature {
+            signature,
+            sighash_type: EcdsaSighashType::All,
+        },
+    );
+    import_psbt(&mut peg_in_deposit, &psbt).unwrap();
+    assert!(peg_in_deposit.tx().input[0].witness.is_empty());
+
+    // finalized witnesses are verified before they are taken over
+    let mut invalid_psbt = export_psbt(&peg_in_deposit);
+    let mut invalid_witness = Witness::new();
+    invalid_witness.push_ecdsa_signature(&bitcoin::ecdsa::Signature {
+        signature: Secp256k1::new().sign_ecdsa(
+            &Message::from(sighash),
+            &operator_context.operator_keypair.secret_key(),
+        ),
+        sighash_type: EcdsaSighashType::All,
+    });
+    invalid_witness.push(script.as_bytes());
+    invalid_psbt.inputs[0].final_script_witness = Some(invalid_witness);
+    assert!(import_psbt(&mut peg_in_deposit, &invalid_psbt).is_err());
+    assert!(peg_in_deposit.tx().input[0].witness.is_empty());
+
+    // PSBTs of other transactions are rejected
+    let mut other_psbt = psbt.clone();
+    other_psbt.unsigned_tx.output[0].value = Amount::from_sat(INITIAL_AMOUNT);
+    assert!(import_psbt(&mut peg_in_deposit, &other_psbt).is_err());
+}
+
+struct P2wpkhSpend {
+    tx: Transaction,
+    prev_outs: Vec<TxOut>,
+    prev_scripts: Vec<ScriptBuf>,
+}
+
+impl PreSignedTransaction for P2wpkhSpend {
+    fn tx(&self) -> &Transaction { &self.tx }
+
+    fn tx_mut(&mut self) -> &mut Transaction { &mut self.tx }
+
+    fn prev_outs(&self) -> &Vec<TxOut> { &self.prev_outs }
+
+    fn prev_scripts(&self) -> &Vec<ScriptBuf> { &self.prev_scripts }
+}
+
+#[tokio::test]
+async fn test_psbt_p2wpkh_input_takes_the_signature_of_the_spent_key() {
+    let (_, depositor_context, operator_context, _, _, _, _, _, _, _, _, _, _, _) =
+        setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+    let script_pubkey = generate_p2wpkh_address(
+        depositor_context.network,
+        &depositor_context.depositor_public_key,
+    )
+    .script_pubkey();
+    let mut spend = P2wpkhSpend {
+        tx: Transaction {
+            version: transaction::Version(2),
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(INITIAL_AMOUNT),
+                script_pubkey: script_pubkey.clone(),
+            }],
+        },
+        prev_outs: vec![TxOut {
+            value: amount,
+            script_pubkey: script_pubkey.clone(),
+        }],
+        prev_scripts: vec![script_pubkey.clone()],
+    };
+
+    let mut psbt = export_psbt(&spend);
+    let sighash = SighashCache::new(&psbt.unsigned_tx)
+        .p2wpkh_signature_hash(0, &script_pubkey, amount, EcdsaSighashType::All)
+        .unwrap();
+    let secp = Secp256k1::new();
+    let operator_signature = bitcoin::ecdsa::Signature {
+        signature: secp.sign_ecdsa(
+            &Message::from(sighash),
+            &operator_context.operator_keypair.secret_key(),
+        ),
+        sighash_type: EcdsaSighashType::All,
+    };
+
+    // a signature of another key does not sign the input
+    psbt.inputs[0].partial_sigs.insert(
+        PublicKey::new(operator_context.operator_keypair.public_key()),
+        operator_signature,
+    );
+    import_psbt(&mut spend, &psbt).unwrap();
+    assert!(spend.tx().input[0].witness.is_empty());
+
+    psbt.inputs[0].partial_sigs.insert(
+        depositor_context.depositor_public_key,
+        bitcoin::ecdsa::Signature {
+            signature: secp.sign_ecdsa(
+                &Message::from(sighash),
+                &depositor_context.depositor_keypair.secret_key(),
+            ),
+            sighash_type: EcdsaSighashType::All,
+        },
+    );
+    import_psbt(&mut spend, &psbt).unwrap();
+    assert_eq!(
+        spend.tx().input[0].witness.nth(1),
+        Some(depositor_context.depositor_public_key.to_bytes().as_slice())
+    );
+}
@@ -11,7 +11,7 @@ use super::super::{helper::generate_stub_outpoint, setup::setup_test};
 
 #[tokio::test]
 async fn test_assert_tx_serialization() {
-    let (client, _, operator_context, verifier_context, _, _, connector_b, _, _, _, _, _, _, _) =
+    let (client, _, operator_context, verifier_contexts, _, _, connector_b, _, _, _, _, _, _, _) =
         setup_test().await;
 
     let amount = Amount::from_sat(ONE_HUNDRED * 2 / 100);
@@ -20,7 +20,14 @@ async fn test_assert_tx_serialization() {
 
     let mut assert_tx = AssertTransaction::new(&operator_context, Input { outpoint, amount });
 
-    assert_tx.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| assert_tx.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        assert_tx.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
 
     let json = serialize(&assert_tx);
     assert!(json.len() > 0);
@@ -1,10 +1,12 @@
 use bitcoin::Amount;
 
 use bitvm::bridge::{
+    error::BridgeError,
     graphs::{
-        base::{FEE_AMOUNT, INITIAL_AMOUNT},
+        base::{FEE_AMOUNT, GRAPH_VERSION, INITIAL_AMOUNT},
         peg_in::PegInGraph,
     },
+    parameters::BridgeParameters,
     scripts::generate_pay_to_pubkey_script_address,
     serialization::{deserialize, serialize},
     transactions::base::Input,
@@ -37,3 +39,69 @@ async fn test_peg_in_graph_serialization() {
     let deserialized_peg_in_graph = deserialize::<PegInGraph>(&json);
     assert!(peg_in_graph == deserialized_peg_in_graph);
 }
+
+#[tokio::test]
+async fn test_peg_in_graph_keeps_its_parameters() {
+    let (client, mut depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+
+    let outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    )
+    .await;
+
+    let default_parameters = BridgeParameters::new(depositor_context.network);
+    depositor_context.parameters.peg_in_refund_timelock =
+        default_parameters.peg_in_refund_timelock + 10;
+    let peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+
+    // the graph is read back with the parameters it was created with, not the defaults
+    let deserialized_peg_in_graph = deserialize::<PegInGraph>(&serialize(&peg_in_graph));
+    assert_eq!(
+        *deserialized_peg_in_graph.parameters(),
+        depositor_context.parameters
+    );
+    assert_ne!(*deserialized_peg_in_graph.parameters(), default_parameters);
+    assert!(deserialized_peg_in_graph
+        .verify(&depositor_context.n_of_n_taproot_public_key)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_peg_in_graph_of_another_version_is_rejected() {
+    let (client, depositor_context, _, _, _, _, _, _, _, _, _, _, _, evm_address) =
+        setup_test().await;
+
+    let amount = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
+
+    let outpoint = generate_stub_outpoint(
+        &client,
+        &generate_pay_to_pubkey_script_address(
+            depositor_context.network,
+            &depositor_context.depositor_public_key,
+        ),
+        amount,
+    )
+    .await;
+
+    let peg_in_graph =
+        PegInGraph::new(&depositor_context, Input { outpoint, amount }, &evm_address);
+
+    let json = serialize(&peg_in_graph).replace(
+        &format!("\"version\":\"{}\"", GRAPH_VERSION),
+        "\"version\":\"0.1\"",
+    );
+    let deserialized_peg_in_graph = deserialize::<PegInGraph>(&json);
+    assert!(matches!(
+        deserialized_peg_in_graph.verify(&depositor_context.n_of_n_taproot_public_key),
+        Err(BridgeError::InvalidGraph(_))
+    ));
+}
@@ -1,10 +1,12 @@
 use bitcoin::Amount;
 
 use bitvm::bridge::{
+    commitments::generate_commit_y_public_key,
+    connectors::{connector::TaprootConnector, connector_y::ConnectorY},
     graphs::{
         base::{FEE_AMOUNT, INITIAL_AMOUNT},
         peg_in::PegInGraph,
-        peg_out::PegOutGraph,
+        peg_out::{generate_id, PegOutGraph},
     },
     scripts::generate_pay_to_pubkey_script_address,
     serialization::{deserialize, serialize},
@@ -37,10 +39,15 @@ async fn test_peg_out_graph_serialization() {
 
     let kick_off_outpoint = generate_stub_outpoint(
         &client,
-        &generate_pay_to_pubkey_script_address(
+        &ConnectorY::new(
             operator_context.network,
-            &operator_context.operator_public_key,
-        ),
+            &operator_context.operator_taproot_public_key,
+            &generate_commit_y_public_key(
+                &operator_context,
+                &generate_id(&peg_in_graph, &operator_context.operator_public_key),
+            ),
+        )
+        .generate_taproot_address(),
         kick_off_amount,
     )
     .await;
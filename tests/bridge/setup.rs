@@ -1,26 +1,110 @@
-use bitcoin::Network;
+use bitcoin::{key::Keypair, Address, Network};
 
 use bitvm::bridge::{
-    client::client::BitVMClient,
+    client::{
+        chain::{
+            base::ChainBackend,
+            esplora::{Esplora, MUTINYNET_ESPLORA_URL},
+        },
+        client::BitVMClient,
+    },
     connectors::{
         connector_0::Connector0, connector_1::Connector1, connector_2::Connector2,
         connector_3::Connector3, connector_a::ConnectorA, connector_b::ConnectorB,
         connector_c::ConnectorC, connector_z::ConnectorZ,
     },
     contexts::{
-        base::generate_keys_from_secret, depositor::DepositorContext, operator::OperatorContext,
-        verifier::VerifierContext, withdrawer::WithdrawerContext,
-    },
-    graphs::base::{
-        DEPOSITOR_SECRET, EVM_ADDRESS, N_OF_N_SECRET, OPERATOR_SECRET, WITHDRAWER_SECRET,
+        base::{generate_keys_from_keypair, generate_n_of_n_public_key},
+        depositor::DepositorContext,
+        operator::OperatorContext,
+        verifier::VerifierContext,
+        withdrawer::WithdrawerContext,
     },
+    keys::key_chain::{KeyChain, KeyRole},
+    registry::ParticipantRegistry,
+    scripts::generate_pay_to_pubkey_script_address,
 };
 
+pub const EVM_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+// Every test participant derives its keys from its own BIP-32 seed
+const DEPOSITOR_SEED: [u8; 32] = [0x10; 32];
+const OPERATOR_SEED: [u8; 32] = [0x20; 32];
+const OPERATOR_1_SEED: [u8; 32] = [0x21; 32];
+const VERIFIER_0_SEED: [u8; 32] = [0x30; 32];
+const VERIFIER_1_SEED: [u8; 32] = [0x31; 32];
+const WITHDRAWER_SEED: [u8; 32] = [0x40; 32];
+
+fn derive_test_keypair(seed: &[u8; 32], role: KeyRole) -> Keypair {
+    KeyChain::from_seed(Network::Testnet, seed)
+        .unwrap()
+        .derive_keypair(role, 0)
+        .unwrap()
+}
+
+// The clients derive the depositor and withdrawer keys of every graph from the key chains
+pub fn depositor_key_chain() -> KeyChain {
+    KeyChain::from_seed(Network::Testnet, &DEPOSITOR_SEED).unwrap()
+}
+
+pub fn withdrawer_key_chain() -> KeyChain {
+    KeyChain::from_seed(Network::Testnet, &WITHDRAWER_SEED).unwrap()
+}
+
+// The keys of the first graph of a client
+pub fn depositor_keypair() -> Keypair { derive_test_keypair(&DEPOSITOR_SEED, KeyRole::Depositor) }
+
+pub fn operator_keypair() -> Keypair { derive_test_keypair(&OPERATOR_SEED, KeyRole::Operator) }
+
+// A second operator, for the tests of multiple registered operators
+pub fn operator_1_keypair() -> Keypair { derive_test_keypair(&OPERATOR_1_SEED, KeyRole::Operator) }
+
+pub fn verifier_0_keypair() -> Keypair { derive_test_keypair(&VERIFIER_0_SEED, KeyRole::Verifier) }
+
+pub fn verifier_1_keypair() -> Keypair { derive_test_keypair(&VERIFIER_1_SEED, KeyRole::Verifier) }
+
+pub fn withdrawer_keypair() -> Keypair {
+    derive_test_keypair(&WITHDRAWER_SEED, KeyRole::Withdrawer)
+}
+
+// Where the peg-outs of the tests pay the withdrawer
+pub fn withdrawer_destination_address(network: Network) -> Address {
+    generate_pay_to_pubkey_script_address(
+        network,
+        &generate_keys_from_keypair(network, &withdrawer_keypair()).2,
+    )
+}
+
 pub async fn setup_test() -> (
     BitVMClient,
     DepositorContext,
     OperatorContext,
-    VerifierContext,
+    Vec<VerifierContext>,
+    WithdrawerContext,
+    ConnectorA,
+    ConnectorB,
+    ConnectorC,
+    ConnectorZ,
+    Connector0,
+    Connector1,
+    Connector2,
+    Connector3,
+    String,
+) {
+    // the test network addresses are funded on mutinynet
+    setup_test_with_chain(Some(Box::new(
+        Esplora::with_url(MUTINYNET_ESPLORA_URL).unwrap(),
+    )))
+    .await
+}
+
+pub async fn setup_test_with_chain(
+    chain_backend: Option<Box<dyn ChainBackend>>,
+) -> (
+    BitVMClient,
+    DepositorContext,
+    OperatorContext,
+    Vec<VerifierContext>,
     WithdrawerContext,
     ConnectorA,
     ConnectorB,
@@ -34,58 +118,92 @@ pub async fn setup_test() -> (
 ) {
     let network = Network::Testnet;
 
-    let operator_keys = generate_keys_from_secret(network, OPERATOR_SECRET);
-    let verifier_keys = generate_keys_from_secret(network, N_OF_N_SECRET);
+    let depositor_keypair = depositor_keypair();
+    let operator_keypair = operator_keypair();
+    let verifier_0_keypair = verifier_0_keypair();
+    let verifier_1_keypair = verifier_1_keypair();
+    let withdrawer_keypair = withdrawer_keypair();
+
+    let operator_keys = generate_keys_from_keypair(network, &operator_keypair);
+    let verifier_0_keys = generate_keys_from_keypair(network, &verifier_0_keypair);
+    let verifier_1_keys = generate_keys_from_keypair(network, &verifier_1_keypair);
+    let n_of_n_public_keys = vec![verifier_0_keys.2, verifier_1_keys.2];
+    let (n_of_n_public_key, n_of_n_taproot_public_key) =
+        generate_n_of_n_public_key(&n_of_n_public_keys);
 
-    let depositor_context = DepositorContext::new(
+    let depositor_context = DepositorContext::from_keypair(
+        network,
+        &depositor_keypair,
+        &n_of_n_public_key,
+        &n_of_n_taproot_public_key,
+    );
+    let operator_context = OperatorContext::from_keypair(
         network,
-        DEPOSITOR_SECRET,
-        &verifier_keys.2,
-        &verifier_keys.3,
+        &operator_keypair,
+        &n_of_n_public_key,
+        &n_of_n_taproot_public_key,
     );
-    let operator_context =
-        OperatorContext::new(network, OPERATOR_SECRET, &verifier_keys.2, &verifier_keys.3);
-    let verifier_context =
-        VerifierContext::new(network, N_OF_N_SECRET, &operator_keys.2, &operator_keys.3);
-    let withdrawer_context = WithdrawerContext::new(
+    let verifier_contexts = vec![
+        VerifierContext::from_keypair(network, &verifier_0_keypair, &n_of_n_public_keys).unwrap(),
+        VerifierContext::from_keypair(network, &verifier_1_keypair, &n_of_n_public_keys).unwrap(),
+    ];
+    let withdrawer_context = WithdrawerContext::from_keypair(
         network,
-        WITHDRAWER_SECRET,
-        &verifier_keys.2,
-        &verifier_keys.3,
+        &withdrawer_keypair,
+        &n_of_n_public_key,
+        &n_of_n_taproot_public_key,
     );
 
+    let registry =
+        ParticipantRegistry::new(network, &vec![operator_keys.2], &n_of_n_public_keys).unwrap();
     let client = BitVMClient::new(
-        network,
-        Some(DEPOSITOR_SECRET),
-        Some(OPERATOR_SECRET),
-        Some(N_OF_N_SECRET),
-        Some(WITHDRAWER_SECRET),
+        &registry,
+        Some(&depositor_key_chain()),
+        Some(&operator_keypair),
+        Some(&verifier_0_keypair),
+        Some(&withdrawer_key_chain()),
+        None,
+        chain_backend,
     )
-    .await;
+    .await
+    .unwrap();
 
     let connector_a = ConnectorA::new(
         network,
         &operator_context.operator_taproot_public_key,
-        &verifier_context.n_of_n_taproot_public_key,
+        &n_of_n_taproot_public_key,
     );
-    let connector_b = ConnectorB::new(network, &verifier_context.n_of_n_taproot_public_key);
-    let connector_c = ConnectorC::new(network, &verifier_context.n_of_n_taproot_public_key);
+    let connector_b = ConnectorB::new(
+        network,
+        &n_of_n_taproot_public_key,
+        operator_context.parameters.burn_timelock,
+    );
+    let connector_c = ConnectorC::new(network, &n_of_n_taproot_public_key);
     let connector_z = ConnectorZ::new(
         network,
         EVM_ADDRESS,
         &depositor_context.depositor_taproot_public_key,
-        &verifier_context.n_of_n_taproot_public_key,
+        &n_of_n_taproot_public_key,
+        depositor_context.parameters.peg_in_refund_timelock,
+    );
+    let connector_0 = Connector0::new(network, &n_of_n_taproot_public_key);
+    let connector_1 = Connector1::new(
+        network,
+        &operator_context.operator_public_key,
+        operator_context.parameters.take1_timelock,
+    );
+    let connector_2 = Connector2::new(
+        network,
+        &operator_context.operator_public_key,
+        operator_context.parameters.take2_timelock,
     );
-    let connector_0 = Connector0::new(network, &verifier_context.n_of_n_public_key);
-    let connector_1 = Connector1::new(network, &operator_context.operator_public_key);
-    let connector_2 = Connector2::new(network, &operator_context.operator_public_key);
-    let connector_3 = Connector3::new(network, &verifier_context.n_of_n_public_key);
+    let connector_3 = Connector3::new(network, &n_of_n_taproot_public_key);
 
     return (
         client,
         depositor_context,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         withdrawer_context,
         connector_a,
         connector_b,
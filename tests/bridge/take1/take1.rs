@@ -17,7 +17,7 @@ async fn test_take1_tx() {
         client,
         _,
         operator_context,
-        verifier_context,
+        verifier_contexts,
         _,
         connector_a,
         connector_b,
@@ -31,7 +31,7 @@ async fn test_take1_tx() {
     ) = setup_test().await;
 
     let input_value0 = Amount::from_sat(INITIAL_AMOUNT + FEE_AMOUNT);
-    let funding_utxo_address0 = connector_0.generate_address();
+    let funding_utxo_address0 = connector_0.generate_taproot_address();
     let funding_outpoint0 =
         generate_stub_outpoint(&client, &funding_utxo_address0, input_value0).await;
 
@@ -70,10 +70,17 @@ async fn test_take1_tx() {
         },
     );
 
-    take1_tx.pre_sign(&verifier_context);
+    let mut secret_nonces: Vec<_> = verifier_contexts
+        .iter()
+        .map(|verifier_context| take1_tx.push_nonces(verifier_context))
+        .collect();
+    for (verifier_context, secret_nonces) in verifier_contexts.iter().zip(secret_nonces.iter_mut())
+    {
+        take1_tx.pre_sign(verifier_context, secret_nonces).unwrap();
+    }
     let tx = take1_tx.finalize();
     println!("Script Path Spend Transaction: {:?}\n", tx);
-    let result = client.esplora.broadcast(&tx).await;
+    let result = client.chain.broadcast(&tx).await;
     println!("Txid: {:?}", tx.compute_txid());
     println!("Broadcast result: {:?}\n", result);
     println!("Transaction hex: \n{}", serialize_hex(&tx));